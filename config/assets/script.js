@@ -1,13 +1,19 @@
 /**
  * Vellum - Minimal Panel Navigation
- * Simple show/hide logic for pre-rendered panels
+ * Simple show/hide logic for pre-rendered panels, kept in sync with the
+ * location hash so panels and headings can be linked and the back button works
  */
 
 document.addEventListener('DOMContentLoaded', function() {
     const buttons = document.querySelectorAll('#nav-buttons button');
-    const dropdown = document.getElementById('dropdown');
+    const dropdowns = document.querySelectorAll('#nav-buttons select');
     const panels = document.querySelectorAll('.panel');
 
+    // Layouts without navigation show every panel already
+    if (buttons.length === 0 && dropdowns.length === 0) {
+        return;
+    }
+
     function showPanel(panelId) {
         // Hide all panels
         panels.forEach(p => p.classList.add('hidden'));
@@ -18,34 +24,84 @@ document.addEventListener('DOMContentLoaded', function() {
             panel.classList.remove('hidden');
         }
 
-        // Reset button active states
-        buttons.forEach(b => b.classList.remove('active'));
+        // Mark the button or dropdown option of the panel
+        buttons.forEach(b => {
+            const active = b.dataset.panel === panelId;
+            b.classList.toggle('active', active);
+            const group = b.closest('details');
+            if (active && group) group.open = true;
+        });
+        dropdowns.forEach(d => {
+            const index = Array.from(d.options).findIndex(o => o.dataset.panel === panelId);
+            d.selectedIndex = Math.max(index, 0);
+        });
+    }
+
+    // The panel (and heading inside it) a location hash points at
+    function findTarget(hash) {
+        const id = decodeURIComponent(hash.slice(1));
+        if (!id) return null;
+        if (document.getElementById('panel-' + id)) {
+            return { panelId: id, element: null };
+        }
+        const element = document.getElementById(id);
+        const panel = element && element.closest('.panel');
+        return panel ? { panelId: panel.id.slice('panel-'.length), element: element } : null;
+    }
 
-        // Scroll to top
+    function showHash() {
+        const target = findTarget(location.hash);
+        if (!target) return false;
+        showPanel(target.panelId);
+        if (target.element) {
+            target.element.scrollIntoView();
+        } else {
+            window.scrollTo(0, 0);
+        }
+        return true;
+    }
+
+    // Show a panel from the navigation and add it to the history
+    function navigate(panelId) {
+        showPanel(panelId);
+        history.pushState(null, '', '#' + encodeURIComponent(panelId));
         window.scrollTo({ top: 0, behavior: 'smooth' });
     }
 
     // Set up button click handlers
     buttons.forEach(btn => {
-        btn.onclick = () => {
-            showPanel(btn.dataset.panel);
-            btn.classList.add('active');
-            if (dropdown) dropdown.selectedIndex = 0;
-        };
+        btn.onclick = () => navigate(btn.dataset.panel);
     });
 
-    // Set up dropdown change handler
-    if (dropdown) {
+    // Set up dropdown change handlers
+    dropdowns.forEach(dropdown => {
         dropdown.onchange = function() {
             const option = this.options[this.selectedIndex];
             if (option && option.dataset.panel) {
-                showPanel(option.dataset.panel);
+                navigate(option.dataset.panel);
             }
         };
+    });
+
+    // The first panel, shown while the location has no hash
+    function showFirst() {
+        const first = buttons.length > 0
+            ? buttons[0].dataset.panel
+            : panels.length > 0 && panels[0].id.slice('panel-'.length);
+        if (first) {
+            showPanel(first);
+            window.scrollTo(0, 0);
+        }
     }
 
-    // Show first panel on load
-    if (buttons.length > 0) {
-        buttons[0].click();
+    // Back and forward buttons, and links to panels or headings; going back
+    // to the entry the page was opened with shows the first panel again
+    window.addEventListener('popstate', () => {
+        if (!showHash()) showFirst();
+    });
+
+    // Show the linked panel on load, or the first one
+    if (!showHash()) {
+        showFirst();
     }
 });
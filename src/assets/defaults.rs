@@ -0,0 +1,48 @@
+//! Stock assets compiled into the binary.
+//!
+//! Used whenever a project does not provide its own files, so vellum works
+//! without `just install` having copied anything to the config directory.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+pub const STYLE_CSS: &str = include_str!("../../config/assets/style.css");
+pub const SCRIPT_JS: &str = include_str!("../../config/assets/script.js");
+pub const TEMPLATE_HTML: &str = include_str!("../../config/assets/template.html");
+pub const LOGO_PNG: &[u8] = include_bytes!("../../config/assets/logo.png");
+
+/// All default asset files with their names below `assets/`.
+pub const FILES: &[(&str, &[u8])] = &[
+    ("style.css", STYLE_CSS.as_bytes()),
+    ("script.js", SCRIPT_JS.as_bytes()),
+    ("template.html", TEMPLATE_HTML.as_bytes()),
+    ("logo.png", LOGO_PNG),
+];
+
+/// The default logo as a data URI.
+pub fn logo_data_uri() -> String {
+    format!("data:image/png;base64,{}", STANDARD.encode(LOGO_PNG))
+}
+
+/// Look up a default text asset by file name.
+pub fn text_file(name: &str) -> Option<&'static str> {
+    match name {
+        "style.css" => Some(STYLE_CSS),
+        "script.js" => Some(SCRIPT_JS),
+        "template.html" => Some(TEMPLATE_HTML),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults_are_complete() {
+        assert!(TEMPLATE_HTML.contains("{{content}}"));
+        assert!(LOGO_PNG.starts_with(&[0x89, b'P', b'N', b'G']));
+        assert!(logo_data_uri().starts_with("data:image/png;base64,"));
+        assert_eq!(text_file("style.css"), Some(STYLE_CSS));
+        assert!(text_file("logo.png").is_none());
+    }
+}
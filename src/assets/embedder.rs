@@ -2,6 +2,9 @@ use base64::{engine::general_purpose::STANDARD, Engine};
 use std::path::Path;
 use thiserror::Error;
 
+use super::mime::{mime_from_extension, sniff_mime};
+use super::optimize::{optimize_image, ImageOptions};
+
 #[derive(Debug, Error)]
 pub enum EmbedError {
     #[error("Failed to read image file '{path}': {source}")]
@@ -12,9 +15,34 @@ pub enum EmbedError {
 
     #[error("Unsupported image format: {0}")]
     UnsupportedFormat(String),
+
+    #[error("Unknown theme '{0}'")]
+    UnknownTheme(String),
+
+    #[error("Invalid theme '{theme}': {message}")]
+    ThemeError { theme: String, message: String },
+}
+
+/// An image embedded as a data URI, with its size before and after optimisation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedImage {
+    pub data_uri: String,
+    pub mime: &'static str,
+    pub original_size: usize,
+    pub optimized_size: usize,
+    /// Set when the file extension does not match the content
+    pub warning: Option<String>,
 }
 
 pub fn embed_image<P: AsRef<Path>>(path: P) -> Result<String, EmbedError> {
+    embed_image_with(path, &ImageOptions::default()).map(|image| image.data_uri)
+}
+
+/// Embed an image, optimising it first if `options` enable it.
+pub fn embed_image_with<P: AsRef<Path>>(
+    path: P,
+    options: &ImageOptions,
+) -> Result<EmbeddedImage, EmbedError> {
     let path = path.as_ref();
     let path_str = path.display().to_string();
 
@@ -23,19 +51,46 @@ pub fn embed_image<P: AsRef<Path>>(path: P) -> Result<String, EmbedError> {
         source: e,
     })?;
 
-    let mime = match path.extension().and_then(|e| e.to_str()) {
-        Some("png") => "image/png",
-        Some("jpg") | Some("jpeg") => "image/jpeg",
-        Some("gif") => "image/gif",
-        Some("svg") => "image/svg+xml",
-        Some("webp") => "image/webp",
-        Some(ext) => return Err(EmbedError::UnsupportedFormat(ext.to_string())),
-        None => return Err(EmbedError::UnsupportedFormat("unknown".to_string())),
+    let extension = path.extension().and_then(|e| e.to_str());
+    let extension_mime = extension.and_then(mime_from_extension);
+
+    // The content wins over the extension; warn when they disagree
+    let mut warning = None;
+    let mime = match (sniff_mime(&bytes), extension_mime) {
+        (Some(sniffed), Some(expected)) if sniffed != expected => {
+            warning = Some(format!(
+                "'{}' has a .{} extension but contains {}",
+                path_str,
+                extension.unwrap_or_default(),
+                sniffed
+            ));
+            sniffed
+        }
+        (Some(sniffed), _) => sniffed,
+        (None, Some(expected)) => expected,
+        (None, None) => {
+            return Err(EmbedError::UnsupportedFormat(
+                extension.unwrap_or("unknown").to_string(),
+            ));
+        }
     };
 
-    let encoded = STANDARD.encode(&bytes);
+    let optimized = if options.is_enabled() {
+        optimize_image(&bytes, mime, options)
+    } else {
+        None
+    };
+    let final_bytes = optimized.as_deref().unwrap_or(&bytes);
 
-    Ok(format!("data:{};base64,{}", mime, encoded))
+    let encoded = STANDARD.encode(final_bytes);
+
+    Ok(EmbeddedImage {
+        data_uri: format!("data:{};base64,{}", mime, encoded),
+        mime,
+        original_size: bytes.len(),
+        optimized_size: final_bytes.len(),
+        warning,
+    })
 }
 
 #[cfg(test)]
@@ -53,7 +108,7 @@ mod tests {
             0x9C, 0x63, 0x00, 0x01, 0x00, 0x00, 0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00,
             0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
         ];
-        std::fs::write(&path, &png_bytes).unwrap();
+        std::fs::write(&path, png_bytes).unwrap();
 
         let result = embed_image(&path).unwrap();
         assert!(result.starts_with("data:image/png;base64,"));
@@ -65,7 +120,7 @@ mod tests {
         let dir = std::env::temp_dir();
         let path = dir.join("test_image.jpg");
         let jpeg_bytes: [u8; 4] = [0xFF, 0xD8, 0xFF, 0xE0];
-        std::fs::write(&path, &jpeg_bytes).unwrap();
+        std::fs::write(&path, jpeg_bytes).unwrap();
 
         let result = embed_image(&path).unwrap();
         assert!(result.starts_with("data:image/jpeg;base64,"));
@@ -130,6 +185,28 @@ mod tests {
         std::fs::remove_file(&path).ok();
     }
 
+    #[test]
+    fn test_content_wins_over_extension() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("photo.jpg");
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n....").unwrap();
+
+        let image = embed_image_with(&path, &ImageOptions::default()).unwrap();
+        assert!(image.data_uri.starts_with("data:image/png;base64,"));
+        assert!(image.warning.unwrap().contains("contains image/png"));
+    }
+
+    #[test]
+    fn test_sniffed_without_extension() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("logo");
+        std::fs::write(&path, b"GIF89a").unwrap();
+
+        let image = embed_image_with(&path, &ImageOptions::default()).unwrap();
+        assert_eq!(image.mime, "image/gif");
+        assert!(image.warning.is_none());
+    }
+
     #[test]
     fn test_missing_file() {
         let result = embed_image("nonexistent.png");
@@ -0,0 +1,181 @@
+//! Fonts and attachments embedded next to the images.
+//!
+//! Fonts become `@font-face` rules with data URI sources; attachments become
+//! data URIs that Markdown links can offer for download.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use std::path::Path;
+
+use super::mime::{attachment_mime, font_format, sniff_font_mime};
+use super::EmbedError;
+
+/// A web font from `[assets] fonts`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FontAsset {
+    pub family: String,
+    pub path: String,
+    pub weight: Option<String>,
+    pub style: Option<String>,
+    /// Apply the font to these elements, e.g. `"body"`
+    pub selector: Option<String>,
+}
+
+/// A downloadable file from `[assets] attachments`, linked as `attachment:<name>`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Attachment {
+    pub name: String,
+    pub path: String,
+}
+
+impl Attachment {
+    /// File name offered when downloading.
+    pub fn file_name(&self) -> String {
+        Path::new(&self.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.name.clone())
+    }
+}
+
+/// A file embedded as a data URI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedFile {
+    pub data_uri: String,
+    pub size: usize,
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, EmbedError> {
+    std::fs::read(path).map_err(|e| EmbedError::ReadError {
+        path: path.to_string(),
+        source: e,
+    })
+}
+
+/// Embed an attachment as a data URI.
+pub fn embed_attachment(attachment: &Attachment) -> Result<EmbeddedFile, EmbedError> {
+    let bytes = read_file(&attachment.path)?;
+    let ext = Path::new(&attachment.path)
+        .extension()
+        .and_then(|e| e.to_str());
+    let mime = attachment_mime(&bytes, ext);
+
+    Ok(EmbeddedFile {
+        data_uri: format!("data:{};base64,{}", mime, STANDARD.encode(&bytes)),
+        size: bytes.len(),
+    })
+}
+
+/// Build `@font-face` rules (and optional selector rules) for the configured fonts.
+pub fn font_face_css(fonts: &[FontAsset]) -> Result<String, EmbedError> {
+    let mut css = String::new();
+
+    for font in fonts {
+        let bytes = read_file(&font.path)?;
+        let Some(mime) = sniff_font_mime(&bytes) else {
+            return Err(EmbedError::UnsupportedFormat(format!(
+                "font '{}'",
+                font.path
+            )));
+        };
+
+        css.push_str("@font-face {\n");
+        css.push_str(&format!(
+            "    font-family: \"{}\";\n",
+            css_string(&font.family)
+        ));
+        css.push_str(&format!(
+            "    src: url(\"data:{};base64,{}\") format(\"{}\");\n",
+            mime,
+            STANDARD.encode(&bytes),
+            font_format(mime)
+        ));
+        if let Some(ref weight) = font.weight {
+            css.push_str(&format!("    font-weight: {};\n", weight));
+        }
+        if let Some(ref style) = font.style {
+            css.push_str(&format!("    font-style: {};\n", style));
+        }
+        css.push_str("    font-display: swap;\n}\n");
+    }
+
+    for font in fonts {
+        if let Some(ref selector) = font.selector {
+            css.push_str(&format!(
+                "{} {{ font-family: \"{}\", sans-serif; }}\n",
+                selector,
+                css_string(&font.family)
+            ));
+        }
+    }
+
+    Ok(css)
+}
+
+/// Escape a value for a double-quoted CSS string.
+fn css_string(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_font_face_css() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("corp.woff2");
+        std::fs::write(&path, b"wOF2\x00\x01").unwrap();
+
+        let fonts = vec![FontAsset {
+            family: "Corporate Sans".to_string(),
+            path: path.to_str().unwrap().to_string(),
+            weight: Some("700".to_string()),
+            style: None,
+            selector: Some("body".to_string()),
+        }];
+        let css = font_face_css(&fonts).unwrap();
+
+        assert!(css.contains("font-family: \"Corporate Sans\";"));
+        assert!(css.contains("url(\"data:font/woff2;base64,"));
+        assert!(css.contains("format(\"woff2\")"));
+        assert!(css.contains("font-weight: 700;"));
+        assert!(css.contains("body { font-family: \"Corporate Sans\", sans-serif; }"));
+    }
+
+    #[test]
+    fn test_font_not_a_font() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fake.woff2");
+        std::fs::write(&path, b"hello").unwrap();
+
+        let fonts = vec![FontAsset {
+            family: "Fake".to_string(),
+            path: path.to_str().unwrap().to_string(),
+            weight: None,
+            style: None,
+            selector: None,
+        }];
+        assert!(matches!(
+            font_face_css(&fonts),
+            Err(EmbedError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_embed_attachment() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("cv.pdf");
+        std::fs::write(&path, b"%PDF-1.4 test").unwrap();
+
+        let attachment = Attachment {
+            name: "cv".to_string(),
+            path: path.to_str().unwrap().to_string(),
+        };
+        let file = embed_attachment(&attachment).unwrap();
+        assert!(file.data_uri.starts_with("data:application/pdf;base64,"));
+        assert_eq!(file.size, 13);
+        assert_eq!(attachment.file_name(), "cv.pdf");
+    }
+}
@@ -0,0 +1,162 @@
+//! MIME type detection for embedded files.
+//!
+//! The content decides the type; the file extension is only a fallback for
+//! formats without a reliable signature.
+
+/// Detect an image type from its magic bytes.
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[0..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if is_avif(bytes) {
+        Some("image/avif")
+    } else if bytes.starts_with(&[0x00, 0x00, 0x01, 0x00]) {
+        Some("image/x-icon")
+    } else if bytes.starts_with(b"BM") && bytes.len() >= 14 {
+        Some("image/bmp")
+    } else if is_svg(bytes) {
+        Some("image/svg+xml")
+    } else {
+        None
+    }
+}
+
+/// The MIME type usually associated with a file extension.
+pub fn mime_from_extension(ext: &str) -> Option<&'static str> {
+    match ext.to_ascii_lowercase().as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "svg" => Some("image/svg+xml"),
+        "webp" => Some("image/webp"),
+        "avif" => Some("image/avif"),
+        "ico" => Some("image/x-icon"),
+        "bmp" => Some("image/bmp"),
+        _ => None,
+    }
+}
+
+/// Detect a web font type from its magic bytes.
+pub fn sniff_font_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"wOF2") {
+        Some("font/woff2")
+    } else if bytes.starts_with(b"wOFF") {
+        Some("font/woff")
+    } else if bytes.starts_with(b"OTTO") {
+        Some("font/otf")
+    } else if bytes.starts_with(&[0x00, 0x01, 0x00, 0x00]) || bytes.starts_with(b"true") {
+        Some("font/ttf")
+    } else {
+        None
+    }
+}
+
+/// The `format()` hint of an `@font-face` source.
+pub fn font_format(mime: &str) -> &'static str {
+    match mime {
+        "font/woff2" => "woff2",
+        "font/woff" => "woff",
+        "font/otf" => "opentype",
+        _ => "truetype",
+    }
+}
+
+/// MIME type of a downloadable attachment, by content where possible.
+pub fn attachment_mime(bytes: &[u8], ext: Option<&str>) -> &'static str {
+    if bytes.starts_with(b"%PDF-") {
+        return "application/pdf";
+    }
+    if let Some(mime) = sniff_mime(bytes) {
+        return mime;
+    }
+    match ext.map(str::to_ascii_lowercase).as_deref() {
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("txt") => "text/plain",
+        Some("csv") => "text/csv",
+        Some("md") => "text/markdown",
+        Some("json") => "application/json",
+        Some("doc") => "application/msword",
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some("xlsx") => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        _ => "application/octet-stream",
+    }
+}
+
+/// ISO base media file with an `avif`/`avis` brand in its `ftyp` box.
+fn is_avif(bytes: &[u8]) -> bool {
+    if bytes.len() < 16 || &bytes[4..8] != b"ftyp" {
+        return false;
+    }
+    let box_len = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    let brands = &bytes[8..box_len.clamp(16, bytes.len())];
+    brands
+        .chunks_exact(4)
+        .any(|brand| brand == b"avif" || brand == b"avis")
+}
+
+/// Text starting with an XML prolog, comment or `<svg` element that contains an `<svg` tag.
+fn is_svg(bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]);
+    let text = head.trim_start_matches('\u{feff}').trim_start();
+    let opens_like_svg = ["<?xml", "<!--", "<svg", "<!DOCTYPE svg"]
+        .iter()
+        .any(|prefix| text.starts_with(prefix));
+    opens_like_svg && text.contains("<svg")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_common_formats() {
+        assert_eq!(sniff_mime(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(sniff_mime(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff_mime(b"GIF89a"), Some("image/gif"));
+        assert_eq!(sniff_mime(b"RIFF\x00\x00\x00\x00WEBP"), Some("image/webp"));
+        assert_eq!(
+            sniff_mime(b"<?xml version=\"1.0\"?>\n<svg></svg>"),
+            Some("image/svg+xml")
+        );
+        assert_eq!(sniff_mime(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_sniff_avif_ico_bmp() {
+        let avif = b"\x00\x00\x00\x1cftypavif\x00\x00\x00\x00avifmif1miaf";
+        assert_eq!(sniff_mime(avif), Some("image/avif"));
+        let heic = b"\x00\x00\x00\x18ftypheic\x00\x00\x00\x00mif1heic";
+        assert_eq!(sniff_mime(heic), None);
+        assert_eq!(
+            sniff_mime(&[0x00, 0x00, 0x01, 0x00, 0x01, 0x00]),
+            Some("image/x-icon")
+        );
+        assert_eq!(
+            sniff_mime(b"BM\x3a\x00\x00\x00\x00\x00\x00\x00\x36\x00\x00\x00"),
+            Some("image/bmp")
+        );
+    }
+
+    #[test]
+    fn test_sniff_fonts_and_attachments() {
+        assert_eq!(sniff_font_mime(b"wOF2...."), Some("font/woff2"));
+        assert_eq!(sniff_font_mime(&[0x00, 0x01, 0x00, 0x00]), Some("font/ttf"));
+        assert_eq!(font_format("font/otf"), "opentype");
+        assert_eq!(attachment_mime(b"%PDF-1.7", None), "application/pdf");
+        assert_eq!(attachment_mime(b"a,b", Some("CSV")), "text/csv");
+        assert_eq!(attachment_mime(b"??", None), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_mime_from_extension() {
+        assert_eq!(mime_from_extension("JPG"), Some("image/jpeg"));
+        assert_eq!(mime_from_extension("ico"), Some("image/x-icon"));
+        assert_eq!(mime_from_extension("txt"), None);
+    }
+}
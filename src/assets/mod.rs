@@ -1,45 +1,103 @@
+pub mod defaults;
 mod embedder;
+mod files;
+mod mime;
+mod optimize;
+mod theme;
 
-pub use embedder::{embed_image, EmbedError};
+pub use embedder::{embed_image, embed_image_with, EmbedError, EmbeddedImage};
+pub use files::{embed_attachment, font_face_css, Attachment, EmbeddedFile, FontAsset};
+pub use mime::{attachment_mime, mime_from_extension, sniff_font_mime, sniff_mime};
+pub use optimize::{minify_svg, optimize_image, ImageOptions};
+pub use theme::{builtin_theme, BuiltinTheme, Theme, BUILTIN_THEMES};
 
+use theme::read_optional;
+
+use std::collections::HashMap;
 use std::path::Path;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Assets {
     pub styles: String,
     pub script: String,
     pub template: String,
+    /// Template partials from `assets/partials/*.html`, keyed by file stem
+    pub partials: HashMap<String, String>,
+    /// Data URI of the theme's `logo.*`, used when the config sets no logo
+    pub logo: Option<String>,
+    /// Files neither the project nor the theme provided, taken from the built-in defaults
+    pub defaults_used: Vec<String>,
 }
 
 impl Assets {
     pub fn load(config_dir: &Path) -> Result<Self, EmbedError> {
+        Self::load_with_theme(config_dir, None)
+    }
+
+    /// Load assets for a theme.
+    ///
+    /// Files in `<config>/assets/` override the theme file by file; files
+    /// found in neither fall back to the defaults compiled into the binary.
+    pub fn load_with_theme(config_dir: &Path, theme: Option<&str>) -> Result<Self, EmbedError> {
         let assets_dir = config_dir.join("assets");
+        let theme = theme
+            .map(|name| Theme::resolve(config_dir, name))
+            .transpose()?;
 
-        let styles = std::fs::read_to_string(assets_dir.join("style.css")).map_err(|e| {
-            EmbedError::ReadError {
-                path: assets_dir.join("style.css").display().to_string(),
-                source: e,
+        let mut defaults_used = Vec::new();
+        let mut load_file = |file: &str| -> Result<String, EmbedError> {
+            if let Some(content) = read_optional(&assets_dir.join(file))? {
+                return Ok(content);
             }
-        })?;
-
-        let script = std::fs::read_to_string(assets_dir.join("script.js")).map_err(|e| {
-            EmbedError::ReadError {
-                path: assets_dir.join("script.js").display().to_string(),
-                source: e,
+            if let Some(content) = theme.as_ref().map(|t| t.read(file)).transpose()?.flatten() {
+                return Ok(content);
             }
-        })?;
+            defaults_used.push(file.to_string());
+            Ok(defaults::text_file(file).unwrap_or_default().to_string())
+        };
 
-        let template = std::fs::read_to_string(assets_dir.join("template.html")).map_err(|e| {
-            EmbedError::ReadError {
-                path: assets_dir.join("template.html").display().to_string(),
-                source: e,
-            }
-        })?;
+        let styles = load_file("style.css")?;
+        let script = load_file("script.js")?;
+        let template = load_file("template.html")?;
+
+        let (mut partials, logo) = match theme {
+            Some(ref theme) => (theme.partials()?, theme.logo_data_uri()?),
+            None => (HashMap::new(), None),
+        };
+        partials.extend(load_partials(&assets_dir.join("partials"))?);
 
         Ok(Self {
             styles,
             script,
             template,
+            partials,
+            logo,
+            defaults_used,
         })
     }
 }
+
+/// Load all `*.html` files of a directory as template partials.
+/// A missing directory simply means there are no partials.
+fn load_partials(dir: &Path) -> Result<HashMap<String, String>, EmbedError> {
+    let mut partials = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(partials);
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "html") {
+            let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else {
+                continue;
+            };
+            let content = std::fs::read_to_string(&path).map_err(|e| EmbedError::ReadError {
+                path: path.display().to_string(),
+                source: e,
+            })?;
+            partials.insert(name, content);
+        }
+    }
+
+    Ok(partials)
+}
@@ -0,0 +1,156 @@
+//! Image optimisation applied while embedding.
+//!
+//! Raster images are downscaled to a maximum width and recompressed; SVG
+//! files are minified. The optimised result is only used when it is smaller
+//! than the original file.
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{CompressionType, FilterType as PngFilter, PngEncoder};
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use regex::Regex;
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+/// Quality used when re-encoding JPEG images.
+const JPEG_QUALITY: u8 = 85;
+
+/// Image settings from the `[assets]` config section.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ImageOptions {
+    /// Downscale raster images wider than this many pixels
+    pub max_image_width: Option<u32>,
+    /// Fail when all embedded images together exceed this many bytes (as data URIs)
+    pub max_embed_bytes: Option<u64>,
+}
+
+impl ImageOptions {
+    /// Optimisation runs as soon as any image setting is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.max_image_width.is_some() || self.max_embed_bytes.is_some()
+    }
+}
+
+/// Optimise image bytes of the given MIME type.
+///
+/// Returns `None` if the format is not optimised, cannot be decoded or the
+/// result would not be smaller.
+pub fn optimize_image(bytes: &[u8], mime: &str, options: &ImageOptions) -> Option<Vec<u8>> {
+    let optimized = match mime {
+        "image/svg+xml" => Some(minify_svg(std::str::from_utf8(bytes).ok()?).into_bytes()),
+        "image/png" => recompress(bytes, ImageFormat::Png, options.max_image_width),
+        "image/jpeg" => recompress(bytes, ImageFormat::Jpeg, options.max_image_width),
+        "image/webp" => recompress(bytes, ImageFormat::WebP, options.max_image_width),
+        _ => None,
+    }?;
+
+    (optimized.len() < bytes.len()).then_some(optimized)
+}
+
+/// Decode, downscale if too wide and re-encode in the same format.
+fn recompress(bytes: &[u8], format: ImageFormat, max_width: Option<u32>) -> Option<Vec<u8>> {
+    let mut img = image::load_from_memory_with_format(bytes, format).ok()?;
+    if let Some(max_width) = max_width {
+        if img.width() > max_width {
+            img = img.resize(max_width, u32::MAX, FilterType::Lanczos3);
+        }
+    }
+
+    let mut buffer = Vec::new();
+    match format {
+        ImageFormat::Png => {
+            let encoder = PngEncoder::new_with_quality(
+                &mut buffer,
+                CompressionType::Best,
+                PngFilter::Adaptive,
+            );
+            img.write_with_encoder(encoder).ok()?;
+        }
+        ImageFormat::Jpeg => {
+            // JPEG has no alpha channel
+            let rgb = DynamicImage::ImageRgb8(img.to_rgb8());
+            rgb.write_with_encoder(JpegEncoder::new_with_quality(&mut buffer, JPEG_QUALITY))
+                .ok()?;
+        }
+        ImageFormat::WebP => {
+            let rgba = DynamicImage::ImageRgba8(img.to_rgba8());
+            rgba.write_with_encoder(WebPEncoder::new_lossless(&mut buffer))
+                .ok()?;
+        }
+        _ => return None,
+    }
+    Some(buffer)
+}
+
+/// Remove comments, the XML prolog and line breaks between tags.
+pub fn minify_svg(svg: &str) -> String {
+    static COMMENTS: OnceLock<Regex> = OnceLock::new();
+    static BETWEEN_TAGS: OnceLock<Regex> = OnceLock::new();
+
+    let comments = COMMENTS.get_or_init(|| Regex::new(r"(?s)<!--.*?-->|<\?xml.*?\?>").unwrap());
+    // Only whitespace containing a line break, so spaces inside text stay intact
+    let between_tags = BETWEEN_TAGS.get_or_init(|| Regex::new(r">\s*\n\s*<").unwrap());
+
+    let without_comments = comments.replace_all(svg, "");
+    between_tags
+        .replace_all(without_comments.trim(), "><")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageBuffer, Rgb};
+
+    fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+        let img = ImageBuffer::from_fn(width, height, |x, y| {
+            Rgb([(x % 256) as u8, (y % 256) as u8, 128])
+        });
+        let mut buffer = Vec::new();
+        DynamicImage::ImageRgb8(img)
+            .write_with_encoder(PngEncoder::new_with_quality(
+                &mut buffer,
+                CompressionType::Fast,
+                PngFilter::NoFilter,
+            ))
+            .unwrap();
+        buffer
+    }
+
+    #[test]
+    fn test_downscale_png() {
+        let original = png_bytes(400, 200);
+        let options = ImageOptions {
+            max_image_width: Some(100),
+            ..Default::default()
+        };
+        let optimized = optimize_image(&original, "image/png", &options).unwrap();
+        let img = image::load_from_memory(&optimized).unwrap();
+        assert_eq!((img.width(), img.height()), (100, 50));
+    }
+
+    #[test]
+    fn test_keeps_original_when_not_smaller() {
+        // Not decodable, nothing to optimise
+        assert!(optimize_image(b"not a png", "image/png", &ImageOptions::default()).is_none());
+        assert!(optimize_image(b"GIF89a", "image/gif", &ImageOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_minify_svg() {
+        let svg =
+            "<?xml version=\"1.0\"?>\n<!-- drawn by hand -->\n<svg>\n  <text>a b</text>\n</svg>\n";
+        assert_eq!(minify_svg(svg), "<svg><text>a b</text></svg>");
+    }
+
+    #[test]
+    fn test_is_enabled() {
+        assert!(!ImageOptions::default().is_enabled());
+        assert!(ImageOptions {
+            max_embed_bytes: Some(1000),
+            ..Default::default()
+        }
+        .is_enabled());
+    }
+}
@@ -0,0 +1,324 @@
+//! Theme resolution.
+//!
+//! A theme is a directory with `template.html`, `style.css`, `script.js`,
+//! optional `partials/` and other assets such as a logo, looked up in `<config>/themes/<name>/`, or one of the
+//! built-in themes compiled into the binary. A theme may extend another one
+//! with `extends = "<name>"` in its `theme.toml`; files it does not provide
+//! fall back to the parent theme.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::defaults::{
+    SCRIPT_JS as DEFAULT_SCRIPT, STYLE_CSS as DEFAULT_STYLES, TEMPLATE_HTML as DEFAULT_TEMPLATE,
+};
+use super::mime::{mime_from_extension, sniff_mime};
+use super::EmbedError;
+
+/// Logo files a theme may ship, in order of preference.
+const LOGO_FILES: [&str; 6] = [
+    "logo.svg",
+    "logo.png",
+    "logo.webp",
+    "logo.jpg",
+    "logo.jpeg",
+    "logo.gif",
+];
+
+/// A theme compiled into the binary.
+#[derive(Debug)]
+pub struct BuiltinTheme {
+    pub name: &'static str,
+    pub styles: &'static str,
+    pub script: &'static str,
+    pub template: &'static str,
+}
+
+/// Built-in themes; the light and sepia themes layer colour overrides on the default styles.
+pub const BUILTIN_THEMES: &[BuiltinTheme] = &[
+    BuiltinTheme {
+        name: "default",
+        styles: DEFAULT_STYLES,
+        script: DEFAULT_SCRIPT,
+        template: DEFAULT_TEMPLATE,
+    },
+    BuiltinTheme {
+        name: "light",
+        styles: concat!(
+            include_str!("../../config/assets/style.css"),
+            include_str!("themes/light.css")
+        ),
+        script: DEFAULT_SCRIPT,
+        template: DEFAULT_TEMPLATE,
+    },
+    BuiltinTheme {
+        name: "sepia",
+        styles: concat!(
+            include_str!("../../config/assets/style.css"),
+            include_str!("themes/sepia.css")
+        ),
+        script: DEFAULT_SCRIPT,
+        template: DEFAULT_TEMPLATE,
+    },
+];
+
+/// Look up a built-in theme by name.
+pub fn builtin_theme(name: &str) -> Option<&'static BuiltinTheme> {
+    BUILTIN_THEMES.iter().find(|t| t.name == name)
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ThemeManifest {
+    extends: Option<String>,
+}
+
+#[derive(Debug)]
+enum ThemeLayer {
+    Dir(PathBuf),
+    Builtin(&'static BuiltinTheme),
+}
+
+/// A resolved theme: its own files first, then those of the themes it extends.
+#[derive(Debug)]
+pub struct Theme {
+    name: String,
+    layers: Vec<ThemeLayer>,
+}
+
+impl Theme {
+    /// Resolve a theme and its parents, preferring `<config>/themes/<name>/` over built-ins.
+    pub fn resolve(config_dir: &Path, name: &str) -> Result<Self, EmbedError> {
+        let mut layers = Vec::new();
+        let mut visited: Vec<String> = Vec::new();
+        let mut next = Some(name.to_string());
+
+        while let Some(current) = next.take() {
+            if visited.contains(&current) {
+                return Err(EmbedError::ThemeError {
+                    theme: name.to_string(),
+                    message: format!("circular 'extends' via '{}'", current),
+                });
+            }
+            visited.push(current.clone());
+
+            let dir = config_dir.join("themes").join(&current);
+            if dir.is_dir() {
+                next = read_manifest(&dir, name)?.extends;
+                layers.push(ThemeLayer::Dir(dir));
+            } else if let Some(builtin) = builtin_theme(&current) {
+                layers.push(ThemeLayer::Builtin(builtin));
+            } else {
+                return Err(EmbedError::UnknownTheme(current));
+            }
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            layers,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Read a text file of the theme from the first layer that provides it.
+    pub fn read(&self, file: &str) -> Result<Option<String>, EmbedError> {
+        self.read_bytes(file)?
+            .map(|bytes| {
+                String::from_utf8(bytes).map_err(|_| EmbedError::ThemeError {
+                    theme: self.name.clone(),
+                    message: format!("'{}' is not UTF-8 text", file),
+                })
+            })
+            .transpose()
+    }
+
+    /// Read any theme file, such as an image, from the first layer that provides it.
+    pub fn read_bytes(&self, file: &str) -> Result<Option<Vec<u8>>, EmbedError> {
+        for layer in &self.layers {
+            match layer {
+                ThemeLayer::Dir(dir) => {
+                    if let Some(content) = read_optional_bytes(&dir.join(file))? {
+                        return Ok(Some(content));
+                    }
+                }
+                ThemeLayer::Builtin(builtin) => {
+                    let content = match file {
+                        "style.css" => builtin.styles,
+                        "script.js" => builtin.script,
+                        "template.html" => builtin.template,
+                        _ => continue,
+                    };
+                    return Ok(Some(content.as_bytes().to_vec()));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// The theme's `logo.*` file as a data URI.
+    pub fn logo_data_uri(&self) -> Result<Option<String>, EmbedError> {
+        for file in LOGO_FILES {
+            if let Some(bytes) = self.read_bytes(file)? {
+                let extension = file.rsplit('.').next().unwrap_or_default();
+                let mime = sniff_mime(&bytes)
+                    .or_else(|| mime_from_extension(extension))
+                    .unwrap_or("application/octet-stream");
+                return Ok(Some(format!(
+                    "data:{};base64,{}",
+                    mime,
+                    STANDARD.encode(bytes)
+                )));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Partials of all layers; a theme's partials override those of its parents.
+    pub fn partials(&self) -> Result<HashMap<String, String>, EmbedError> {
+        let mut partials = HashMap::new();
+        for layer in self.layers.iter().rev() {
+            if let ThemeLayer::Dir(dir) = layer {
+                partials.extend(super::load_partials(&dir.join("partials"))?);
+            }
+        }
+        Ok(partials)
+    }
+}
+
+fn read_manifest(dir: &Path, theme: &str) -> Result<ThemeManifest, EmbedError> {
+    let Some(content) = read_optional(&dir.join("theme.toml"))? else {
+        return Ok(ThemeManifest::default());
+    };
+    toml::from_str(&content).map_err(|e| EmbedError::ThemeError {
+        theme: theme.to_string(),
+        message: format!("invalid theme.toml: {}", e),
+    })
+}
+
+/// Read a file, treating a missing file as `None`.
+pub(crate) fn read_optional(path: &Path) -> Result<Option<String>, EmbedError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(EmbedError::ReadError {
+            path: path.display().to_string(),
+            source: e,
+        }),
+    }
+}
+
+/// Read a binary file, treating a missing file as `None`.
+fn read_optional_bytes(path: &Path) -> Result<Option<Vec<u8>>, EmbedError> {
+    match std::fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(EmbedError::ReadError {
+            path: path.display().to_string(),
+            source: e,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_themes() {
+        let default = builtin_theme("default").unwrap();
+        let light = builtin_theme("light").unwrap();
+        assert!(light.styles.starts_with(default.styles));
+        assert!(light.styles.len() > default.styles.len());
+        assert!(builtin_theme("nope").is_none());
+    }
+
+    #[test]
+    fn test_resolve_builtin() {
+        let dir = TempDir::new().unwrap();
+        let theme = Theme::resolve(dir.path(), "sepia").unwrap();
+        let styles = theme.read("style.css").unwrap().unwrap();
+        assert!(styles.contains("Sepia Theme"));
+        assert!(theme.read("logo.png").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_resolve_directory_theme_extends_builtin() {
+        let dir = TempDir::new().unwrap();
+        let theme_dir = dir.path().join("themes").join("corporate");
+        fs::create_dir_all(theme_dir.join("partials")).unwrap();
+        fs::write(theme_dir.join("theme.toml"), "extends = \"light\"").unwrap();
+        fs::write(theme_dir.join("style.css"), "body { color: red; }").unwrap();
+        fs::write(
+            theme_dir.join("partials").join("footer.html"),
+            "<footer></footer>",
+        )
+        .unwrap();
+
+        let theme = Theme::resolve(dir.path(), "corporate").unwrap();
+        assert_eq!(
+            theme.read("style.css").unwrap().unwrap(),
+            "body { color: red; }"
+        );
+        // Not provided by the theme, falls back to the built-in parent
+        assert!(theme
+            .read("template.html")
+            .unwrap()
+            .unwrap()
+            .contains("{{content}}"));
+        assert!(theme.partials().unwrap().contains_key("footer"));
+        assert!(theme.logo_data_uri().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_theme_logo() {
+        let dir = TempDir::new().unwrap();
+        let theme_dir = dir.path().join("themes").join("brand");
+        fs::create_dir_all(&theme_dir).unwrap();
+        fs::write(theme_dir.join("logo.png"), super::super::defaults::LOGO_PNG).unwrap();
+
+        let theme = Theme::resolve(dir.path(), "brand").unwrap();
+        assert_eq!(
+            theme.read_bytes("logo.png").unwrap().unwrap(),
+            super::super::defaults::LOGO_PNG
+        );
+        assert!(matches!(
+            theme.read("logo.png"),
+            Err(EmbedError::ThemeError { .. })
+        ));
+        assert!(theme
+            .logo_data_uri()
+            .unwrap()
+            .unwrap()
+            .starts_with("data:image/png;base64,iVBOR"));
+    }
+
+    #[test]
+    fn test_unknown_theme() {
+        let dir = TempDir::new().unwrap();
+        let result = Theme::resolve(dir.path(), "missing");
+        assert!(matches!(result, Err(EmbedError::UnknownTheme(name)) if name == "missing"));
+    }
+
+    #[test]
+    fn test_circular_extends() {
+        let dir = TempDir::new().unwrap();
+        for (name, parent) in [("a", "b"), ("b", "a")] {
+            let theme_dir = dir.path().join("themes").join(name);
+            fs::create_dir_all(&theme_dir).unwrap();
+            fs::write(
+                theme_dir.join("theme.toml"),
+                format!("extends = \"{}\"", parent),
+            )
+            .unwrap();
+        }
+
+        let result = Theme::resolve(dir.path(), "a");
+        assert!(matches!(result, Err(EmbedError::ThemeError { .. })));
+    }
+}
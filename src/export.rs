@@ -0,0 +1,255 @@
+//! JSON export of the parsed document, for tools that want the content
+//! without scraping the generated HTML.
+//!
+//! The schema is documented in the README. Within a `schema_version`,
+//! fields are only ever added, never renamed or removed.
+
+use serde::Serialize;
+
+use crate::assets::Assets;
+use crate::parser::{
+    extract_skill_matrices, parse_document_tree, parse_markdown, process_includes,
+    strip_toc_markers, substitute_variables, ContentPanel, NavNode, SkillRow,
+};
+use crate::renderer::markup::{tokenize, Token};
+use crate::{prepare_document, GenerationStats, GeneratorConfig, GeneratorError, Sources};
+
+/// Version of the JSON schema, bumped on incompatible changes.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Everything Vellum knows about a document after running the pipeline.
+#[derive(Debug, Serialize)]
+pub struct DocumentExport {
+    pub schema_version: u32,
+    pub title: String,
+    pub navigation: Vec<NavNode>,
+    pub panels: Vec<PanelExport>,
+    /// Markdown before the first section shown in the header, empty unless
+    /// the preamble policy is `header`
+    pub preamble: String,
+    pub skill_matrices: Vec<SkillMatrixExport>,
+    pub stats: GenerationStats,
+}
+
+/// A content panel with its rendered HTML and the markup found in it.
+#[derive(Debug, Serialize)]
+pub struct PanelExport {
+    #[serde(flatten)]
+    pub panel: ContentPanel,
+    pub html: String,
+    /// Text of the achievement markers, in document order
+    pub achievements: Vec<String>,
+    pub tags: Vec<TagExport>,
+}
+
+/// Text matched by a `colored_tags` pattern.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TagExport {
+    pub text: String,
+    pub color: String,
+}
+
+/// The rows of one skill matrix and the panel it appears in.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SkillMatrixExport {
+    pub panel: String,
+    pub rows: Vec<SkillRow>,
+}
+
+/// Run the pipeline and collect the document for export.
+///
+/// Input and output paths in `config` are ignored; `base_path` resolves includes.
+pub fn export_with_config(
+    markdown: &str,
+    base_path: &str,
+    config: &GeneratorConfig,
+) -> Result<DocumentExport, GeneratorError> {
+    let mut stats = GenerationStats {
+        source_lines: markdown.lines().count(),
+        ..Default::default()
+    };
+    let doc = prepare_document(markdown, base_path, config, &mut stats)?;
+
+    let panels = doc
+        .panels
+        .into_iter()
+        .map(|panel| {
+            let html = parse_markdown(&strip_toc_markers(&panel.markdown_content));
+            let (achievements, tags) = collect_markup(&html);
+            PanelExport {
+                panel,
+                html,
+                achievements,
+                tags,
+            }
+        })
+        .collect();
+
+    Ok(DocumentExport {
+        schema_version: SCHEMA_VERSION,
+        title: config.title.clone(),
+        navigation: doc.navigation,
+        panels,
+        preamble: doc.preamble,
+        skill_matrices: skill_matrices(markdown, base_path, config),
+        stats,
+    })
+}
+
+/// Export the document a config points at as pretty-printed JSON.
+pub fn export_json(config: &GeneratorConfig) -> Result<(Vec<u8>, GenerationStats), GeneratorError> {
+    let sources = Sources::load(config, &Assets::default())?;
+    let mut export = export_with_config(&sources.markdown, &sources.base_path, config)?;
+    export.stats.warnings.extend(sources.logo_warning);
+
+    let json = serde_json::to_vec_pretty(&export)?;
+    Ok((json, export.stats))
+}
+
+/// Skill matrix rows per panel, read from the Markdown before the tables
+/// are rendered.
+fn skill_matrices(
+    markdown: &str,
+    base_path: &str,
+    config: &GeneratorConfig,
+) -> Vec<SkillMatrixExport> {
+    let dropdown_section = config.dropdown_section.as_deref();
+    let expanded = substitute_variables(
+        &process_includes(markdown, base_path, dropdown_section),
+        base_path,
+    );
+    let doc = parse_document_tree(&expanded, &config.section_styles());
+
+    let mut result = Vec::new();
+    for panel in &doc.panels {
+        // The panel title is a heading too, and may be what marks the matrix
+        let content = format!("## {}\n\n{}", panel.title, panel.markdown_content);
+        for rows in extract_skill_matrices(&content, &config.skill_matrix) {
+            result.push(SkillMatrixExport {
+                panel: panel.id.clone(),
+                rows,
+            });
+        }
+    }
+    result
+}
+
+/// What a span of the rendered HTML is collected as.
+enum Capture {
+    Achievement(String),
+    Tag { text: String, color: String },
+    None,
+}
+
+/// Achievement marker texts and colored tags in rendered panel HTML.
+fn collect_markup(html: &str) -> (Vec<String>, Vec<TagExport>) {
+    let mut achievements = Vec::new();
+    let mut tags = Vec::new();
+    let mut open: Vec<Capture> = Vec::new();
+
+    for token in tokenize(html) {
+        match token {
+            Token::Open { ref name, .. } if name == "span" => {
+                let color = token.attr("class").and_then(|classes| {
+                    classes
+                        .split_whitespace()
+                        .find_map(|c| c.strip_prefix("color-tag-"))
+                });
+                open.push(if token.has_class("achievement-marker") {
+                    Capture::Achievement(String::new())
+                } else if let Some(color) = color {
+                    Capture::Tag {
+                        text: String::new(),
+                        color: color.to_string(),
+                    }
+                } else {
+                    Capture::None
+                });
+            }
+            Token::Close(ref name) if name == "span" => match open.pop() {
+                Some(Capture::Achievement(text)) => achievements.push(text.trim().to_string()),
+                Some(Capture::Tag { text, color }) => tags.push(TagExport { text, color }),
+                _ => {}
+            },
+            Token::Text(ref text) => {
+                for capture in open.iter_mut() {
+                    match capture {
+                        Capture::Achievement(ref mut collected)
+                        | Capture::Tag {
+                            text: ref mut collected,
+                            ..
+                        } => collected.push_str(text),
+                        Capture::None => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    (achievements, tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_collect_markup_nested_spans() {
+        let html = "<p>Led <span class=\"achievement-marker\">Shipped <span class=\"color-tag color-tag-green\">v2</span> &amp; more</span></p>";
+        let (achievements, tags) = collect_markup(html);
+        assert_eq!(achievements, ["Shipped v2 & more"]);
+        assert_eq!(
+            tags,
+            [TagExport {
+                text: "v2".to_string(),
+                color: "green".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_export_with_config() {
+        let markdown = "## About\n\nBuilt it <! Cut costs by 20%\n\nUses Rust\n\n## Skills\n\n| Skill | Level |\n|---|---|\n| **Languages** | |\n| Rust | 5 |\n";
+        let config = GeneratorConfig {
+            title: "Portfolio".to_string(),
+            colored_tags: HashMap::from([("Rust".to_string(), "orange".to_string())]),
+            ..Default::default()
+        };
+
+        let export = export_with_config(markdown, ".", &config).unwrap();
+        assert_eq!(export.schema_version, SCHEMA_VERSION);
+        assert_eq!(export.navigation.len(), 2);
+        assert_eq!(export.panels[0].achievements, ["Cut costs by 20%"]);
+        assert_eq!(export.panels[0].tags[0].text, "Rust");
+        assert!(export.panels[1].html.contains("skill-matrix"));
+        assert_eq!(export.skill_matrices.len(), 1);
+        assert_eq!(export.skill_matrices[0].panel, "skills");
+        assert_eq!(
+            export.skill_matrices[0].rows,
+            [
+                SkillRow::Category("Languages".to_string()),
+                SkillRow::Skill {
+                    name: "Rust".to_string(),
+                    value: "5".to_string(),
+                    notes: String::new(),
+                },
+            ]
+        );
+        assert_eq!(export.stats.achievement_markers, 1);
+
+        let json: serde_json::Value = serde_json::to_value(&export).unwrap();
+        assert_eq!(json["panels"][0]["id"], "about");
+        assert!(json["panels"][0]["markdown_content"]
+            .as_str()
+            .unwrap()
+            .contains("achievement-marker"));
+        assert_eq!(
+            json["skill_matrices"][0]["rows"][0]["category"],
+            "Languages"
+        );
+        assert_eq!(json["skill_matrices"][0]["rows"][1]["skill"]["value"], "5");
+        assert!(json["stats"].get("sizes").is_none());
+    }
+}
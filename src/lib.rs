@@ -1,18 +1,35 @@
 pub mod assets;
+pub mod export;
 pub mod parser;
 pub mod renderer;
+pub mod scaffold;
 
-use assets::{embed_image, Assets};
+use assets::{
+    embed_attachment, embed_image_with, font_face_css, minify_svg, Assets, Attachment, FontAsset,
+    ImageOptions,
+};
 use parser::{
-    parse_document_structure, process_includes, substitute_variables,
-    transform_achievement_markers, transform_colored_tags, transform_skill_matrices,
+    add_contents_panel, decode_image_path, included_files, insert_team_matrix, insert_toc_markers,
+    is_local_image, merge_team_matrix, parse_document_tree, process_includes, render_team_matrix,
+    replace_attachment_links, rewrite_image_urls, substitute_variables,
+    transform_achievement_markers, transform_colored_tags, transform_skill_matrices, ChartKind,
+    DocumentStructure, PreamblePolicy, SectionStyle, SkillMatrixOptions, TeamOptions, TocOptions,
+    ATTACHMENT_SCHEME, TEAM_MATRIX_MARKER,
+};
+use renderer::{
+    escape_html, minify_css, minify_html, minify_js, DocumentMeta, EpubRenderer, HeaderLink,
+    HeaderLogo, HtmlRenderer, MarkdownRenderer, PageHeader, PageLayout, PagesRenderer, PdfRenderer,
+    RenderError, RenderOutput, Renderer, Template, PRINT_STYLES, SEARCH_SCRIPT, SEARCH_STYLES,
 };
-use renderer::{HtmlRenderer, RenderError};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+/// Title of the panel made from the text before the first H2.
+const DEFAULT_PREAMBLE_TITLE: &str = "Introduction";
+
 #[derive(Debug, Clone)]
 pub struct GeneratorConfig {
     pub markdown_path: String,
@@ -20,7 +37,125 @@ pub struct GeneratorConfig {
     pub title: String,
     pub output_path: String,
     pub dropdown_section: Option<String>,
+    /// Navigation style of H2 sections by title; `dropdown_section` adds a dropdown
+    pub navigation: HashMap<String, SectionStyle>,
+    /// Where the text before the first H2 goes
+    pub preamble: PreamblePolicy,
+    /// Title of the preamble panel, or of the only panel without H2 sections
+    pub preamble_title: String,
+    /// Named theme providing the template, styles and script
+    pub theme: Option<String>,
     pub colored_tags: HashMap<String, String>,
+    pub skill_matrix: SkillMatrixOptions,
+    /// Optimisation and size limits for embedded images
+    pub images: ImageOptions,
+    /// Web fonts inlined as `@font-face` rules
+    pub fonts: Vec<FontAsset>,
+    /// Files embedded for download, linked as `attachment:<name>`
+    pub attachments: Vec<Attachment>,
+    pub header: HeaderOptions,
+    pub output: OutputOptions,
+    /// Generated tables of contents
+    pub toc: TocOptions,
+}
+
+/// Output settings from the `[output]` config section.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OutputOptions {
+    /// Minify the styles, script and final HTML
+    pub minify: bool,
+    /// Embed the panels gzip-compressed, expanded by an inline script on load
+    pub compress: bool,
+    pub format: OutputFormat,
+    /// Panel arrangement of the HTML page
+    pub layout: PageLayout,
+    /// Embed a search index and search box in the tabs layout
+    pub search: bool,
+}
+
+/// Kind of file produced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Single HTML page with panel navigation
+    #[default]
+    Html,
+    /// Index page and one HTML page per panel, in a directory
+    Pages,
+    /// All panels in sequence, with bookmarks
+    Pdf,
+    /// E-book with one chapter per panel
+    Epub,
+    /// Single Markdown file with includes and transforms applied
+    Markdown,
+}
+
+impl OutputFormat {
+    /// File extension of the output; `None` for a directory of files.
+    pub fn extension(self) -> Option<&'static str> {
+        match self {
+            OutputFormat::Html => Some("html"),
+            OutputFormat::Pages => None,
+            OutputFormat::Pdf => Some("pdf"),
+            OutputFormat::Epub => Some("epub"),
+            OutputFormat::Markdown => Some("md"),
+        }
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "html" => Ok(OutputFormat::Html),
+            "pages" => Ok(OutputFormat::Pages),
+            "pdf" => Ok(OutputFormat::Pdf),
+            "epub" => Ok(OutputFormat::Epub),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(format!(
+                "Unknown output format '{}' (expected html, pages, pdf, epub or markdown)",
+                s
+            )),
+        }
+    }
+}
+
+/// Page header settings from the `[header]` config section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HeaderOptions {
+    /// Show the logo image; `false` for pages without any logo
+    #[serde(rename = "logo")]
+    pub show_logo: bool,
+    /// Text shown instead of the logo image
+    pub wordmark: Option<String>,
+    /// SVG file inlined instead of the logo image
+    pub logo_svg: Option<String>,
+    pub subtitle: Option<String>,
+    pub author: Option<String>,
+    pub links: Vec<HeaderLink>,
+}
+
+impl Default for HeaderOptions {
+    fn default() -> Self {
+        Self {
+            show_logo: true,
+            wordmark: None,
+            logo_svg: None,
+            subtitle: None,
+            author: None,
+            links: Vec::new(),
+        }
+    }
+}
+
+impl HeaderOptions {
+    /// Whether the logo image is embedded at all.
+    pub fn uses_logo_image(&self) -> bool {
+        self.show_logo && self.wordmark.is_none() && self.logo_svg.is_none()
+    }
 }
 
 impl Default for GeneratorConfig {
@@ -31,7 +166,18 @@ impl Default for GeneratorConfig {
             title: "My Portfolio".to_string(),
             output_path: "output/index.html".to_string(),
             dropdown_section: Some("Projects".to_string()),
+            navigation: HashMap::new(),
+            preamble: PreamblePolicy::default(),
+            preamble_title: DEFAULT_PREAMBLE_TITLE.to_string(),
+            theme: None,
             colored_tags: HashMap::new(),
+            skill_matrix: SkillMatrixOptions::default(),
+            images: ImageOptions::default(),
+            fonts: Vec::new(),
+            attachments: Vec::new(),
+            header: HeaderOptions::default(),
+            output: OutputOptions::default(),
+            toc: TocOptions::default(),
         }
     }
 }
@@ -42,23 +188,119 @@ struct ConfigFile {
     paths: PathsConfig,
     #[serde(default)]
     colored_tags: HashMap<String, String>,
+    #[serde(default)]
+    skill_matrix: SkillMatrixConfig,
+    #[serde(default)]
+    assets: AssetsConfig,
+    #[serde(default)]
+    header: HeaderOptions,
+    #[serde(default)]
+    output: OutputOptions,
+    #[serde(default)]
+    toc: TocOptions,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct AssetsConfig {
+    #[serde(flatten)]
+    images: ImageOptions,
+    #[serde(default)]
+    fonts: Vec<FontAsset>,
+    #[serde(default)]
+    attachments: Vec<Attachment>,
 }
 
 #[derive(Debug, Deserialize)]
 struct DocumentConfig {
     title: String,
     #[serde(default)]
-    dropdown: String,
+    dropdown: SectionNames,
+    #[serde(default)]
+    navigation: HashMap<String, SectionStyle>,
+    #[serde(default)]
+    preamble: PreamblePolicy,
+    preamble_title: Option<String>,
+    theme: Option<String>,
+}
+
+/// One section name or a list of them.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum SectionNames {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Default for SectionNames {
+    fn default() -> Self {
+        SectionNames::Many(Vec::new())
+    }
+}
+
+impl SectionNames {
+    fn into_vec(self) -> Vec<String> {
+        let names = match self {
+            SectionNames::One(name) => vec![name],
+            SectionNames::Many(names) => names,
+        };
+        names
+            .into_iter()
+            .filter(|name| !name.trim().is_empty())
+            .collect()
+    }
 }
 
 #[derive(Debug, Deserialize)]
 struct PathsConfig {
     markdown: String,
+    #[serde(default)]
     logo: String,
     output: String,
 }
 
+#[derive(Debug, Default, Deserialize)]
+struct SkillMatrixConfig {
+    #[serde(default)]
+    chart: ChartKind,
+    #[serde(default)]
+    chart_only: bool,
+    detect_headings: Option<bool>,
+    heading_keywords: Option<Vec<String>>,
+    skill_columns: Option<Vec<String>>,
+    value_columns: Option<Vec<String>>,
+    notes_columns: Option<Vec<String>>,
+    team: Option<TeamOptions>,
+}
+
+impl SkillMatrixConfig {
+    /// Apply the configured values on top of the built-in defaults.
+    fn into_options(self) -> SkillMatrixOptions {
+        let defaults = SkillMatrixOptions::default();
+        SkillMatrixOptions {
+            chart: self.chart,
+            chart_only: self.chart_only,
+            team: self.team,
+            detect_headings: self.detect_headings.unwrap_or(defaults.detect_headings),
+            heading_keywords: self.heading_keywords.unwrap_or(defaults.heading_keywords),
+            skill_columns: self.skill_columns.unwrap_or(defaults.skill_columns),
+            value_columns: self.value_columns.unwrap_or(defaults.value_columns),
+            notes_columns: self.notes_columns.unwrap_or(defaults.notes_columns),
+        }
+    }
+}
+
 impl GeneratorConfig {
+    /// Navigation style of each configured section, including the dropdown section.
+    pub fn section_styles(&self) -> HashMap<String, SectionStyle> {
+        let mut styles = self.navigation.clone();
+        if let Some(ref section) = self.dropdown_section {
+            styles
+                .entry(section.clone())
+                .or_insert(SectionStyle::Dropdown);
+        }
+        styles
+    }
+
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, GeneratorError> {
         let content = std::fs::read_to_string(path.as_ref()).map_err(|e| {
             GeneratorError::ConfigReadError {
@@ -67,18 +309,19 @@ impl GeneratorConfig {
             }
         })?;
 
-        let config_file: ConfigFile = toml::from_str(&content).map_err(|e| {
-            GeneratorError::ConfigParseError {
+        let config_file: ConfigFile =
+            toml::from_str(&content).map_err(|e| GeneratorError::ConfigParseError {
                 path: path.as_ref().display().to_string(),
                 message: e.to_string(),
-            }
-        })?;
+            })?;
 
-        let dropdown_section = if config_file.document.dropdown.is_empty() {
-            None
-        } else {
-            Some(config_file.document.dropdown)
-        };
+        // The first dropdown is the classic `dropdown_section`, further ones join the navigation
+        let mut dropdowns = config_file.document.dropdown.into_vec().into_iter();
+        let dropdown_section = dropdowns.next();
+        let mut navigation = config_file.document.navigation;
+        for name in dropdowns {
+            navigation.entry(name).or_insert(SectionStyle::Dropdown);
+        }
 
         Ok(Self {
             markdown_path: config_file.paths.markdown,
@@ -86,18 +329,111 @@ impl GeneratorConfig {
             title: config_file.document.title,
             output_path: config_file.paths.output,
             dropdown_section,
+            navigation,
+            preamble: config_file.document.preamble,
+            preamble_title: config_file
+                .document
+                .preamble_title
+                .unwrap_or_else(|| DEFAULT_PREAMBLE_TITLE.to_string()),
+            theme: config_file.document.theme,
             colored_tags: config_file.colored_tags,
+            skill_matrix: config_file.skill_matrix.into_options(),
+            images: config_file.assets.images,
+            fonts: config_file.assets.fonts,
+            attachments: config_file.assets.attachments,
+            header: config_file.header,
+            output: config_file.output,
+            toc: config_file.toc,
         })
     }
 }
 
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct GenerationStats {
     pub source_lines: usize,
     pub expanded_lines: usize,
     pub achievement_markers: usize,
+    #[serde(skip)]
     pub html_content_size: usize,
     pub section_count: usize,
+    /// Local Markdown images embedded as data URIs
+    pub images: Vec<ImageReport>,
+    pub fonts_embedded: usize,
+    pub attachments_embedded: usize,
+    /// Which parts of the output take how many bytes
+    #[serde(skip)]
+    pub sizes: SizeBreakdown,
+    /// Non-fatal problems found while generating
+    pub warnings: Vec<String>,
+}
+
+/// Size of one embedded image before and after optimisation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ImageReport {
+    pub path: String,
+    pub original_size: usize,
+    pub optimized_size: usize,
+    /// Length of the data URI placed in the HTML
+    pub embedded_size: usize,
+}
+
+/// Bytes of the generated file taken by each part.
+///
+/// Embedded fonts and other data URIs in the styles count towards `styles`;
+/// `content` is everything else, i.e. the rendered document and the
+/// template markup.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SizeBreakdown {
+    pub content: usize,
+    pub styles: usize,
+    pub script: usize,
+    /// Image data URIs, including the logo
+    pub images: usize,
+    /// Attachment data URIs
+    pub attachments: usize,
+}
+
+impl SizeBreakdown {
+    /// Split `output` into its parts, given the styles and script placed in it.
+    fn measure(output: &[u8], styles: &str, script: &str) -> Self {
+        let html = String::from_utf8_lossy(output);
+        // Data URIs of the styles and script belong to those, not the content
+        let (images, attachments) = data_uri_sizes(&html);
+        let (style_images, style_attachments) = data_uri_sizes(styles);
+        let (script_images, script_attachments) = data_uri_sizes(script);
+        let images = images.saturating_sub(style_images + script_images);
+        let attachments = attachments.saturating_sub(style_attachments + script_attachments);
+
+        let styles = styles.len();
+        let script = script.len();
+        Self {
+            content: output
+                .len()
+                .saturating_sub(styles + script + images + attachments),
+            styles,
+            script,
+            images,
+            attachments,
+        }
+    }
+}
+
+/// Bytes of image and attachment data URIs in `text`; fonts are not counted.
+fn data_uri_sizes(text: &str) -> (usize, usize) {
+    let mut images = 0;
+    let mut attachments = 0;
+    for (start, _) in text.match_indices("data:") {
+        let uri = &text[start..];
+        let len = uri
+            .find(|c: char| matches!(c, '"' | '\'' | ')' | '<') || c.is_whitespace())
+            .unwrap_or(uri.len());
+        if uri.starts_with("data:image/") {
+            images += len;
+        } else if !uri.starts_with("data:font/") && len > "data:".len() {
+            attachments += len;
+        }
+    }
+    (images, attachments)
 }
 
 #[derive(Debug, Error)]
@@ -120,11 +456,24 @@ pub enum GeneratorError {
     #[error("Input file not found: {0}")]
     InputNotFound(String),
 
+    #[error("Output '{0}' would overwrite the source file '{1}'")]
+    OutputOverwritesSource(String, String),
+
     #[error("Asset embedding failed: {0}")]
     AssetError(#[from] assets::EmbedError),
 
     #[error("Rendering failed: {0}")]
     RenderError(#[from] RenderError),
+
+    #[error("JSON export failed: {0}")]
+    JsonError(#[from] serde_json::Error),
+
+    #[error("Embedded images take {total} bytes, exceeding max_embed_bytes = {limit} (largest: {largest})")]
+    ImageBudgetExceeded {
+        total: u64,
+        limit: u64,
+        largest: String,
+    },
 }
 
 pub fn generate_html_from_content(
@@ -136,10 +485,192 @@ pub fn generate_html_from_content(
     colored_tags: &HashMap<String, String>,
     assets: &Assets,
 ) -> Result<(Vec<u8>, GenerationStats), GeneratorError> {
+    let config = GeneratorConfig {
+        title: title.to_string(),
+        dropdown_section: dropdown_section.map(str::to_string),
+        colored_tags: colored_tags.clone(),
+        ..Default::default()
+    };
+    generate_html_with_config(markdown, base_path, logo_data_uri, &config, assets)
+}
+
+/// Run the full pipeline on markdown content using all options from `config`.
+///
+/// Input and output paths in `config` are ignored; `base_path` resolves includes.
+pub fn generate_html_with_config(
+    markdown: &str,
+    base_path: &str,
+    logo_data_uri: &str,
+    config: &GeneratorConfig,
+    assets: &Assets,
+) -> Result<(Vec<u8>, GenerationStats), GeneratorError> {
+    let mut stats = GenerationStats {
+        source_lines: markdown.lines().count(),
+        ..Default::default()
+    };
+    let mut doc_structure = prepare_document(markdown, base_path, config, &mut stats)?;
+    if config.toc.global && config.output.layout == PageLayout::Tabs {
+        add_contents_panel(&mut doc_structure, &config.toc.title);
+    }
+
+    // Step 7: Render using the new panel-based approach
+    let assets = theme_assets(config, assets)?;
+    stats.fonts_embedded = config.fonts.len();
+    if config.output.compress
+        && Template::parse(&assets.template).is_ok_and(|template| template.uses("panels"))
+    {
+        stats.warnings.push(
+            "The template uses 'panels', which repeats the panels uncompressed; compress = true saves little"
+                .to_string(),
+        );
+    }
+
+    let header = page_header(&config.header, logo_data_uri, &doc_structure.preamble)?;
+    let renderer = HtmlRenderer::new()
+        .with_compression(config.output.compress)
+        .with_layout(config.output.layout)
+        .with_search(config.output.search);
+    let mut output = renderer.render_page(&doc_structure, &config.title, &header, &assets)?;
+    if config.output.minify {
+        output = minify_html(&String::from_utf8_lossy(&output)).into_bytes();
+    }
+
+    // Calculate HTML content size from output
+    stats.html_content_size = output.len();
+    stats.sizes = SizeBreakdown::measure(&output, &assets.styles, &assets.script);
+
+    Ok((output, stats))
+}
+
+/// Theme assets with the configured fonts and layout styles, minified if configured.
+fn theme_assets<'a>(
+    config: &GeneratorConfig,
+    assets: &'a Assets,
+) -> Result<Cow<'a, Assets>, GeneratorError> {
+    // Fonts go before the theme styles so those can refer to the families
+    let font_css = font_face_css(&config.fonts)?;
+    let mut assets = if font_css.is_empty() {
+        Cow::Borrowed(assets)
+    } else {
+        Cow::Owned(Assets {
+            styles: format!("{}\n{}", font_css, assets.styles),
+            ..assets.clone()
+        })
+    };
+    if config.output.layout == PageLayout::Print {
+        assets = Cow::Owned(Assets {
+            styles: format!("{}\n{}", assets.styles, PRINT_STYLES),
+            ..assets.into_owned()
+        });
+    }
+    if config.output.search && config.output.layout == PageLayout::Tabs {
+        assets = Cow::Owned(Assets {
+            styles: format!("{}\n{}", assets.styles, SEARCH_STYLES),
+            script: format!("{}\n{}", assets.script, SEARCH_SCRIPT),
+            ..assets.into_owned()
+        });
+    }
+    if config.output.minify {
+        let minified = Assets {
+            styles: minify_css(&assets.styles),
+            script: minify_js(&assets.script),
+            ..assets.into_owned()
+        };
+        assets = Cow::Owned(minified);
+    }
+    Ok(assets)
+}
+
+/// Run the full pipeline and render the document with `renderer`.
+///
+/// Input and output paths in `config` are ignored; `base_path` resolves includes.
+pub fn render_with_config(
+    markdown: &str,
+    base_path: &str,
+    logo_data_uri: &str,
+    config: &GeneratorConfig,
+    assets: &Assets,
+    renderer: &dyn Renderer,
+) -> Result<(RenderOutput, GenerationStats), GeneratorError> {
     let mut stats = GenerationStats {
         source_lines: markdown.lines().count(),
         ..Default::default()
     };
+    let doc_structure = prepare_document(markdown, base_path, config, &mut stats)?;
+
+    let header = page_header(&config.header, logo_data_uri, &doc_structure.preamble)?;
+    let meta = DocumentMeta {
+        title: &config.title,
+        header: &header,
+        assets,
+    };
+    let output = renderer.render(&doc_structure, &meta, &mut stats.warnings)?;
+    stats.html_content_size = output.len();
+
+    Ok((output, stats))
+}
+
+/// Run the full pipeline and render the configured output format.
+pub fn generate_with_config(
+    markdown: &str,
+    base_path: &str,
+    logo_data_uri: &str,
+    config: &GeneratorConfig,
+    assets: &Assets,
+) -> Result<(RenderOutput, GenerationStats), GeneratorError> {
+    let render = |renderer: &dyn Renderer| {
+        let (output, mut stats) =
+            render_with_config(markdown, base_path, logo_data_uri, config, assets, renderer)?;
+        if config.toc.global {
+            stats.warnings.push(
+                "[toc] global = true is ignored: only HTML output gets a Contents panel"
+                    .to_string(),
+            );
+        }
+        Ok((output, stats))
+    };
+    match config.output.format {
+        OutputFormat::Html => {
+            let (html, stats) =
+                generate_html_with_config(markdown, base_path, logo_data_uri, config, assets)?;
+            Ok((RenderOutput::File(html), stats))
+        }
+        OutputFormat::Pages => {
+            let theme = theme_assets(config, assets)?;
+            let (mut output, mut stats) = render_with_config(
+                markdown,
+                base_path,
+                logo_data_uri,
+                config,
+                &theme,
+                &PagesRenderer::new(),
+            )?;
+            stats.fonts_embedded = config.fonts.len();
+            if config.output.minify {
+                if let RenderOutput::Files(ref mut files) = output {
+                    for file in files.iter_mut() {
+                        file.contents =
+                            minify_html(&String::from_utf8_lossy(&file.contents)).into_bytes();
+                    }
+                }
+                stats.html_content_size = output.len();
+            }
+            Ok((output, stats))
+        }
+        OutputFormat::Pdf => render(&PdfRenderer::new()),
+        OutputFormat::Epub => render(&EpubRenderer::new()),
+        OutputFormat::Markdown => render(&MarkdownRenderer::new()),
+    }
+}
+
+/// Run the Markdown pipeline up to the document structure both renderers use.
+fn prepare_document(
+    markdown: &str,
+    base_path: &str,
+    config: &GeneratorConfig,
+    stats: &mut GenerationStats,
+) -> Result<DocumentStructure, GeneratorError> {
+    let dropdown_section = config.dropdown_section.as_deref();
 
     // Step 1: Process includes
     let with_includes = process_includes(markdown, base_path, dropdown_section);
@@ -153,50 +684,374 @@ pub fn generate_html_from_content(
     stats.achievement_markers = transformed.matches("achievement-marker").count();
 
     // Step 4: Transform colored tags
-    let with_colored_tags = transform_colored_tags(&transformed, colored_tags);
+    let with_colored_tags = transform_colored_tags(&transformed, &config.colored_tags);
 
     // Step 5: Transform skill matrices
-    let with_skill_matrices = transform_skill_matrices(&with_colored_tags);
+    let with_skill_matrices = transform_skill_matrices(&with_colored_tags, &config.skill_matrix);
 
-    // Step 6: Parse document structure (extracts sections and dropdown items)
-    let doc_structure = parse_document_structure(&with_skill_matrices, dropdown_section);
-    stats.section_count = doc_structure.nav_buttons.len() + doc_structure.dropdown_items.len();
+    // Step 5b: Merge team skill matrices into the placeholder
+    let with_skill_matrices = match config.skill_matrix.team {
+        Some(ref team) => {
+            let with_team =
+                transform_team_matrix(&with_skill_matrices, team, &config.skill_matrix, stats)?;
+            with_team.unwrap_or(with_skill_matrices)
+        }
+        None => with_skill_matrices,
+    };
 
-    // Step 7: Render using the new panel-based approach
-    let renderer = HtmlRenderer::new();
-    let output = renderer.render_from_structure(&doc_structure, title, logo_data_uri, assets)?;
+    // Step 5c: Embed local images last, so no other transform scans the data URIs
+    let with_images = embed_local_images(&with_skill_matrices, base_path, &config.images, stats);
+    check_image_budget(&stats.images, &config.images)?;
+    let with_attachments = embed_attachments(&with_images, &config.attachments, stats)?;
 
-    // Calculate HTML content size from output
-    stats.html_content_size = output.len();
+    // Step 6: Parse document structure (extracts sections, dropdown and group items)
+    let section_styles = config.section_styles();
+    let mut doc_structure = parse_document_tree(&with_attachments, &section_styles);
+    stats.warnings.append(&mut doc_structure.warnings);
+    stats
+        .warnings
+        .extend(doc_structure.unmatched_sections(&section_styles));
+    stats
+        .warnings
+        .extend(doc_structure.place_preamble(config.preamble, &config.preamble_title));
+    stats.section_count = doc_structure.section_count();
 
-    Ok((output, stats))
+    // Step 6b: Mark the panels that get a table of contents
+    if config.toc.panels {
+        insert_toc_markers(&mut doc_structure, config.toc.min_headings);
+    }
+
+    Ok(doc_structure)
+}
+
+/// Replace local image paths with data URIs, resolved relative to `base_path`.
+///
+/// Remote, missing and unsupported images keep their original reference and
+/// are reported as warnings.
+fn embed_local_images(
+    content: &str,
+    base_path: &str,
+    options: &ImageOptions,
+    stats: &mut GenerationStats,
+) -> String {
+    let base = Path::new(base_path);
+    let mut embedded: HashMap<String, Option<String>> = HashMap::new();
+
+    rewrite_image_urls(content, |url| {
+        if let Some(data_uri) = embedded.get(url) {
+            return data_uri.clone();
+        }
+        let data_uri = if is_local_image(url) {
+            match embed_image_with(base.join(decode_image_path(url)), options) {
+                Ok(image) => {
+                    stats.warnings.extend(image.warning);
+                    stats.images.push(ImageReport {
+                        path: url.to_string(),
+                        original_size: image.original_size,
+                        optimized_size: image.optimized_size,
+                        embedded_size: image.data_uri.len(),
+                    });
+                    Some(image.data_uri)
+                }
+                Err(assets::EmbedError::ReadError { path, .. }) => {
+                    stats.warnings.push(format!("Image not found: {}", path));
+                    None
+                }
+                Err(e) => {
+                    stats
+                        .warnings
+                        .push(format!("Image '{}' not embedded: {}", url, e));
+                    None
+                }
+            }
+        } else if !url.starts_with("data:") && !url.starts_with('#') {
+            stats
+                .warnings
+                .push(format!("Remote image not embedded: {}", url));
+            None
+        } else {
+            None
+        };
+        embedded.insert(url.to_string(), data_uri.clone());
+        data_uri
+    })
 }
 
-pub fn generate_html(config: &GeneratorConfig, assets: &Assets) -> Result<(Vec<u8>, GenerationStats), GeneratorError> {
-    let markdown = std::fs::read_to_string(&config.markdown_path).map_err(|e| {
-        GeneratorError::MarkdownReadError {
-            path: config.markdown_path.clone(),
+/// Resolve the header settings into what the template shows, with the
+/// preamble left for the header as `intro`.
+fn page_header(
+    options: &HeaderOptions,
+    logo_data_uri: &str,
+    intro: &str,
+) -> Result<PageHeader, GeneratorError> {
+    let logo = if let Some(ref wordmark) = options.wordmark {
+        HeaderLogo::Text(wordmark.clone())
+    } else if let Some(ref path) = options.logo_svg {
+        let svg = std::fs::read_to_string(path).map_err(|e| assets::EmbedError::ReadError {
+            path: path.clone(),
             source: e,
+        })?;
+        HeaderLogo::Svg(minify_svg(&svg))
+    } else if options.show_logo && !logo_data_uri.is_empty() {
+        HeaderLogo::Image(logo_data_uri.to_string())
+    } else {
+        HeaderLogo::None
+    };
+
+    Ok(PageHeader {
+        logo,
+        subtitle: options.subtitle.clone(),
+        author: options.author.clone(),
+        links: options.links.clone(),
+        intro: Some(intro.to_string()).filter(|intro| !intro.is_empty()),
+    })
+}
+
+/// Replace `attachment:` links with download links to the embedded files.
+///
+/// Only referenced attachments are embedded; unknown names are reported.
+fn embed_attachments(
+    content: &str,
+    attachments: &[Attachment],
+    stats: &mut GenerationStats,
+) -> Result<String, GeneratorError> {
+    if attachments.is_empty() && !content.contains(ATTACHMENT_SCHEME) {
+        return Ok(content.to_string());
+    }
+
+    let mut embedded: HashMap<String, assets::EmbeddedFile> = HashMap::new();
+    let mut error = None;
+    let result = replace_attachment_links(content, |name, label| {
+        let Some(attachment) = attachments.iter().find(|a| a.name == name) else {
+            stats
+                .warnings
+                .push(format!("Unknown attachment '{}'", name));
+            return None;
+        };
+        if !embedded.contains_key(name) {
+            match embed_attachment(attachment) {
+                Ok(file) => {
+                    embedded.insert(name.to_string(), file);
+                }
+                Err(e) => {
+                    error.get_or_insert(e);
+                    return None;
+                }
+            }
         }
-    })?;
+        let file = &embedded[name];
+        Some(format!(
+            "<a class=\"attachment\" href=\"{}\" download=\"{}\">{}</a>",
+            file.data_uri,
+            escape_html(&attachment.file_name()),
+            label
+        ))
+    });
+
+    if let Some(e) = error {
+        return Err(e.into());
+    }
+    stats.attachments_embedded = embedded.len();
+    Ok(result)
+}
+
+/// Fail if the embedded images exceed the configured budget.
+fn check_image_budget(
+    images: &[ImageReport],
+    options: &ImageOptions,
+) -> Result<(), GeneratorError> {
+    let Some(limit) = options.max_embed_bytes else {
+        return Ok(());
+    };
+    let total: u64 = images.iter().map(|image| image.embedded_size as u64).sum();
+    if total <= limit {
+        return Ok(());
+    }
+    let largest = images
+        .iter()
+        .max_by_key(|image| image.embedded_size)
+        .map(|image| format!("{}, {} bytes", image.path, image.embedded_size))
+        .unwrap_or_default();
+    Err(GeneratorError::ImageBudgetExceeded {
+        total,
+        limit,
+        largest,
+    })
+}
+
+/// Build the team matrix and insert it at its placeholder.
+///
+/// Returns `None` (with a warning) if the content has no placeholder.
+fn transform_team_matrix(
+    content: &str,
+    team: &TeamOptions,
+    options: &SkillMatrixOptions,
+    stats: &mut GenerationStats,
+) -> Result<Option<String>, GeneratorError> {
+    let mut sources = Vec::with_capacity(team.members.len());
+    for member in &team.members {
+        let markdown = std::fs::read_to_string(&member.skills).map_err(|e| {
+            GeneratorError::MarkdownReadError {
+                path: member.skills.clone(),
+                source: e,
+            }
+        })?;
+        sources.push((member.name.clone(), markdown));
+    }
 
-    let base_path = Path::new(&config.markdown_path)
+    let matrix = merge_team_matrix(&sources, options);
+    stats.warnings.extend(matrix.warnings.iter().cloned());
+
+    let risks = matrix.risks(team.threshold);
+    if !risks.is_empty() {
+        stats.warnings.push(format!(
+            "Nobody reaches level {} in: {}",
+            team.threshold,
+            risks.join(", ")
+        ));
+    }
+
+    let html = render_team_matrix(&matrix, team.threshold);
+    let result = insert_team_matrix(content, &html);
+    if result.is_none() {
+        stats.warnings.push(format!(
+            "Team matrix configured but no {} placeholder found",
+            TEAM_MATRIX_MARKER
+        ));
+    }
+
+    Ok(result)
+}
+
+pub fn generate_html(
+    config: &GeneratorConfig,
+    assets: &Assets,
+) -> Result<(Vec<u8>, GenerationStats), GeneratorError> {
+    let sources = Sources::load(config, assets)?;
+    let (html, mut stats) = generate_html_with_config(
+        &sources.markdown,
+        &sources.base_path,
+        &sources.logo_data_uri,
+        config,
+        assets,
+    )?;
+    stats.warnings.extend(sources.logo_warning);
+    Ok((html, stats))
+}
+
+/// Generate the output in the configured format.
+pub fn generate(
+    config: &GeneratorConfig,
+    assets: &Assets,
+) -> Result<(RenderOutput, GenerationStats), GeneratorError> {
+    let sources = Sources::load(config, assets)?;
+    let (output, mut stats) = generate_with_config(
+        &sources.markdown,
+        &sources.base_path,
+        &sources.logo_data_uri,
+        config,
+        assets,
+    )?;
+    stats.warnings.extend(sources.logo_warning);
+    Ok((output, stats))
+}
+
+/// The Markdown and logo a config points at.
+struct Sources {
+    markdown: String,
+    base_path: String,
+    logo_data_uri: String,
+    logo_warning: Option<String>,
+}
+
+impl Sources {
+    fn load(config: &GeneratorConfig, assets: &Assets) -> Result<Self, GeneratorError> {
+        let markdown = std::fs::read_to_string(&config.markdown_path).map_err(|e| {
+            GeneratorError::MarkdownReadError {
+                path: config.markdown_path.clone(),
+                source: e,
+            }
+        })?;
+
+        let base_path = markdown_base_path(&config.markdown_path);
+
+        // Without a configured logo, or when it is missing, use the theme's
+        // or the one compiled into the binary
+        let default_logo = || {
+            assets
+                .logo
+                .clone()
+                .unwrap_or_else(assets::defaults::logo_data_uri)
+        };
+        let (logo_data_uri, logo_warning) = if !config.header.uses_logo_image() {
+            (String::new(), None)
+        } else if config.logo_path.is_empty() {
+            (default_logo(), None)
+        } else if !Path::new(&config.logo_path).exists() {
+            let warning = format!(
+                "Logo not found: {}, using the default logo",
+                config.logo_path
+            );
+            (default_logo(), Some(warning))
+        } else {
+            let logo = embed_image_with(&config.logo_path, &ImageOptions::default())?;
+            (logo.data_uri, logo.warning)
+        };
+
+        Ok(Self {
+            markdown,
+            base_path,
+            logo_data_uri,
+            logo_warning,
+        })
+    }
+}
+
+/// Directory that paths in the Markdown file are relative to.
+fn markdown_base_path(markdown_path: &str) -> String {
+    Path::new(markdown_path)
         .parent()
         .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or_else(|| ".".to_string());
+        .unwrap_or_else(|| ".".to_string())
+}
 
-    let logo_data_uri = embed_image(&config.logo_path)?;
+/// Refuse output paths that are the Markdown file or one of its includes.
+pub fn check_output_paths(
+    config: &GeneratorConfig,
+    outputs: &[PathBuf],
+) -> Result<(), GeneratorError> {
+    let markdown = std::fs::read_to_string(&config.markdown_path).unwrap_or_default();
+    let base_path = markdown_base_path(&config.markdown_path);
+    let mut sources = vec![PathBuf::from(&config.markdown_path)];
+    sources.extend(included_files(&markdown, &base_path));
+    let sources: Vec<(PathBuf, PathBuf)> = sources
+        .into_iter()
+        .filter_map(|source| Some((source.canonicalize().ok()?, source)))
+        .collect();
 
-    let dropdown_section = config.dropdown_section.as_deref();
-    generate_html_from_content(&markdown, &base_path, &config.title, &logo_data_uri, dropdown_section, &config.colored_tags, assets)
+    // Files that do not exist yet cannot be a source
+    for output in outputs {
+        let Ok(canonical) = output.canonicalize() else {
+            continue;
+        };
+        if let Some((_, source)) = sources.iter().find(|(path, _)| *path == canonical) {
+            return Err(GeneratorError::OutputOverwritesSource(
+                output.display().to_string(),
+                source.display().to_string(),
+            ));
+        }
+    }
+    Ok(())
 }
 
 pub fn validate_inputs(config: &GeneratorConfig) -> Result<(), GeneratorError> {
     if !Path::new(&config.markdown_path).exists() {
         return Err(GeneratorError::InputNotFound(config.markdown_path.clone()));
     }
-    if !Path::new(&config.logo_path).exists() {
-        return Err(GeneratorError::InputNotFound(config.logo_path.clone()));
+    if let Some(ref svg) = config.header.logo_svg {
+        if !Path::new(svg).exists() {
+            return Err(GeneratorError::InputNotFound(svg.clone()));
+        }
     }
     Ok(())
 }
@@ -220,6 +1075,7 @@ mod tests {
 </body>
 </html>"#
                 .to_string(),
+            ..Default::default()
         }
     }
 
@@ -229,7 +1085,15 @@ mod tests {
         let logo_uri = "data:image/png;base64,AAAA";
         let assets = test_assets();
 
-        let result = generate_html_from_content(markdown, ".", "Test Doc", logo_uri, None, &HashMap::new(), &assets);
+        let result = generate_html_from_content(
+            markdown,
+            ".",
+            "Test Doc",
+            logo_uri,
+            None,
+            &HashMap::new(),
+            &assets,
+        );
         assert!(result.is_ok());
 
         let (html, stats) = result.unwrap();
@@ -244,7 +1108,16 @@ mod tests {
         let logo_uri = "data:image/png;base64,AAAA";
         let assets = test_assets();
 
-        let (_, stats) = generate_html_from_content(markdown, ".", "Test", logo_uri, None, &HashMap::new(), &assets).unwrap();
+        let (_, stats) = generate_html_from_content(
+            markdown,
+            ".",
+            "Test",
+            logo_uri,
+            None,
+            &HashMap::new(),
+            &assets,
+        )
+        .unwrap();
         assert_eq!(stats.achievement_markers, 1);
     }
 
@@ -254,10 +1127,114 @@ mod tests {
         let logo_uri = "data:image/png;base64,AAAA";
         let assets = test_assets();
 
-        let (_, stats) = generate_html_from_content(markdown, ".", "Test", logo_uri, None, &HashMap::new(), &assets).unwrap();
+        let (_, stats) = generate_html_from_content(
+            markdown,
+            ".",
+            "Test",
+            logo_uri,
+            None,
+            &HashMap::new(),
+            &assets,
+        )
+        .unwrap();
         assert_eq!(stats.section_count, 3);
     }
 
+    #[test]
+    fn test_generate_html_with_config_skill_chart() {
+        let markdown = "## Skills\n\n| Skill | Level |\n|-------|-------|\n| Rust | 8 |\n";
+        let config = GeneratorConfig {
+            skill_matrix: SkillMatrixOptions {
+                chart: ChartKind::Bar,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let (html, _) = generate_html_with_config(
+            markdown,
+            ".",
+            "data:image/png;base64,AAAA",
+            &config,
+            &test_assets(),
+        )
+        .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("skill-chart-bar"));
+        assert!(html.contains("<table class=\"skill-matrix\">"));
+    }
+
+    #[test]
+    fn test_size_breakdown() {
+        let markdown = "## One\n\n![Dot](data:image/gif;base64,R0lGODlh)\n";
+        let logo_uri = "data:image/png;base64,AAAA";
+        let mut assets = test_assets();
+        assets.styles = "body { background: url(data:image/gif;base64,R0lG); }".to_string();
+
+        let (html, stats) = generate_html_from_content(
+            markdown,
+            ".",
+            "Test",
+            logo_uri,
+            None,
+            &HashMap::new(),
+            &assets,
+        )
+        .unwrap();
+        let sizes = stats.sizes;
+        assert_eq!(sizes.styles, assets.styles.len());
+        assert_eq!(sizes.script, assets.script.len());
+        assert_eq!(
+            sizes.images,
+            logo_uri.len() + "data:image/gif;base64,R0lGODlh".len()
+        );
+        assert_eq!(sizes.attachments, 0);
+        assert_eq!(
+            sizes.content + sizes.styles + sizes.script + sizes.images + sizes.attachments,
+            html.len()
+        );
+    }
+
+    #[test]
+    fn test_compress_warns_about_panels_variable() {
+        let mut assets = test_assets();
+        let mut config = GeneratorConfig {
+            dropdown_section: None,
+            ..Default::default()
+        };
+        config.output.compress = true;
+
+        let (_, stats) =
+            generate_html_with_config("## One\n\nA\n", ".", "", &config, &assets).unwrap();
+        assert!(stats.warnings.is_empty());
+
+        assets.template = "{% for panel in panels %}{{ panel.content }}{% endfor %}".to_string();
+        let (_, stats) =
+            generate_html_with_config("## One\n\nA\n", ".", "", &config, &assets).unwrap();
+        assert_eq!(stats.warnings.len(), 1);
+        assert!(stats.warnings[0].starts_with("The template uses 'panels'"));
+    }
+
+    #[test]
+    fn test_minified_output() {
+        let markdown = "## One\n\nSome   content.\n\n```\nkeep   this\n```\n";
+        let mut assets = test_assets();
+        assets.styles = "/* base */\nbody {\n    color: black;\n}\n".to_string();
+        let mut config = GeneratorConfig::default();
+
+        let (plain, _) = generate_html_with_config(markdown, ".", "", &config, &assets).unwrap();
+        config.output.minify = true;
+        let (minified, stats) =
+            generate_html_with_config(markdown, ".", "", &config, &assets).unwrap();
+        let html = String::from_utf8(minified.clone()).unwrap();
+
+        assert!(minified.len() < plain.len());
+        assert!(html.contains("<style>body{color:black}</style>"));
+        assert!(html.contains("keep   this"));
+        assert_eq!(stats.html_content_size, minified.len());
+        assert_eq!(stats.sizes.styles, "body{color:black}".len());
+    }
+
     #[test]
     fn test_generation_stats_default() {
         let stats = GenerationStats::default();
@@ -275,6 +1252,7 @@ mod tests {
             output_path: "output/test.html".to_string(),
             dropdown_section: None,
             colored_tags: HashMap::new(),
+            ..Default::default()
         };
         let result = validate_inputs(&config);
         assert!(matches!(result, Err(GeneratorError::InputNotFound(_))));
@@ -1,9 +1,17 @@
-use vellum::{generate_html, validate_inputs, GeneratorConfig, assets::Assets};
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 use std::time::Instant;
+use vellum::{
+    assets::Assets,
+    check_output_paths,
+    export::export_json,
+    generate,
+    renderer::{PageLayout, RenderOutput},
+    scaffold::{init_project, Preset},
+    validate_inputs, GeneratorConfig, OutputFormat,
+};
 
 const CONFIG_DIR: &str = "vellum";
 const CONFIG_FILE: &str = "config.toml";
@@ -13,19 +21,68 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
 
 struct Args {
     config_dir: Option<PathBuf>,
+    format: Option<OutputFormat>,
+    print: bool,
+    emit_json: bool,
     show_help: bool,
     show_version: bool,
+    init: Option<InitArgs>,
+}
+
+struct InitArgs {
+    dir: PathBuf,
+    preset: Preset,
+    force: bool,
 }
 
 fn parse_args() -> Result<Args, String> {
     let mut args = Args {
         config_dir: None,
+        format: None,
+        print: false,
+        emit_json: false,
         show_help: false,
         show_version: false,
+        init: None,
     };
 
     let mut argv: Vec<String> = env::args().skip(1).collect();
 
+    if argv.first().is_some_and(|arg| arg == "init") {
+        argv.remove(0);
+        let mut init = InitArgs {
+            dir: PathBuf::from("."),
+            preset: Preset::default(),
+            force: false,
+        };
+        let mut dir_set = false;
+        while !argv.is_empty() {
+            let arg = argv.remove(0);
+            match arg.as_str() {
+                "-h" | "--help" => args.show_help = true,
+                "-f" | "--force" => init.force = true,
+                "-p" | "--preset" => {
+                    if argv.is_empty() {
+                        return Err(format!("{} requires a preset name", arg));
+                    }
+                    init.preset = argv.remove(0).parse().map_err(|e| format!("{}", e))?;
+                }
+                _ if arg.starts_with('-') => {
+                    return Err(format!("Unknown option: {}", arg));
+                }
+                _ if !dir_set => {
+                    init.dir = PathBuf::from(arg);
+                    dir_set = true;
+                }
+                _ => {
+                    return Err(format!("Unexpected argument: {}", arg));
+                }
+            }
+        }
+        args.init = Some(init);
+        return Ok(args);
+    }
+
     while !argv.is_empty() {
         let arg = argv.remove(0);
         match arg.as_str() {
@@ -37,6 +94,26 @@ fn parse_args() -> Result<Args, String> {
                 }
                 args.config_dir = Some(PathBuf::from(argv.remove(0)));
             }
+            "--format" => {
+                if argv.is_empty() {
+                    return Err(format!(
+                        "{} requires a format (html, pages, pdf, epub or markdown)",
+                        arg
+                    ));
+                }
+                args.format = Some(argv.remove(0).parse()?);
+            }
+            "--print" => args.print = true,
+            "--emit" => {
+                if argv.is_empty() {
+                    return Err(format!("{} requires a kind (json)", arg));
+                }
+                let kind = argv.remove(0);
+                if kind != "json" {
+                    return Err(format!("Unknown emit kind '{}' (expected json)", kind));
+                }
+                args.emit_json = true;
+            }
             _ if arg.starts_with('-') => {
                 return Err(format!("Unknown option: {}", arg));
             }
@@ -54,16 +131,27 @@ fn print_help() {
     println!();
     println!("USAGE:");
     println!("    vellum [OPTIONS]");
+    println!("    vellum init [DIR] [--preset <NAME>] [--force]");
     println!();
     println!("OPTIONS:");
     println!("    -c, --config <PATH> Use config from specified directory or file");
+    println!("        --format <FMT>  Output format: html (default), pages, pdf, epub or markdown");
+    println!("        --print         All panels expanded with a table of contents, for printing");
+    println!("        --emit json     Write the parsed document and stats as JSON instead");
     println!("    -h, --help          Print help information");
     println!("    -V, --version       Print version information");
     println!();
+    println!("INIT OPTIONS:");
+    println!("    -p, --preset <NAME> portfolio (default), documentation or band");
+    println!("    -f, --force         Overwrite an existing project");
+    println!();
     println!("CONFIG SEARCH ORDER (when -c not specified):");
     println!("    1. ~/.config/vellum/config.toml");
     println!("    2. ./config/config.toml");
-    println!("    3. ../config/config.toml (up to {} levels)", MAX_PARENT_SEARCH_DEPTH);
+    println!(
+        "    3. ../config/config.toml (up to {} levels)",
+        MAX_PARENT_SEARCH_DEPTH
+    );
 }
 
 fn get_global_config_dir() -> Option<PathBuf> {
@@ -95,6 +183,123 @@ fn find_config_dir() -> Option<PathBuf> {
     None
 }
 
+fn run_init(init: &InitArgs) -> ExitCode {
+    match init_project(&init.dir, init.preset, init.force) {
+        Ok(files) => {
+            for file in &files {
+                println!(
+                    "Created {}",
+                    file.strip_prefix(&init.dir).unwrap_or(file).display()
+                );
+            }
+            println!();
+            if init.dir != Path::new(".") {
+                println!("Next: cd {} && vellum -c config", init.dir.display());
+            } else {
+                println!("Next: vellum -c config");
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// The configured output path with the format's extension instead of `.html`;
+/// without extension for formats written to a directory.
+fn format_output_path(output_path: &str, format: OutputFormat) -> String {
+    let path = Path::new(output_path);
+    let is_html = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"));
+    if !is_html || format == OutputFormat::Html {
+        return output_path.to_string();
+    }
+    path.with_extension(format.extension().unwrap_or_default())
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Name of the output format in progress messages.
+fn format_name(format: OutputFormat) -> &'static str {
+    match format {
+        OutputFormat::Html => "HTML content",
+        OutputFormat::Pages => "HTML pages",
+        OutputFormat::Pdf => "PDF",
+        OutputFormat::Epub => "EPUB",
+        OutputFormat::Markdown => "Markdown",
+    }
+}
+
+/// Paths `write_output` writes to.
+fn output_paths(output_path: &str, output: &RenderOutput) -> Vec<PathBuf> {
+    match output {
+        RenderOutput::File(_) => vec![PathBuf::from(output_path)],
+        RenderOutput::Files(files) => files
+            .iter()
+            .map(|file| Path::new(output_path).join(&file.path))
+            .collect(),
+    }
+}
+
+/// Write the output to the output path, or into it as a directory for several files.
+fn write_output(output_path: &str, output: &RenderOutput) -> std::io::Result<()> {
+    match output {
+        RenderOutput::File(contents) => fs::write(output_path, contents),
+        RenderOutput::Files(files) => {
+            for file in files {
+                let path = Path::new(output_path).join(&file.path);
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(path, &file.contents)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Write the JSON export of the document to the output path.
+fn run_export(config: &GeneratorConfig, start_time: Instant) -> ExitCode {
+    let (json, stats) = match export_json(config) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("Loaded {} lines from source", stats.source_lines);
+    println!("Found {} section(s)", stats.section_count);
+    for warning in &stats.warnings {
+        println!("Warning: {}", warning);
+    }
+
+    if let Err(e) = check_output_paths(config, &[PathBuf::from(&config.output_path)]) {
+        eprintln!("Error: {}", e);
+        return ExitCode::FAILURE;
+    }
+    if let Some(parent) = Path::new(&config.output_path).parent() {
+        if let Err(e) = fs::create_dir_all(parent) {
+            eprintln!("Error creating output directory: {}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+    if let Err(e) = fs::write(&config.output_path, &json) {
+        eprintln!("Error writing output: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    println!();
+    println!("===============================");
+    println!("Output: {} ({} bytes)", config.output_path, json.len());
+    println!("Processed in {:.2?}", start_time.elapsed());
+    println!("Done!");
+    ExitCode::SUCCESS
+}
+
 fn main() -> ExitCode {
     let args = match parse_args() {
         Ok(args) => args,
@@ -115,6 +320,10 @@ fn main() -> ExitCode {
         return ExitCode::SUCCESS;
     }
 
+    if let Some(init) = args.init {
+        return run_init(&init);
+    }
+
     let start_time = Instant::now();
 
     println!("Vellum - Static HTML Generator");
@@ -123,9 +332,12 @@ fn main() -> ExitCode {
     // Determine config directory and path
     // Accept either a directory (will append config.toml) or a direct file path
     let (config_dir, config_path) = if let Some(path) = args.config_dir {
-        if path.is_file() || path.extension().map_or(false, |ext| ext == "toml") {
+        if path.is_file() || path.extension().is_some_and(|ext| ext == "toml") {
             // Direct file path provided
-            let dir = path.parent().map(|p| p.to_path_buf()).unwrap_or_else(|| PathBuf::from("."));
+            let dir = path
+                .parent()
+                .map(|p| p.to_path_buf())
+                .unwrap_or_else(|| PathBuf::from("."));
             (dir, path)
         } else {
             // Directory provided
@@ -148,14 +360,17 @@ fn main() -> ExitCode {
                 if let Some(global_dir) = get_global_config_dir() {
                     eprintln!("  - {}", global_dir.join(CONFIG_FILE).display());
                 }
-                eprintln!("  - ./config/config.toml (and {} parent directories)", MAX_PARENT_SEARCH_DEPTH);
+                eprintln!(
+                    "  - ./config/config.toml (and {} parent directories)",
+                    MAX_PARENT_SEARCH_DEPTH
+                );
                 eprintln!("Hint: Run `just install` to set up the global config directory,");
                 eprintln!("      or create a 'config' folder with config.toml in your project");
                 return ExitCode::FAILURE;
             }
         }
     };
-    let config = match GeneratorConfig::from_file(&config_path) {
+    let mut config = match GeneratorConfig::from_file(&config_path) {
         Ok(config) => {
             println!("Config: {}", config_path.display());
             config
@@ -167,11 +382,28 @@ fn main() -> ExitCode {
         }
     };
 
-    let assets = match Assets::load(&config_dir) {
+    if let Some(format) = args.format {
+        config.output.format = format;
+    }
+    if args.print {
+        config.output.layout = PageLayout::Print;
+    }
+    config.output_path = format_output_path(&config.output_path, config.output.format);
+    if args.emit_json {
+        config.output_path = Path::new(&config.output_path)
+            .with_extension("json")
+            .to_string_lossy()
+            .to_string();
+    }
+
+    let assets = match Assets::load_with_theme(&config_dir, config.theme.as_deref()) {
         Ok(assets) => assets,
         Err(e) => {
             eprintln!("Error loading assets: {}", e);
-            eprintln!("Hint: Run `just install` to set up the config directory");
+            eprintln!(
+                "Hint: Check the `theme` setting and the files in {}",
+                config_dir.join("assets").display()
+            );
             return ExitCode::FAILURE;
         }
     };
@@ -189,11 +421,33 @@ fn main() -> ExitCode {
         .unwrap_or(0);
 
     println!("Input: {} ({} bytes)", config.markdown_path, markdown_size);
-    println!("Logo: {} ({} bytes)", config.logo_path, logo_size);
+    if let Some(ref wordmark) = config.header.wordmark {
+        println!("Logo: wordmark \"{}\"", wordmark);
+    } else if let Some(ref svg) = config.header.logo_svg {
+        println!("Logo: {} (inline SVG)", svg);
+    } else if !config.header.show_logo {
+        println!("Logo: none");
+    } else if config.logo_path.is_empty() || !Path::new(&config.logo_path).exists() {
+        let source = if assets.logo.is_some() {
+            "theme"
+        } else {
+            "built-in"
+        };
+        println!("Logo: {}", source);
+    } else {
+        println!("Logo: {} ({} bytes)", config.logo_path, logo_size);
+    }
+    if !assets.defaults_used.is_empty() {
+        println!("Assets: built-in {}", assets.defaults_used.join(", "));
+    }
     println!();
 
-    match generate_html(&config, &assets) {
-        Ok((html, stats)) => {
+    if args.emit_json {
+        return run_export(&config, start_time);
+    }
+
+    match generate(&config, &assets) {
+        Ok((output, stats)) => {
             println!("Loaded {} lines from source", stats.source_lines);
             println!("Processing file includes...");
             if stats.expanded_lines > stats.source_lines {
@@ -208,13 +462,44 @@ fn main() -> ExitCode {
             if stats.achievement_markers > 0 {
                 println!("Found {} achievement marker(s)", stats.achievement_markers);
             }
-            println!("Parsing markdown to HTML...");
-            println!("Generated {} bytes of HTML content", stats.html_content_size);
+            match config.output.format {
+                OutputFormat::Pdf => println!("Laying out PDF pages..."),
+                OutputFormat::Markdown => println!("Flattening panels to Markdown..."),
+                _ => println!("Parsing markdown to HTML..."),
+            }
+            println!(
+                "Generated {} bytes of {}",
+                stats.html_content_size,
+                format_name(config.output.format)
+            );
             println!("Extracting navigation sections...");
             println!("Found {} section(s)", stats.section_count);
             println!("Rendering final document...");
+            if !stats.images.is_empty() {
+                println!("Embedded {} image(s):", stats.images.len());
+                for image in &stats.images {
+                    println!(
+                        "  {}: {} -> {} bytes ({} bytes as data URI)",
+                        image.path, image.original_size, image.optimized_size, image.embedded_size
+                    );
+                }
+            }
+            if stats.fonts_embedded > 0 {
+                println!("Embedded {} font(s)", stats.fonts_embedded);
+            }
+            if stats.attachments_embedded > 0 {
+                println!("Embedded {} attachment(s)", stats.attachments_embedded);
+            }
             println!("Embedded assets and styles");
+            for warning in &stats.warnings {
+                println!("Warning: {}", warning);
+            }
 
+            if let Err(e) = check_output_paths(&config, &output_paths(&config.output_path, &output))
+            {
+                eprintln!("Error: {}", e);
+                return ExitCode::FAILURE;
+            }
             if let Some(parent) = std::path::Path::new(&config.output_path).parent() {
                 if !parent.exists() {
                     if let Err(e) = fs::create_dir_all(parent) {
@@ -224,13 +509,44 @@ fn main() -> ExitCode {
                 }
             }
 
-            match fs::write(&config.output_path, &html) {
+            match write_output(&config.output_path, &output) {
                 Ok(_) => {
                     let elapsed = start_time.elapsed();
 
                     println!();
                     println!("===============================");
-                    println!("Output: {} ({} bytes)", config.output_path, html.len());
+                    match output {
+                        RenderOutput::File(ref contents) => {
+                            println!("Output: {} ({} bytes)", config.output_path, contents.len())
+                        }
+                        RenderOutput::Files(ref files) => println!(
+                            "Output: {}/ ({} files, {} bytes)",
+                            config.output_path,
+                            files.len(),
+                            output.len()
+                        ),
+                    }
+                    let sizes = &stats.sizes;
+                    if config.output.format == OutputFormat::Html {
+                        println!("  Content:     {:>9} bytes", sizes.content);
+                        println!("  Styles:      {:>9} bytes", sizes.styles);
+                        println!("  Script:      {:>9} bytes", sizes.script);
+                        println!("  Images:      {:>9} bytes", sizes.images);
+                        if sizes.attachments > 0 {
+                            println!("  Attachments: {:>9} bytes", sizes.attachments);
+                        }
+                    }
+                    if config.output.minify {
+                        println!("Minified styles, script and HTML");
+                    }
+                    if config.output.compress {
+                        println!("Panels compressed (self-extracting)");
+                    }
+                    if config.output.format == OutputFormat::Html
+                        && config.output.layout == PageLayout::Print
+                    {
+                        println!("Print layout: all panels expanded");
+                    }
                     println!("Processed in {:.2?}", elapsed);
                     println!("Done!");
                     ExitCode::SUCCESS
@@ -0,0 +1,93 @@
+//! Attachment links.
+//!
+//! `[Download my CV](attachment:cv)` links to a file embedded in the
+//! document. Such links are replaced with inline HTML anchors, since a
+//! Markdown link cannot carry the `download` attribute browsers need to
+//! save a data URI.
+
+use pulldown_cmark::{html, Event, Parser, Tag, TagEnd};
+use std::ops::Range;
+
+use super::markdown::MARKDOWN_OPTIONS;
+
+/// Link scheme referring to a configured attachment.
+pub const ATTACHMENT_SCHEME: &str = "attachment:";
+
+/// Replace every `attachment:` link.
+///
+/// `replace` receives the attachment name and the rendered link label and
+/// returns the replacement HTML, or `None` to keep the link as written.
+pub fn replace_attachment_links<F>(markdown: &str, mut replace: F) -> String
+where
+    F: FnMut(&str, &str) -> Option<String>,
+{
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    // Name, source range and label events of the link being collected
+    let mut current: Option<(String, Range<usize>, Vec<Event>)> = None;
+
+    for (event, range) in Parser::new_ext(markdown, MARKDOWN_OPTIONS).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Link { ref dest_url, .. }) if current.is_none() => {
+                if let Some(name) = dest_url.strip_prefix(ATTACHMENT_SCHEME) {
+                    current = Some((name.trim().to_string(), range, Vec::new()));
+                }
+            }
+            Event::End(TagEnd::Link) if current.is_some() => {
+                if let Some((name, range, label_events)) = current.take() {
+                    let mut label = String::new();
+                    html::push_html(&mut label, label_events.into_iter());
+                    if let Some(replacement) = replace(&name, &label) {
+                        edits.push((range, replacement));
+                    }
+                }
+            }
+            event => {
+                if let Some((_, _, ref mut label_events)) = current {
+                    label_events.push(event);
+                }
+            }
+        }
+    }
+
+    let mut result = String::with_capacity(markdown.len());
+    let mut last = 0;
+    for (range, replacement) in edits {
+        result.push_str(&markdown[last..range.start]);
+        result.push_str(&replacement);
+        last = range.end;
+    }
+    result.push_str(&markdown[last..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_attachment_links() {
+        let markdown = "See [my **CV**](attachment:cv) or [site](https://example.com).";
+        let result = replace_attachment_links(markdown, |name, label| {
+            Some(format!("<a data-name=\"{}\">{}</a>", name, label))
+        });
+        assert_eq!(
+            result,
+            "See <a data-name=\"cv\">my <strong>CV</strong></a> or [site](https://example.com)."
+        );
+    }
+
+    #[test]
+    fn test_unknown_attachment_kept() {
+        let markdown = "[CV](attachment:missing)";
+        assert_eq!(replace_attachment_links(markdown, |_, _| None), markdown);
+    }
+
+    #[test]
+    fn test_attachment_in_code_ignored() {
+        let markdown = "`[CV](attachment:cv)`";
+        assert_eq!(
+            replace_attachment_links(markdown, |_, _| Some(String::new())),
+            markdown
+        );
+    }
+}
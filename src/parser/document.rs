@@ -1,16 +1,20 @@
-/// Document structure parsing module.
-///
-/// Parses processed markdown into a tree structure that separates
-/// regular sections from dropdown items for pre-rendering.
-///
-/// Each content panel receives normalized markdown where headings
-/// start at the appropriate level for rendering, regardless of
-/// their original position in the document tree.
+//! Document structure parsing module.
+//!
+//! Parses processed markdown into a navigation tree: each H2 section is a
+//! button, or a dropdown or collapsible group of its H3 subsections.
+//!
+//! Each content panel receives normalized markdown where headings
+//! start at the appropriate level for rendering, regardless of
+//! their original position in the document tree.
 
 use super::sections::slugify;
+use super::toc::fenced_lines;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// A navigation item (either button or dropdown option)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct NavItem {
     pub id: String,
     pub title: String,
@@ -18,7 +22,7 @@ pub struct NavItem {
 
 /// A content panel ready for rendering.
 /// The markdown_content has normalized heading levels.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ContentPanel {
     pub id: String,
     pub title: String,
@@ -46,27 +50,283 @@ fn shift_markdown_headings(content: &str) -> String {
         .join("\n")
 }
 
+/// How an H2 section appears in the navigation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionStyle {
+    /// A single panel with its own button
+    #[default]
+    Button,
+    /// A `<select>` of the section's H3 subsections
+    Dropdown,
+    /// A collapsible group of buttons for the H3 subsections
+    Group,
+}
+
+impl SectionStyle {
+    /// The name used in the config.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SectionStyle::Button => "button",
+            SectionStyle::Dropdown => "dropdown",
+            SectionStyle::Group => "group",
+        }
+    }
+}
+
+/// Whether the H2 title `title` is the section a config entry names.
+///
+/// Names match by slug, so case, punctuation and surrounding spaces do not
+/// matter. A name in slashes, like `/^Projects \d+$/`, is a regular
+/// expression for the title instead.
+pub fn section_matches(name: &str, title: &str) -> bool {
+    match section_pattern(name) {
+        Some(Ok(pattern)) => pattern.is_match(title.trim()),
+        Some(Err(_)) => false,
+        None => {
+            name.trim().to_lowercase() == title.trim().to_lowercase()
+                || (!slugify(name).is_empty() && slugify(name) == slugify(title))
+        }
+    }
+}
+
+/// The regular expression of a `/.../` section name, `None` for a plain name.
+fn section_pattern(name: &str) -> Option<Result<Regex, regex::Error>> {
+    let inner = name.trim().strip_prefix('/')?.strip_suffix('/')?;
+    Some(Regex::new(inner))
+}
+
+/// Style of the section titled `title`: its exact entry in `styles`, else
+/// the first matching name in sorted order.
+fn section_style(styles: &HashMap<String, SectionStyle>, title: &str) -> SectionStyle {
+    if let Some(style) = styles.get(title) {
+        return *style;
+    }
+    let mut names: Vec<&String> = styles.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .find(|name| section_matches(name, title))
+        .map(|name| styles[name])
+        .unwrap_or_default()
+}
+
+/// A top-level navigation entry, one per H2 section.
+///
+/// Dropdowns and groups list their H3 subsections as `items`, each with its
+/// own panel; a button is a panel itself and has no items.
+#[derive(Debug, Clone, Serialize)]
+pub struct NavNode {
+    pub id: String,
+    pub title: String,
+    pub style: SectionStyle,
+    pub items: Vec<NavItem>,
+}
+
+/// What becomes of the text between the H1 and the first H2.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreamblePolicy {
+    /// A panel of its own before the sections
+    #[default]
+    Panel,
+    /// A block below the page header, shown with every panel
+    Header,
+    /// Left out of the output
+    Drop,
+}
+
 /// Parsed document structure
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct DocumentStructure {
-    pub nav_buttons: Vec<NavItem>,
-    pub dropdown_title: Option<String>,
-    pub dropdown_items: Vec<NavItem>,
+    /// Navigation entries in document order
+    pub navigation: Vec<NavNode>,
     pub panels: Vec<ContentPanel>,
+    /// Markdown before the first H2, without the H1, that is not in a panel
+    pub preamble: String,
+    /// Content of dropdown and group sections that has no panel to go in
+    #[serde(skip)]
+    pub warnings: Vec<String>,
+    /// Id of the panel the preamble was placed in, which stays first
+    #[serde(skip)]
+    pub preamble_panel: Option<String>,
+}
+
+impl DocumentStructure {
+    /// The dropdown or group a panel belongs to, if any.
+    pub fn parent_of(&self, panel_id: &str) -> Option<&NavNode> {
+        self.navigation
+            .iter()
+            .find(|node| node.items.iter().any(|item| item.id == panel_id))
+    }
+
+    /// Sections shown as buttons.
+    pub fn buttons(&self) -> impl Iterator<Item = &NavNode> {
+        self.navigation
+            .iter()
+            .filter(|node| node.style == SectionStyle::Button)
+    }
+
+    /// Sections shown as dropdowns.
+    pub fn dropdowns(&self) -> impl Iterator<Item = &NavNode> {
+        self.navigation
+            .iter()
+            .filter(|node| node.style == SectionStyle::Dropdown)
+    }
+
+    /// Number of navigation entries that open a panel.
+    pub fn section_count(&self) -> usize {
+        self.navigation
+            .iter()
+            .map(|node| match node.style {
+                SectionStyle::Button => 1,
+                _ => node.items.len(),
+            })
+            .sum()
+    }
+
+    /// Warnings for configured section names that match no H2 heading,
+    /// or are not valid patterns.
+    pub fn unmatched_sections(&self, styles: &HashMap<String, SectionStyle>) -> Vec<String> {
+        let mut names: Vec<(&String, &SectionStyle)> = styles.iter().collect();
+        names.sort_by_key(|(name, _)| *name);
+
+        names
+            .into_iter()
+            .filter_map(|(name, style)| {
+                if let Some(Err(e)) = section_pattern(name) {
+                    return Some(format!(
+                        "Section pattern '{}' is not a valid regular expression: {}",
+                        name, e
+                    ));
+                }
+                if self
+                    .navigation
+                    .iter()
+                    .any(|node| section_matches(name, &node.title))
+                {
+                    return None;
+                }
+                Some(format!(
+                    "No '## ' heading matches the {} section '{}'; it is shown as usual",
+                    style.as_str(),
+                    name
+                ))
+            })
+            .collect()
+    }
+
+    /// Place the preamble according to `policy`, with `title` for its panel.
+    ///
+    /// A document without H2 sections becomes a single panel unless the
+    /// preamble is dropped. Returns a warning for content that is dropped or
+    /// not where the author might expect it.
+    pub fn place_preamble(&mut self, policy: PreamblePolicy, title: &str) -> Option<String> {
+        let lines = self.preamble.lines().count();
+        if self.navigation.is_empty() {
+            if lines == 0 {
+                return Some("The document has no content".to_string());
+            }
+            if policy == PreamblePolicy::Drop {
+                self.preamble.clear();
+                return Some(format!(
+                    "The document has no '## ' sections; its {} line(s) of text are dropped (preamble = \"drop\")",
+                    lines
+                ));
+            }
+            let preamble = std::mem::take(&mut self.preamble);
+            self.preamble_panel = Some(self.insert_panel(0, title, preamble));
+            return Some(
+                "The document has no '## ' sections; it is shown as a single panel".to_string(),
+            );
+        }
+
+        match policy {
+            _ if lines == 0 => None,
+            PreamblePolicy::Panel => {
+                let preamble = std::mem::take(&mut self.preamble);
+                self.preamble_panel = Some(self.insert_panel(0, title, preamble));
+                None
+            }
+            PreamblePolicy::Header => None,
+            PreamblePolicy::Drop => {
+                self.preamble.clear();
+                Some(format!(
+                    "{} line(s) before the first '## ' section are dropped (preamble = \"drop\")",
+                    lines
+                ))
+            }
+        }
+    }
+
+    /// Add a panel with its own button at `index` and return its id.
+    ///
+    /// `index` counts navigation entries and panels alike, so it must not be
+    /// past a dropdown or group.
+    pub fn insert_panel(&mut self, index: usize, title: &str, markdown_content: String) -> String {
+        let mut used_ids: Vec<String> = self
+            .navigation
+            .iter()
+            .flat_map(|node| {
+                std::iter::once(&node.id).chain(node.items.iter().map(|item| &item.id))
+            })
+            .cloned()
+            .collect();
+        let id = generate_unique_id(title, &mut used_ids);
+
+        self.navigation.insert(
+            index,
+            NavNode {
+                id: id.clone(),
+                title: title.to_string(),
+                style: SectionStyle::Button,
+                items: Vec::new(),
+            },
+        );
+        self.panels.insert(
+            index,
+            ContentPanel {
+                id: id.clone(),
+                title: title.to_string(),
+                markdown_content,
+            },
+        );
+        id
+    }
 }
 
 /// Parse processed markdown into a document structure.
 ///
 /// Identifies H2 sections as nav buttons, and if a dropdown section is configured,
 /// extracts H3 subsections within that section as dropdown items.
-pub fn parse_document_structure(markdown: &str, dropdown_section: Option<&str>) -> DocumentStructure {
-    let mut nav_buttons = Vec::new();
-    let mut dropdown_title = None;
-    let mut dropdown_items = Vec::new();
+pub fn parse_document_structure(
+    markdown: &str,
+    dropdown_section: Option<&str>,
+) -> DocumentStructure {
+    let styles = dropdown_section
+        .map(|title| HashMap::from([(title.to_string(), SectionStyle::Dropdown)]))
+        .unwrap_or_default();
+    parse_document_tree(markdown, &styles)
+}
+
+/// Parse processed markdown into a navigation tree.
+///
+/// `styles` maps H2 section titles to their style; other sections are buttons.
+/// The H3 subsections of dropdowns and groups become separate panels, and
+/// the text before the first of them is dropped.
+pub fn parse_document_tree(
+    markdown: &str,
+    styles: &HashMap<String, SectionStyle>,
+) -> DocumentStructure {
+    let mut navigation = Vec::new();
     let mut panels = Vec::new();
+    let mut preamble = Vec::new();
+    let mut warnings = Vec::new();
     let mut used_ids: Vec<String> = Vec::new();
 
     let lines: Vec<&str> = markdown.lines().collect();
+    let in_code: Vec<bool> = fenced_lines(markdown).map(|(_, code)| code).collect();
+    let mut h1_skipped = false;
     let mut i = 0;
 
     while i < lines.len() {
@@ -74,13 +334,14 @@ pub fn parse_document_structure(markdown: &str, dropdown_section: Option<&str>)
         let trimmed = line.trim();
 
         // Check for H2 heading (section)
-        if trimmed.starts_with("## ") && !trimmed.starts_with("### ") {
+        if !in_code[i] && trimmed.starts_with("## ") && !trimmed.starts_with("### ") {
             let title = trimmed[3..].trim().to_string();
             let id = generate_unique_id(&title, &mut used_ids);
+            let style = section_style(styles, &title);
 
-            // Check if this is the dropdown section
-            if dropdown_section.is_some() && dropdown_section.unwrap() == title {
-                dropdown_title = Some(title.clone());
+            if style != SectionStyle::Button {
+                let mut items = Vec::new();
+                let mut has_intro = false;
 
                 // Extract H3 subsections within this section
                 i += 1;
@@ -89,12 +350,18 @@ pub fn parse_document_structure(markdown: &str, dropdown_section: Option<&str>)
                     let sub_trimmed = sub_line.trim();
 
                     // Stop at next H2
-                    if sub_trimmed.starts_with("## ") && !sub_trimmed.starts_with("### ") {
+                    if !in_code[i]
+                        && sub_trimmed.starts_with("## ")
+                        && !sub_trimmed.starts_with("### ")
+                    {
                         break;
                     }
 
                     // Found an H3 subsection
-                    if sub_trimmed.starts_with("### ") && !sub_trimmed.starts_with("#### ") {
+                    if !in_code[i]
+                        && sub_trimmed.starts_with("### ")
+                        && !sub_trimmed.starts_with("#### ")
+                    {
                         let sub_title = sub_trimmed[4..].trim().to_string();
                         let sub_id = generate_unique_id(&sub_title, &mut used_ids);
 
@@ -105,9 +372,10 @@ pub fn parse_document_structure(markdown: &str, dropdown_section: Option<&str>)
                             let content_line = lines[i];
                             let content_trimmed = content_line.trim();
 
-                            if content_trimmed.starts_with("## ")
-                                || (content_trimmed.starts_with("### ")
-                                    && !content_trimmed.starts_with("#### "))
+                            if !in_code[i]
+                                && (content_trimmed.starts_with("## ")
+                                    || (content_trimmed.starts_with("### ")
+                                        && !content_trimmed.starts_with("#### ")))
                             {
                                 break;
                             }
@@ -115,7 +383,7 @@ pub fn parse_document_structure(markdown: &str, dropdown_section: Option<&str>)
                             i += 1;
                         }
 
-                        dropdown_items.push(NavItem {
+                        items.push(NavItem {
                             id: sub_id.clone(),
                             title: sub_title.clone(),
                         });
@@ -131,8 +399,29 @@ pub fn parse_document_structure(markdown: &str, dropdown_section: Option<&str>)
 
                         continue;
                     }
+                    has_intro |= !sub_trimmed.is_empty();
                     i += 1;
                 }
+
+                if items.is_empty() {
+                    warnings.push(format!(
+                        "The {} section '{}' has no '### ' subsections, so nothing of it is shown",
+                        style.as_str(),
+                        title
+                    ));
+                } else if has_intro {
+                    warnings.push(format!(
+                        "Text before the first '### ' of the {} section '{}' is not shown",
+                        style.as_str(),
+                        title
+                    ));
+                }
+                navigation.push(NavNode {
+                    id,
+                    title,
+                    style,
+                    items,
+                });
                 continue;
             }
 
@@ -143,16 +432,21 @@ pub fn parse_document_structure(markdown: &str, dropdown_section: Option<&str>)
                 let content_line = lines[i];
                 let content_trimmed = content_line.trim();
 
-                if content_trimmed.starts_with("## ") && !content_trimmed.starts_with("### ") {
+                if !in_code[i]
+                    && content_trimmed.starts_with("## ")
+                    && !content_trimmed.starts_with("### ")
+                {
                     break;
                 }
                 content_lines.push(content_line);
                 i += 1;
             }
 
-            nav_buttons.push(NavItem {
+            navigation.push(NavNode {
                 id: id.clone(),
                 title: title.clone(),
+                style,
+                items: Vec::new(),
             });
 
             panels.push(ContentPanel {
@@ -164,19 +458,32 @@ pub fn parse_document_structure(markdown: &str, dropdown_section: Option<&str>)
             continue;
         }
 
+        // Sections run to the next H2, so this is before the first one;
+        // only the document's own H1 is left out
+        let is_h1 = !in_code[i] && (trimmed.starts_with("# ") || trimmed == "#");
+        if is_h1 && !h1_skipped {
+            h1_skipped = true;
+        } else {
+            preamble.push(line);
+        }
         i += 1;
     }
 
     DocumentStructure {
-        nav_buttons,
-        dropdown_title,
-        dropdown_items,
+        navigation,
         panels,
+        preamble: if preamble.iter().all(|line| line.trim().is_empty()) {
+            String::new()
+        } else {
+            preamble.join("\n").trim_matches('\n').to_string()
+        },
+        warnings,
+        preamble_panel: None,
     }
 }
 
 /// Generate a unique ID from a title, avoiding duplicates
-fn generate_unique_id(title: &str, used_ids: &mut Vec<String>) -> String {
+pub fn generate_unique_id(title: &str, used_ids: &mut Vec<String>) -> String {
     let base_id = slugify(title);
     let mut id = base_id.clone();
     let mut counter = 1;
@@ -199,11 +506,11 @@ mod tests {
         let markdown = "## Overview\nContent 1\n\n## Projects\nContent 2\n";
         let doc = parse_document_structure(markdown, None);
 
-        assert_eq!(doc.nav_buttons.len(), 2);
-        assert_eq!(doc.nav_buttons[0].title, "Overview");
-        assert_eq!(doc.nav_buttons[1].title, "Projects");
+        assert_eq!(doc.navigation.len(), 2);
+        assert_eq!(doc.navigation[0].title, "Overview");
+        assert_eq!(doc.navigation[1].title, "Projects");
         assert_eq!(doc.panels.len(), 2);
-        assert!(doc.dropdown_title.is_none());
+        assert_eq!(doc.dropdowns().count(), 0);
     }
 
     #[test]
@@ -220,13 +527,16 @@ Worklog content
 "#;
         let doc = parse_document_structure(markdown, Some("More"));
 
-        assert_eq!(doc.nav_buttons.len(), 1);
-        assert_eq!(doc.nav_buttons[0].title, "Overview");
-        assert_eq!(doc.dropdown_title, Some("More".to_string()));
-        assert_eq!(doc.dropdown_items.len(), 2);
-        assert_eq!(doc.dropdown_items[0].title, "Projects");
-        assert_eq!(doc.dropdown_items[1].title, "Worklog");
+        assert_eq!(doc.buttons().count(), 1);
+        assert_eq!(doc.navigation[0].title, "Overview");
+        let dropdown = &doc.navigation[1];
+        assert_eq!(dropdown.title, "More");
+        assert_eq!(dropdown.style, SectionStyle::Dropdown);
+        assert_eq!(dropdown.items.len(), 2);
+        assert_eq!(dropdown.items[0].title, "Projects");
+        assert_eq!(dropdown.items[1].title, "Worklog");
         assert_eq!(doc.panels.len(), 3);
+        assert_eq!(doc.section_count(), 3);
     }
 
     #[test]
@@ -234,8 +544,8 @@ Worklog content
         let markdown = "## Test\nContent 1\n\n## Test\nContent 2\n";
         let doc = parse_document_structure(markdown, None);
 
-        assert_eq!(doc.nav_buttons[0].id, "test");
-        assert_eq!(doc.nav_buttons[1].id, "test-1");
+        assert_eq!(doc.navigation[0].id, "test");
+        assert_eq!(doc.navigation[1].id, "test-1");
     }
 
     #[test]
@@ -247,7 +557,7 @@ Line 2
 "#;
         let doc = parse_document_structure(markdown, Some("More"));
 
-        assert_eq!(doc.dropdown_items.len(), 1);
+        assert_eq!(doc.navigation[0].items.len(), 1);
         let panel = &doc.panels[0];
         assert!(panel.markdown_content.contains("Line 1"));
         assert!(panel.markdown_content.contains("Line 2"));
@@ -267,4 +577,201 @@ Content here
         assert!(panel.markdown_content.contains("### Subsection"));
         assert!(!panel.markdown_content.contains("#### Subsection"));
     }
+
+    #[test]
+    fn test_parse_several_dropdowns_and_groups() {
+        let markdown = "## Guides\nIntro\n### Install\nSteps\n### Usage\nRun it\n\n## About\nMe\n\n## Archive\n### 2023\nOld\n";
+        let styles = HashMap::from([
+            ("Guides".to_string(), SectionStyle::Group),
+            ("Archive".to_string(), SectionStyle::Dropdown),
+        ]);
+        let doc = parse_document_tree(markdown, &styles);
+
+        let styles: Vec<SectionStyle> = doc.navigation.iter().map(|node| node.style).collect();
+        assert_eq!(
+            styles,
+            [
+                SectionStyle::Group,
+                SectionStyle::Button,
+                SectionStyle::Dropdown
+            ]
+        );
+        assert_eq!(doc.navigation[0].items.len(), 2);
+        assert!(doc.navigation[1].items.is_empty());
+
+        let ids: Vec<&str> = doc.panels.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, ["install", "usage", "about", "2023"]);
+        assert_eq!(
+            doc.parent_of("usage").map(|node| node.id.as_str()),
+            Some("guides")
+        );
+        assert_eq!(
+            doc.parent_of("2023").map(|node| node.id.as_str()),
+            Some("archive")
+        );
+        assert!(doc.parent_of("about").is_none());
+        assert_eq!(doc.section_count(), 4);
+    }
+
+    #[test]
+    fn test_dropped_section_content_warns() {
+        let markdown =
+            "## Guides\nIntro\n### Install\nSteps\n\n## Archive\n\n## Talks\nNo subsections\n";
+        let styles = HashMap::from([
+            ("Guides".to_string(), SectionStyle::Group),
+            ("Archive".to_string(), SectionStyle::Dropdown),
+            ("Talks".to_string(), SectionStyle::Dropdown),
+        ]);
+        let doc = parse_document_tree(markdown, &styles);
+
+        assert_eq!(
+            doc.warnings,
+            [
+                "Text before the first '### ' of the group section 'Guides' is not shown",
+                "The dropdown section 'Archive' has no '### ' subsections, so nothing of it is shown",
+                "The dropdown section 'Talks' has no '### ' subsections, so nothing of it is shown",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_headings_in_code_blocks() {
+        let markdown = "# Notes\n\n```md\n## Not a section\n```\n\n## Guides\n### Install\n\
+                        ```md\n## Not a section\n### Not an item\n```\nSteps\n\n## About\n\
+                        ~~~\n## Not a section\n~~~\nMe\n";
+        let styles = HashMap::from([("Guides".to_string(), SectionStyle::Dropdown)]);
+        let doc = parse_document_tree(markdown, &styles);
+
+        let titles: Vec<&str> = doc.navigation.iter().map(|n| n.title.as_str()).collect();
+        assert_eq!(titles, ["Guides", "About"]);
+        assert_eq!(doc.preamble, "```md\n## Not a section\n```");
+        assert_eq!(doc.navigation[0].items.len(), 1);
+        assert_eq!(
+            doc.panels[0].markdown_content,
+            "```md\n## Not a section\n### Not an item\n```\nSteps"
+        );
+        assert_eq!(
+            doc.panels[1].markdown_content,
+            "~~~\n## Not a section\n~~~\nMe"
+        );
+        assert!(doc.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_place_preamble() {
+        let markdown = "# Title\n\nWelcome, **reader**.\n\n## About\nMe\n";
+        let doc = parse_document_structure(markdown, None);
+        assert_eq!(doc.preamble, "Welcome, **reader**.");
+
+        let mut panel = parse_document_structure(markdown, None);
+        assert_eq!(panel.place_preamble(PreamblePolicy::Panel, "About"), None);
+        let ids: Vec<&str> = panel
+            .navigation
+            .iter()
+            .map(|node| node.id.as_str())
+            .collect();
+        assert_eq!(ids, ["about-1", "about"]);
+        assert_eq!(panel.panels[0].markdown_content, "Welcome, **reader**.");
+        assert!(panel.preamble.is_empty());
+
+        let mut header = parse_document_structure(markdown, None);
+        assert_eq!(header.place_preamble(PreamblePolicy::Header, "Intro"), None);
+        assert_eq!(header.panels.len(), 1);
+        assert_eq!(header.preamble, "Welcome, **reader**.");
+
+        let mut dropped = parse_document_structure(markdown, None);
+        let warning = dropped
+            .place_preamble(PreamblePolicy::Drop, "Intro")
+            .unwrap();
+        assert!(warning.starts_with("1 line(s) before the first '## ' section are dropped"));
+        assert!(dropped.preamble.is_empty());
+
+        // Only the H1 before the sections: nothing to place
+        let mut titled = parse_document_structure("# Title\n\n## About\nMe\n", None);
+        assert_eq!(titled.place_preamble(PreamblePolicy::Drop, "Intro"), None);
+        assert_eq!(titled.panels.len(), 1);
+    }
+
+    #[test]
+    fn test_document_without_sections() {
+        let markdown = "# Notes\n\nJust text.\n\n### Details\nMore\n";
+        let mut doc = parse_document_structure(markdown, None);
+        assert!(doc.navigation.is_empty());
+
+        let warning = doc.place_preamble(PreamblePolicy::Header, "Notes").unwrap();
+        assert!(warning.contains("shown as a single panel"));
+        assert_eq!(doc.section_count(), 1);
+        assert_eq!(doc.panels[0].id, "notes");
+        assert_eq!(
+            doc.panels[0].markdown_content,
+            "Just text.\n\n### Details\nMore"
+        );
+        assert!(doc.preamble.is_empty());
+
+        let mut dropped = parse_document_structure(markdown, None);
+        assert!(dropped
+            .place_preamble(PreamblePolicy::Drop, "Notes")
+            .unwrap()
+            .contains("dropped"));
+        assert!(dropped.panels.is_empty());
+
+        // Only the title is left out, not comments in code or later H1s
+        let markdown = "# Build\n\n```bash\n# fetch the sources\ngit clone x\n```\n\n# Appendix\n";
+        let doc = parse_document_structure(markdown, None);
+        assert_eq!(
+            doc.preamble,
+            "```bash\n# fetch the sources\ngit clone x\n```\n\n# Appendix"
+        );
+
+        let mut empty = parse_document_structure("", None);
+        assert_eq!(
+            empty
+                .place_preamble(PreamblePolicy::Panel, "Notes")
+                .as_deref(),
+            Some("The document has no content")
+        );
+    }
+
+    #[test]
+    fn test_section_matches() {
+        assert!(section_matches("Projects", "Projects "));
+        assert!(section_matches("projects", "PROJECTS"));
+        assert!(section_matches("Side projects", "Side-Projects!"));
+        assert!(section_matches("/^Projects \\d{4}$/", "Projects 2024"));
+        assert!(!section_matches("/^Projects$/", "Old Projects"));
+        assert!(!section_matches("Projects", "Old Projects"));
+        assert!(!section_matches("/[/", "["));
+        assert!(!section_matches("!!", "??"));
+    }
+
+    #[test]
+    fn test_section_styles_match_loosely() {
+        let markdown = "## projects \n### One\nA\n\n## Archive 2023\n### Q1\nB\n\n## About\nC\n";
+        let styles = HashMap::from([
+            ("Projects".to_string(), SectionStyle::Dropdown),
+            ("/^Archive/".to_string(), SectionStyle::Group),
+            ("Contact".to_string(), SectionStyle::Dropdown),
+            ("/(/".to_string(), SectionStyle::Dropdown),
+        ]);
+        let doc = parse_document_tree(markdown, &styles);
+
+        let styles_found: Vec<SectionStyle> =
+            doc.navigation.iter().map(|node| node.style).collect();
+        assert_eq!(
+            styles_found,
+            [
+                SectionStyle::Dropdown,
+                SectionStyle::Group,
+                SectionStyle::Button
+            ]
+        );
+
+        let warnings = doc.unmatched_sections(&styles);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("Section pattern '/(/' is not a valid regular expression"));
+        assert_eq!(
+            warnings[1],
+            "No '## ' heading matches the dropdown section 'Contact'; it is shown as usual"
+        );
+    }
 }
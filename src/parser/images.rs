@@ -0,0 +1,286 @@
+//! Markdown image reference handling.
+//!
+//! Finds image destinations through the pulldown-cmark event stream, so
+//! included files can rebase their relative paths and local images can be
+//! replaced with data URIs before rendering.
+
+use pulldown_cmark::{Event, LinkType, Parser, Tag};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use super::markdown::MARKDOWN_OPTIONS;
+
+/// Rewrite the destination of every image in `markdown`.
+///
+/// `rewrite` receives the destination as written and returns the
+/// replacement, or `None` to leave it untouched. Inline images and the
+/// reference definitions used by images are both rewritten.
+pub fn rewrite_image_urls<F>(markdown: &str, mut rewrite: F) -> String
+where
+    F: FnMut(&str) -> Option<String>,
+{
+    let parser = Parser::new_ext(markdown, MARKDOWN_OPTIONS);
+    let definitions: Vec<(String, Range<usize>)> = parser
+        .reference_definitions()
+        .iter()
+        .map(|(_, def)| (def.dest.to_string(), def.span.clone()))
+        .collect();
+
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut referenced: HashSet<String> = HashSet::new();
+
+    for (event, range) in parser.into_offset_iter() {
+        let Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            ..
+        }) = event
+        else {
+            continue;
+        };
+        if link_type != LinkType::Inline {
+            referenced.insert(dest_url.to_string());
+            continue;
+        }
+        if let Some(dest) = find_inline_dest(markdown, &range, &dest_url) {
+            if let Some(replacement) = rewrite(&dest_url) {
+                edits.push((dest, replacement));
+            }
+        }
+    }
+
+    for (dest_url, span) in definitions {
+        if !referenced.contains(&dest_url) {
+            continue;
+        }
+        let Some(offset) = markdown[span.clone()].find("]:") else {
+            continue;
+        };
+        let Some(dest) = dest_at(markdown, span.start + offset + 2, span.end) else {
+            continue;
+        };
+        if let Some(replacement) = rewrite(&dest_url) {
+            edits.push((dest, replacement));
+        }
+    }
+
+    apply_edits(markdown, edits)
+}
+
+/// Locate the destination of an inline image `![alt](dest "title")` in the source.
+///
+/// The destination as written may differ from `dest_url`, which has its
+/// backslash escapes and entities decoded, so each candidate is decoded too.
+fn find_inline_dest(markdown: &str, range: &Range<usize>, dest_url: &str) -> Option<Range<usize>> {
+    markdown[range.clone()]
+        .match_indices("](")
+        .filter_map(|(idx, _)| dest_at(markdown, range.start + idx + 2, range.end))
+        .find(|dest| {
+            let raw = &markdown[dest.clone()];
+            raw == dest_url || decode_dest(raw, markdown[..dest.start].ends_with('<')) == dest_url
+        })
+}
+
+/// Source range of the link destination after `start` and any spaces, up to
+/// `end`, without its angle brackets.
+fn dest_at(markdown: &str, start: usize, end: usize) -> Option<Range<usize>> {
+    let slice = &markdown[start..end];
+    let offset = start + slice.len() - slice.trim_start().len();
+    let rest = &markdown[offset..end];
+
+    if let Some(inner) = rest.strip_prefix('<') {
+        let mut escaped = false;
+        let len = inner.find(|c| {
+            let close = c == '>' && !escaped;
+            escaped = c == '\\' && !escaped;
+            close
+        })?;
+        return Some(offset + 1..offset + 1 + len);
+    }
+
+    let mut depth = 0;
+    let mut escaped = false;
+    let len = rest
+        .find(|c: char| {
+            let stop = !escaped && (c.is_whitespace() || (c == ')' && depth == 0));
+            if !escaped {
+                match c {
+                    '(' => depth += 1,
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+            }
+            escaped = c == '\\' && !escaped;
+            stop
+        })
+        .unwrap_or(rest.len());
+    (len > 0).then(|| offset..offset + len)
+}
+
+/// A destination as written, with escapes and entities decoded the way
+/// pulldown-cmark does.
+fn decode_dest(raw: &str, bracketed: bool) -> String {
+    let image = if bracketed {
+        format!("![](<{}>)", raw)
+    } else {
+        format!("![]({})", raw)
+    };
+    Parser::new_ext(&image, MARKDOWN_OPTIONS)
+        .find_map(|event| match event {
+            Event::Start(Tag::Image { dest_url, .. }) => Some(dest_url.to_string()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+fn apply_edits(markdown: &str, mut edits: Vec<(Range<usize>, String)>) -> String {
+    edits.sort_by_key(|(range, _)| range.start);
+
+    let mut result = String::with_capacity(markdown.len());
+    let mut last = 0;
+    for (range, replacement) in edits {
+        if range.start < last {
+            continue;
+        }
+        result.push_str(&markdown[last..range.start]);
+        // Bare destinations end at the first space
+        let bracketed = markdown[..range.start].ends_with('<');
+        if replacement.contains(' ') && !bracketed {
+            result.push_str(&format!("<{}>", replacement));
+        } else {
+            result.push_str(&replacement);
+        }
+        last = range.end;
+    }
+    result.push_str(&markdown[last..]);
+    result
+}
+
+/// Whether an image destination refers to a local file.
+pub fn is_local_image(url: &str) -> bool {
+    !(url.is_empty() || url.starts_with('#') || url.starts_with("//") || has_scheme(url))
+}
+
+/// `https:`, `data:` and similar prefixes; a Windows drive letter is not a scheme.
+fn has_scheme(url: &str) -> bool {
+    match url.split_once(':') {
+        Some((scheme, _)) => {
+            scheme.len() > 1
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        }
+        None => false,
+    }
+}
+
+/// Make the relative image paths of an included file relative to `dir`.
+pub fn rebase_image_paths(markdown: &str, dir: &Path) -> String {
+    if dir.as_os_str().is_empty() {
+        return markdown.to_string();
+    }
+    rewrite_image_urls(markdown, |url| {
+        (is_local_image(url) && Path::new(url).is_relative())
+            .then(|| dir.join(url).to_string_lossy().replace('\\', "/"))
+    })
+}
+
+/// Decode `%XX` escapes in a local image path.
+pub fn decode_image_path(url: &str) -> String {
+    let bytes = url.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_inline_images() {
+        let markdown = "Text ![diagram](img/arch.png \"Arch\") and [link](doc.pdf)";
+        let result = rewrite_image_urls(markdown, |url| Some(format!("x/{}", url)));
+        assert_eq!(
+            result,
+            "Text ![diagram](x/img/arch.png \"Arch\") and [link](doc.pdf)"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_reference_images() {
+        let markdown = "![logo][l]\n\n[l]: logo.png\n[other]: other.png\n";
+        let result = rewrite_image_urls(markdown, |url| Some(format!("x/{}", url)));
+        assert!(result.contains("[l]: x/logo.png"));
+        assert!(result.contains("[other]: other.png"));
+    }
+
+    #[test]
+    fn test_rewrite_skips_code() {
+        let markdown = "`![a](a.png)`\n\n```\n![b](b.png)\n```\n";
+        let result = rewrite_image_urls(markdown, |_| Some("changed".to_string()));
+        assert_eq!(result, markdown);
+    }
+
+    #[test]
+    fn test_rewrite_escaped_destinations() {
+        let markdown =
+            "![a](my\\_pic.png) ![b](<a&amp;b.png> \"B\")\n\n![c][c]\n\n[c]: c\\(1\\).png\n";
+        let mut seen = Vec::new();
+        let result = rewrite_image_urls(markdown, |url| {
+            seen.push(url.to_string());
+            Some(format!("x/{}", url))
+        });
+        assert_eq!(seen, ["my_pic.png", "a&b.png", "c(1).png"]);
+        assert_eq!(
+            result,
+            "![a](x/my_pic.png) ![b](<x/a&b.png> \"B\")\n\n![c][c]\n\n[c]: x/c(1).png\n"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_wraps_spaces() {
+        let markdown = "![a](a.png)";
+        let result = rewrite_image_urls(markdown, |_| Some("my dir/a.png".to_string()));
+        assert_eq!(result, "![a](<my dir/a.png>)");
+    }
+
+    #[test]
+    fn test_is_local_image() {
+        assert!(is_local_image("img/a.png"));
+        assert!(is_local_image("/abs/a.png"));
+        assert!(is_local_image("C:/images/a.png"));
+        assert!(!is_local_image("https://example.com/a.png"));
+        assert!(!is_local_image("data:image/png;base64,AAAA"));
+        assert!(!is_local_image("//cdn.example.com/a.png"));
+    }
+
+    #[test]
+    fn test_rebase_image_paths() {
+        let markdown = "![a](a.png) ![b](https://x.org/b.png) ![c](/abs/c.png)";
+        let result = rebase_image_paths(markdown, Path::new("sub"));
+        assert_eq!(
+            result,
+            "![a](sub/a.png) ![b](https://x.org/b.png) ![c](/abs/c.png)"
+        );
+    }
+
+    #[test]
+    fn test_decode_image_path() {
+        assert_eq!(decode_image_path("my%20image.png"), "my image.png");
+        assert_eq!(decode_image_path("100%.png"), "100%.png");
+    }
+}
@@ -1,8 +1,10 @@
 use chrono::Local;
-use pulldown_cmark::{html, Options, Parser};
+use pulldown_cmark::{html, Event, Options, Parser, Tag, TagEnd};
 use regex::Regex;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use super::images::rebase_image_paths;
 
 pub fn substitute_variables(markdown: &str, base_path: &str) -> String {
     let now = Local::now();
@@ -65,7 +67,7 @@ pub fn transform_achievement_markers(markdown: &str) -> String {
     let mut result = String::with_capacity(markdown.len());
 
     for line in markdown.lines() {
-        if let Some(marker_pos) = line.find("<!") {
+        if let Some(marker_pos) = find_achievement_marker(line) {
             let (before, after) = line.split_at(marker_pos);
             let marker_text = after[2..].trim_start();
             if !marker_text.is_empty() {
@@ -85,6 +87,13 @@ pub fn transform_achievement_markers(markdown: &str) -> String {
     result
 }
 
+/// Find the position of a `<!` achievement marker, ignoring HTML comments (`<!--`).
+fn find_achievement_marker(line: &str) -> Option<usize> {
+    line.match_indices("<!")
+        .map(|(pos, _)| pos)
+        .find(|&pos| !line[pos..].starts_with("<!--"))
+}
+
 pub fn transform_colored_tags(markdown: &str, colored_tags: &HashMap<String, String>) -> String {
     if colored_tags.is_empty() {
         return markdown.to_string();
@@ -109,13 +118,57 @@ pub fn transform_colored_tags(markdown: &str, colored_tags: &HashMap<String, Str
     result
 }
 
-pub fn process_includes(markdown: &str, base_path: &str, _dropdown_section: Option<&str>) -> String {
-    process_includes_recursive(markdown, base_path, 0, false)
+/// The `.md` file an `Include: [label](path)` line refers to.
+fn include_target(line: &str) -> Option<&str> {
+    if !line.trim().starts_with("Include:") {
+        return None;
+    }
+    let start = line.find('(')?;
+    let end = line[start..].find(')')?;
+    Some(&line[start + 1..start + end]).filter(|path| path.ends_with(".md"))
 }
 
+/// Every file the includes of `markdown` refer to, recursively.
+pub fn included_files(markdown: &str, base_path: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut pending = vec![(markdown.to_string(), PathBuf::from(base_path))];
+
+    while let Some((content, base)) = pending.pop() {
+        for path in content.lines().filter_map(include_target) {
+            let full_path = base.join(path);
+            if files.contains(&full_path) || !full_path.exists() {
+                continue;
+            }
+            files.push(full_path.clone());
+            if let Ok(included) = std::fs::read_to_string(&full_path) {
+                if !included.starts_with("PRIVATE_NEVER_AS_IS") {
+                    let parent = full_path
+                        .parent()
+                        .map(Path::to_path_buf)
+                        .unwrap_or(base.clone());
+                    pending.push((included, parent));
+                }
+            }
+        }
+    }
+
+    files
+}
+
+pub fn process_includes(
+    markdown: &str,
+    base_path: &str,
+    _dropdown_section: Option<&str>,
+) -> String {
+    process_includes_recursive(markdown, base_path, Path::new(""), 0, false)
+}
+
+/// `image_dir` is the directory of the current file relative to the root
+/// file, used to keep image paths of included files resolvable.
 fn process_includes_recursive(
     markdown: &str,
     base_path: &str,
+    image_dir: &Path,
     parent_level: usize,
     is_included: bool,
 ) -> String {
@@ -130,7 +183,7 @@ fn process_includes_recursive(
         // Track current heading level
         if trimmed.starts_with('#') {
             let level = trimmed.chars().take_while(|&c| c == '#').count();
-            if level > 0 && trimmed.chars().nth(level).map_or(true, |c| c.is_whitespace()) {
+            if level > 0 && trimmed.chars().nth(level).is_none_or(|c| c.is_whitespace()) {
                 // Skip first h1 in included files (treated as file title)
                 if is_included && level == 1 && !first_h1_skipped {
                     first_h1_skipped = true;
@@ -153,42 +206,36 @@ fn process_includes_recursive(
             }
         }
 
-        if trimmed.starts_with("Include:") {
-            if let Some(start) = line.find('(') {
-                if let Some(end) = line[start..].find(')') {
-                    let path = &line[start + 1..start + end];
-                    if path.ends_with(".md") {
-                        let full_path = base.join(path);
-                        match std::fs::read_to_string(&full_path) {
-                            Ok(content) => {
-                                if content.starts_with("PRIVATE_NEVER_AS_IS") {
-                                    result.push_str(line);
-                                    result.push('\n');
-                                    continue;
-                                }
-                                let parent = full_path
-                                    .parent()
-                                    .map(|p| p.to_string_lossy().to_string())
-                                    .unwrap_or_else(|| base_path.to_string());
-                                let processed = process_includes_recursive(
-                                    &content,
-                                    &parent,
-                                    current_level,
-                                    true,
-                                );
-                                result.push_str(&processed);
-                                result.push('\n');
-                                continue;
-                            }
-                            Err(e) => {
-                                result.push_str(&format!(
-                                    "**Error: Could not include '{}': {}**\n",
-                                    path, e
-                                ));
-                                continue;
-                            }
-                        }
+        if let Some(path) = include_target(line) {
+            let full_path = base.join(path);
+            match std::fs::read_to_string(&full_path) {
+                Ok(content) => {
+                    if content.starts_with("PRIVATE_NEVER_AS_IS") {
+                        result.push_str(line);
+                        result.push('\n');
+                        continue;
                     }
+                    let parent = full_path
+                        .parent()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|| base_path.to_string());
+                    let include_dir =
+                        image_dir.join(Path::new(path).parent().unwrap_or(Path::new("")));
+                    let content = rebase_image_paths(&content, &include_dir);
+                    let processed = process_includes_recursive(
+                        &content,
+                        &parent,
+                        &include_dir,
+                        current_level,
+                        true,
+                    );
+                    result.push_str(&processed);
+                    result.push('\n');
+                    continue;
+                }
+                Err(e) => {
+                    result.push_str(&format!("**Error: Could not include '{}': {}**\n", path, e));
+                    continue;
                 }
             }
         }
@@ -199,13 +246,14 @@ fn process_includes_recursive(
     result
 }
 
-pub fn parse_markdown(markdown: &str) -> String {
-    let options = Options::ENABLE_TABLES
-        | Options::ENABLE_FOOTNOTES
-        | Options::ENABLE_STRIKETHROUGH
-        | Options::ENABLE_TASKLISTS;
+/// Markdown extensions used for rendering documents.
+pub(crate) const MARKDOWN_OPTIONS: Options = Options::ENABLE_TABLES
+    .union(Options::ENABLE_FOOTNOTES)
+    .union(Options::ENABLE_STRIKETHROUGH)
+    .union(Options::ENABLE_TASKLISTS);
 
-    let parser = Parser::new_ext(markdown, options);
+pub fn parse_markdown(markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, MARKDOWN_OPTIONS);
 
     let mut html_output = String::new();
     html::push_html(&mut html_output, parser);
@@ -213,6 +261,61 @@ pub fn parse_markdown(markdown: &str) -> String {
     html_output
 }
 
+/// Render a single line of inline Markdown (e.g. a table cell) to HTML.
+///
+/// The paragraph wrapper is dropped so the result can be placed inside other elements.
+pub fn parse_inline_markdown(text: &str) -> String {
+    let text = escape_block_markers(text);
+    let parser = Parser::new_ext(&text, Options::ENABLE_STRIKETHROUGH).filter(|event| {
+        !matches!(
+            event,
+            Event::Start(Tag::Paragraph) | Event::End(TagEnd::Paragraph)
+        )
+    });
+
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, parser);
+
+    html_output.trim_end().to_string()
+}
+
+/// Escape what would start a list, heading, quote, rule or code block, so
+/// that a cell like `-`, `1.` or `# x` stays text.
+fn escape_block_markers(text: &str) -> String {
+    let text = text.trim();
+    let rest = text.get(1..).unwrap_or_default();
+    let starts_block = match text.chars().next() {
+        Some('#') => {
+            let hashes = text.len() - text.trim_start_matches('#').len();
+            hashes <= 6
+                && text[hashes..]
+                    .chars()
+                    .next()
+                    .is_none_or(char::is_whitespace)
+        }
+        Some('>') => true,
+        Some('-' | '+' | '*') if rest.chars().next().is_none_or(char::is_whitespace) => true,
+        Some(c @ ('-' | '*' | '_')) => text.chars().all(|ch| ch == c || ch == ' ' || ch == '\t'),
+        Some('`' | '~') => text.starts_with("```") || text.starts_with("~~~"),
+        _ => false,
+    };
+    if starts_block {
+        return format!("\\{}", text);
+    }
+
+    // Ordered list items: up to nine digits followed by `.` or `)`
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let after = &text[digits..];
+    if (1..=9).contains(&digits)
+        && (after.starts_with('.') || after.starts_with(')'))
+        && after[1..].chars().next().is_none_or(char::is_whitespace)
+    {
+        return format!("{}\\{}", &text[..digits], after);
+    }
+
+    text.to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,6 +348,13 @@ mod tests {
         assert!(!output.contains("achievement-marker"));
     }
 
+    #[test]
+    fn test_html_comment_is_not_a_marker() {
+        let input = "<!-- vellum:skill-matrix -->";
+        let output = transform_achievement_markers(input);
+        assert_eq!(output.trim(), input);
+    }
+
     #[test]
     fn test_basic_markdown_parsing() {
         let input = "# Hello\n\nThis is **bold** text.";
@@ -357,6 +467,25 @@ mod tests {
         std::fs::remove_file(&private_path).ok();
     }
 
+    #[test]
+    fn test_included_files_recursive() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(dir.path().join("parts")).unwrap();
+        std::fs::write(
+            dir.path().join("parts/a.md"),
+            "# A\nInclude: [b](b.md)\nInclude: [a](a.md)\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("parts/b.md"), "# B\n").unwrap();
+
+        let base = dir.path().to_str().unwrap();
+        let files = included_files("Include: [a](parts/a.md)\nInclude: [x](missing.md)\n", base);
+        assert_eq!(
+            files,
+            [dir.path().join("parts/a.md"), dir.path().join("parts/b.md")]
+        );
+    }
+
     #[test]
     fn test_process_includes_non_md_file() {
         let input = "Include: [test](file.txt)";
@@ -370,11 +499,14 @@ mod tests {
         let include_path = dir.join("include_new.md");
         std::fs::write(&include_path, "# Included Title\n## Subtitle\nContent").unwrap();
 
-        let input = format!("## Parent Section\n\nInclude: [test]({}/include_new.md)", dir.display());
-        // We need to pass the base path correctly. 
+        let input = format!(
+            "## Parent Section\n\nInclude: [test]({}/include_new.md)",
+            dir.display()
+        );
+        // We need to pass the base path correctly.
         // Note: process_includes currently doesn't take current level, so we'll need to update its signature or use a wrapper.
         let output = process_includes(&input, ".", None);
-        
+
         // Expected behavior:
         // # Included Title is ignored.
         // ## Subtitle becomes #### Subtitle (2 + 2 = 4? OR 2 (parent) + (2 (child) - 1 (offset)) = 3?)
@@ -382,12 +514,24 @@ mod tests {
         // If parent is at ## (level 2), maybe it means child's level 2 becomes level 3?
         // Let's assume: new_level = parent_level + child_level - 1 (since level 1 is ignored and effectively acts as level 0)
         // So ## (2) in child becomes 2 + 2 - 1 = 3? OR if it's "to the level", maybe 2 + 1 = 3.
-        
+
         assert!(!output.contains("# Included Title"));
-        assert!(output.contains("### Subtitle")); 
+        assert!(output.contains("### Subtitle"));
         std::fs::remove_file(&include_path).ok();
     }
 
+    #[test]
+    fn test_process_includes_rebases_image_paths() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("chapters")).unwrap();
+        std::fs::write(dir.path().join("chapters").join("a.md"), "![x](img/x.png)").unwrap();
+
+        let input = "![root](root.png)\n\nInclude: [a](chapters/a.md)";
+        let output = process_includes(input, dir.path().to_str().unwrap(), None);
+        assert!(output.contains("![root](root.png)"));
+        assert!(output.contains("![x](chapters/img/x.png)"));
+    }
+
     #[test]
     fn test_process_includes_no_label() {
         let dir = std::env::temp_dir();
@@ -401,6 +545,39 @@ mod tests {
         std::fs::remove_file(&include_path).ok();
     }
 
+    #[test]
+    fn test_inline_markdown() {
+        let output = parse_inline_markdown("See [docs](https://example.com) and `a < b` & *more*");
+        assert_eq!(
+            output,
+            "See <a href=\"https://example.com\">docs</a> and <code>a &lt; b</code> &amp; <em>more</em>"
+        );
+        assert!(!output.contains("<p>"));
+    }
+
+    #[test]
+    fn test_inline_markdown_keeps_block_markers_as_text() {
+        let cells = [
+            ("-", "-"),
+            ("+ more", "+ more"),
+            ("1.", "1."),
+            ("2) second", "2) second"),
+            ("# x", "# x"),
+            ("> quote", "&gt; quote"),
+            ("---", "---"),
+            ("* * *", "* * *"),
+            ("```", "```"),
+            ("    indented", "indented"),
+        ];
+        for (cell, expected) in cells {
+            assert_eq!(parse_inline_markdown(cell), expected, "cell {:?}", cell);
+        }
+
+        assert_eq!(parse_inline_markdown("*more*"), "<em>more</em>");
+        assert_eq!(parse_inline_markdown("#1 pick"), "#1 pick");
+        assert_eq!(parse_inline_markdown("2024. A year"), "2024. A year");
+    }
+
     #[test]
     fn test_strikethrough() {
         let input = "This is ~~deleted~~ text.";
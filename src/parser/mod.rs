@@ -1,12 +1,34 @@
+mod attachments;
 mod document;
+mod images;
 mod markdown;
 mod sections;
+mod skill_chart;
 mod skill_matrix;
+mod team_matrix;
+mod toc;
 
-pub use document::{parse_document_structure, ContentPanel, DocumentStructure, NavItem};
+pub use attachments::{replace_attachment_links, ATTACHMENT_SCHEME};
+pub use document::{
+    generate_unique_id, parse_document_structure, parse_document_tree, section_matches,
+    ContentPanel, DocumentStructure, NavItem, NavNode, PreamblePolicy, SectionStyle,
+};
+pub use images::{decode_image_path, is_local_image, rebase_image_paths, rewrite_image_urls};
 pub use markdown::{
-    parse_markdown, process_includes, substitute_variables, transform_achievement_markers,
-    transform_colored_tags,
+    included_files, parse_inline_markdown, parse_markdown, process_includes, substitute_variables,
+    transform_achievement_markers, transform_colored_tags,
 };
 pub use sections::{extract_sections, slugify, Section};
-pub use skill_matrix::transform_skill_matrices;
+pub use skill_chart::{render_skill_charts, ChartKind};
+pub use skill_matrix::{
+    detect_columns, extract_skill_matrices, is_skill_matrix_heading, transform_skill_matrices,
+    SkillMatrixOptions, SkillRow, TableColumns,
+};
+pub use team_matrix::{
+    insert_team_matrix, merge_team_matrix, render_team_matrix, TeamMatrix, TeamMember, TeamOptions,
+    TeamRow, TEAM_MATRIX_MARKER,
+};
+pub use toc::{
+    add_contents_panel, insert_toc_markers, strip_toc_markers, TocOptions, DOCUMENT_TOC_MARKER,
+    TOC_MARKER,
+};
@@ -0,0 +1,367 @@
+//! Skill chart rendering module.
+//!
+//! Renders skill matrix rows as inline SVG (radar or horizontal bar charts),
+//! so charts stay self-contained without any JavaScript library.
+
+use serde::Deserialize;
+
+use super::skill_matrix::SkillRow;
+
+/// Highest level a skill can reach, used to scale the charts.
+const MAX_LEVEL: f64 = 10.0;
+
+/// Radar charts need at least this many axes to be readable.
+const MIN_RADAR_AXES: usize = 3;
+
+/// Chart type rendered for each skill matrix category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChartKind {
+    #[default]
+    None,
+    Radar,
+    Bar,
+}
+
+/// A chart group: one category of the skill matrix with its numeric skills.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkillGroup {
+    pub name: Option<String>,
+    pub skills: Vec<(String, f64)>,
+}
+
+/// Group skill rows by their category rows.
+///
+/// Skills before the first category row form an unnamed group. Skills
+/// without a finite numeric value are left out, as they cannot be plotted.
+pub fn group_skills(rows: &[SkillRow]) -> Vec<SkillGroup> {
+    let mut groups: Vec<SkillGroup> = Vec::new();
+
+    for row in rows {
+        match row {
+            SkillRow::Category(name) => groups.push(SkillGroup {
+                name: Some(name.clone()),
+                skills: Vec::new(),
+            }),
+            SkillRow::Skill { name, value, .. } => {
+                let Some(level) = value
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|level| level.is_finite())
+                else {
+                    continue;
+                };
+                if groups.is_empty() {
+                    groups.push(SkillGroup {
+                        name: None,
+                        skills: Vec::new(),
+                    });
+                }
+                if let Some(group) = groups.last_mut() {
+                    group
+                        .skills
+                        .push((name.clone(), level.clamp(0.0, MAX_LEVEL)));
+                }
+            }
+        }
+    }
+
+    groups.retain(|g| !g.skills.is_empty());
+    groups
+}
+
+/// Render one chart per skill group, wrapped in a `skill-chart` container.
+///
+/// Returns `None` if charts are disabled or no group has plottable values.
+pub fn render_skill_charts(rows: &[SkillRow], kind: ChartKind) -> Option<String> {
+    if kind == ChartKind::None {
+        return None;
+    }
+
+    let groups = group_skills(rows);
+    if groups.is_empty() {
+        return None;
+    }
+
+    let mut html = String::from("<div class=\"skill-chart\">\n");
+    for group in &groups {
+        // Radar charts degrade to bars when there are too few axes
+        let svg = if kind == ChartKind::Radar && group.skills.len() >= MIN_RADAR_AXES {
+            render_radar(group)
+        } else {
+            render_bar(group)
+        };
+        html.push_str("<figure>");
+        html.push_str(&svg);
+        if let Some(ref name) = group.name {
+            html.push_str(&format!("<figcaption>{}</figcaption>", escape_xml(name)));
+        }
+        html.push_str("</figure>\n");
+    }
+    html.push_str("</div>");
+
+    Some(html)
+}
+
+/// Fill colour for a skill level, matching the pastel table palette in a stronger tone.
+pub fn level_color(level: f64) -> &'static str {
+    match level.round() as u32 {
+        0..=3 => "#e57373",
+        4..=5 => "#ffd54f",
+        6 => "#ffb74d",
+        7 => "#aed581",
+        8 => "#81c784",
+        _ => "#4caf50",
+    }
+}
+
+/// Render a horizontal bar chart for a skill group.
+fn render_bar(group: &SkillGroup) -> String {
+    let label_width = 150.0;
+    let bar_width = 220.0;
+    let row_height = 24.0;
+    let width = label_width + bar_width + 40.0;
+    let height = row_height * group.skills.len() as f64 + 8.0;
+
+    let mut svg = format!(
+        "<svg class=\"skill-chart-bar\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" width=\"{}\" height=\"{}\" role=\"img\">",
+        width, height, width, height
+    );
+
+    for (i, (name, level)) in group.skills.iter().enumerate() {
+        let y = 4.0 + i as f64 * row_height;
+        let length = bar_width * level / MAX_LEVEL;
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\" font-size=\"12\" fill=\"currentColor\">{}</text>",
+            label_width - 8.0,
+            y + row_height * 0.65,
+            escape_xml(name)
+        ));
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{:.1}\" width=\"{}\" height=\"{:.1}\" fill=\"#e0e0e0\" rx=\"3\"/>",
+            label_width,
+            y + 4.0,
+            bar_width,
+            row_height - 8.0
+        ));
+        svg.push_str(&format!(
+            "<rect x=\"{}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\" rx=\"3\"/>",
+            label_width,
+            y + 4.0,
+            length,
+            row_height - 8.0,
+            level_color(*level)
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" font-size=\"12\" fill=\"currentColor\">{}</text>",
+            label_width + bar_width + 6.0,
+            y + row_height * 0.65,
+            format_level(*level)
+        ));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Render a radar (spider) chart for a skill group.
+fn render_radar(group: &SkillGroup) -> String {
+    let size = 360.0;
+    let center = size / 2.0;
+    let radius = 110.0;
+    let axes = group.skills.len();
+
+    let point = |index: usize, value: f64| -> (f64, f64) {
+        let angle =
+            std::f64::consts::TAU * index as f64 / axes as f64 - std::f64::consts::FRAC_PI_2;
+        let r = radius * value / MAX_LEVEL;
+        (center + r * angle.cos(), center + r * angle.sin())
+    };
+
+    let mut svg = format!(
+        "<svg class=\"skill-chart-radar\" xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {} {}\" width=\"{}\" height=\"{}\" role=\"img\">",
+        size, size, size, size
+    );
+
+    // Concentric grid rings at every second level
+    for ring in (2..=10).step_by(2) {
+        let points: Vec<String> = (0..axes)
+            .map(|i| {
+                let (x, y) = point(i, ring as f64);
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect();
+        svg.push_str(&format!(
+            "<polygon points=\"{}\" fill=\"none\" stroke=\"#cccccc\" stroke-width=\"1\"/>",
+            points.join(" ")
+        ));
+    }
+
+    // Axes and labels
+    for (i, (name, _)) in group.skills.iter().enumerate() {
+        let (x, y) = point(i, MAX_LEVEL);
+        svg.push_str(&format!(
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#cccccc\" stroke-width=\"1\"/>",
+            center, center, x, y
+        ));
+
+        let (lx, ly) = point(i, MAX_LEVEL * 1.12);
+        let anchor = if (lx - center).abs() < 1.0 {
+            "middle"
+        } else if lx > center {
+            "start"
+        } else {
+            "end"
+        };
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"{}\" dominant-baseline=\"middle\" font-size=\"11\" fill=\"currentColor\">{}</text>",
+            lx,
+            ly,
+            anchor,
+            escape_xml(name)
+        ));
+    }
+
+    // Value polygon
+    let values: Vec<String> = group
+        .skills
+        .iter()
+        .enumerate()
+        .map(|(i, (_, level))| {
+            let (x, y) = point(i, *level);
+            format!("{:.1},{:.1}", x, y)
+        })
+        .collect();
+    svg.push_str(&format!(
+        "<polygon points=\"{}\" fill=\"#2dd4bf\" fill-opacity=\"0.35\" stroke=\"#14b8a6\" stroke-width=\"2\"/>",
+        values.join(" ")
+    ));
+
+    for (i, (name, level)) in group.skills.iter().enumerate() {
+        let (x, y) = point(i, *level);
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3.5\" fill=\"{}\"><title>{}: {}</title></circle>",
+            x,
+            y,
+            level_color(*level),
+            escape_xml(name),
+            format_level(*level)
+        ));
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Format a level without a trailing `.0` for whole numbers.
+fn format_level(level: f64) -> String {
+    if level.fract() == 0.0 {
+        format!("{}", level as u32)
+    } else {
+        format!("{:.1}", level)
+    }
+}
+
+/// Escape text for use inside SVG/XML markup.
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skill(name: &str, value: &str) -> SkillRow {
+        SkillRow::Skill {
+            name: name.to_string(),
+            value: value.to_string(),
+            notes: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_group_skills_by_category() {
+        let rows = vec![
+            SkillRow::Category("Languages".to_string()),
+            skill("Rust", "8"),
+            skill("Go", "6"),
+            SkillRow::Category("Tools".to_string()),
+            skill("Docker", "7"),
+        ];
+        let groups = group_skills(&rows);
+
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, Some("Languages".to_string()));
+        assert_eq!(groups[0].skills.len(), 2);
+        assert_eq!(groups[1].skills[0], ("Docker".to_string(), 7.0));
+    }
+
+    #[test]
+    fn test_group_skills_without_category() {
+        let rows = vec![skill("Rust", "8"), skill("Soft skills", "n/a")];
+        let groups = group_skills(&rows);
+
+        assert_eq!(groups.len(), 1);
+        assert!(groups[0].name.is_none());
+        assert_eq!(groups[0].skills.len(), 1);
+    }
+
+    #[test]
+    fn test_group_skills_skips_non_finite() {
+        let rows = vec![
+            skill("Rust", "8"),
+            skill("Go", "NaN"),
+            skill("C", "inf"),
+            skill("Zig", "-infinity"),
+        ];
+        let groups = group_skills(&rows);
+
+        assert_eq!(groups[0].skills, [("Rust".to_string(), 8.0)]);
+    }
+
+    #[test]
+    fn test_render_bar_chart() {
+        let rows = vec![skill("Rust", "8"), skill("C & C++", "5")];
+        let html = render_skill_charts(&rows, ChartKind::Bar).unwrap();
+
+        assert!(html.contains("skill-chart-bar"));
+        assert!(html.contains("C &amp; C++"));
+        assert_eq!(html.matches("<svg").count(), 1);
+    }
+
+    #[test]
+    fn test_render_radar_chart_per_category() {
+        let rows = vec![
+            SkillRow::Category("Languages".to_string()),
+            skill("Rust", "8"),
+            skill("Go", "6"),
+            skill("Python", "7"),
+            SkillRow::Category("Tools".to_string()),
+            skill("Docker", "7"),
+        ];
+        let html = render_skill_charts(&rows, ChartKind::Radar).unwrap();
+
+        assert!(html.contains("skill-chart-radar"));
+        assert!(html.contains("<figcaption>Languages</figcaption>"));
+        // Too few axes for a radar, falls back to bars
+        assert!(html.contains("skill-chart-bar"));
+    }
+
+    #[test]
+    fn test_render_disabled() {
+        let rows = vec![skill("Rust", "8")];
+        assert!(render_skill_charts(&rows, ChartKind::None).is_none());
+    }
+
+    #[test]
+    fn test_svg_has_no_blank_lines() {
+        // Blank lines would terminate the surrounding Markdown HTML block
+        let rows = vec![skill("Rust", "8"), skill("Go", "6"), skill("Python", "7")];
+        let html = render_skill_charts(&rows, ChartKind::Radar).unwrap();
+        assert!(!html.contains("\n\n"));
+    }
+}
@@ -1,6 +1,23 @@
-/// Skill matrix detection and transformation module.
-///
-/// Provides flexible pattern recognition for skill/competency tables in Markdown.
+//! Skill matrix detection and transformation module.
+//!
+//! Provides flexible pattern recognition for skill/competency tables in Markdown.
+//!
+//! Tables are picked up either explicitly (a `<!-- vellum:skill-matrix -->`
+//! annotation or a fenced ```` ```skills ```` block) or heuristically, when
+//! they follow a heading containing one of the configured keywords.
+
+use pulldown_cmark::Alignment;
+use serde::Serialize;
+
+use super::markdown::parse_inline_markdown;
+use super::skill_chart::{render_skill_charts, ChartKind};
+use super::team_matrix::TeamOptions;
+
+/// Annotation marking the following table as a skill matrix.
+const SKILL_MATRIX_MARKER: &str = "<!-- vellum:skill-matrix -->";
+
+/// Info string of a fenced code block containing a skill matrix table.
+const SKILL_MATRIX_FENCE: &str = "skills";
 
 /// Keywords that indicate a skill matrix heading (case insensitive).
 const HEADING_KEYWORDS: &[&str] = &["skill", "matrix", "competenc", "proficienc"];
@@ -46,8 +63,80 @@ pub struct TableColumns {
     pub notes_header: Option<String>,
 }
 
-/// Check if a heading line indicates a skill matrix section.
+/// A parsed row of a skill matrix table.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillRow {
+    /// Category row (bold skill name, empty value)
+    Category(String),
+    Skill {
+        name: String,
+        value: String,
+        notes: String,
+    },
+}
+
+/// Options controlling how skill matrices are detected and rendered.
+#[derive(Debug, Clone)]
+pub struct SkillMatrixOptions {
+    /// Chart rendered alongside each matrix
+    pub chart: ChartKind,
+    /// Render only the chart and omit the table
+    pub chart_only: bool,
+    /// Merge several people's matrices into a team matrix
+    pub team: Option<TeamOptions>,
+    /// Detect skill matrices from heading keywords (explicit markers always work)
+    pub detect_headings: bool,
+    pub heading_keywords: Vec<String>,
+    pub skill_columns: Vec<String>,
+    pub value_columns: Vec<String>,
+    pub notes_columns: Vec<String>,
+}
+
+impl Default for SkillMatrixOptions {
+    fn default() -> Self {
+        Self {
+            chart: ChartKind::None,
+            chart_only: false,
+            team: None,
+            detect_headings: true,
+            heading_keywords: to_strings(HEADING_KEYWORDS),
+            skill_columns: to_strings(SKILL_COLUMNS),
+            value_columns: to_strings(VALUE_COLUMNS),
+            notes_columns: to_strings(NOTES_COLUMNS),
+        }
+    }
+}
+
+impl SkillMatrixOptions {
+    /// Check if a heading line indicates a skill matrix section.
+    ///
+    /// Always false when heading detection is disabled.
+    pub fn is_heading(&self, line: &str) -> bool {
+        self.detect_headings && heading_matches(line, &self.heading_keywords)
+    }
+
+    /// Detect column indices from a table header row using the configured aliases.
+    pub fn detect_columns(&self, header_row: &str) -> Option<TableColumns> {
+        detect_columns_with(
+            header_row,
+            &self.skill_columns,
+            &self.value_columns,
+            &self.notes_columns,
+        )
+    }
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+/// Check if a heading line indicates a skill matrix section, using the default keywords.
 pub fn is_skill_matrix_heading(line: &str) -> bool {
+    heading_matches(line, HEADING_KEYWORDS)
+}
+
+fn heading_matches<S: AsRef<str>>(line: &str, keywords: &[S]) -> bool {
     let trimmed = line.trim();
 
     if !trimmed.starts_with('#') {
@@ -69,14 +158,30 @@ pub fn is_skill_matrix_heading(line: &str) -> bool {
     let heading_text = trimmed[hash_count..].trim().to_lowercase();
 
     // Check if any keyword is present in the heading
-    HEADING_KEYWORDS
+    keywords
         .iter()
-        .any(|keyword| heading_text.contains(keyword))
+        .any(|keyword| heading_text.contains(&keyword.as_ref().to_lowercase()))
 }
 
-/// Detect column indices from a table header row.
+/// Detect column indices from a table header row, using the default column names.
 pub fn detect_columns(header_row: &str) -> Option<TableColumns> {
-    let cols: Vec<&str> = header_row.split('|').map(|s| s.trim()).collect();
+    detect_columns_with(header_row, SKILL_COLUMNS, VALUE_COLUMNS, NOTES_COLUMNS)
+}
+
+fn column_matches<S: AsRef<str>>(lower: &str, names: &[S]) -> bool {
+    names.iter().any(|name| {
+        let name = name.as_ref().to_lowercase();
+        lower == name || lower.starts_with(&name)
+    })
+}
+
+fn detect_columns_with<S: AsRef<str>>(
+    header_row: &str,
+    skill_columns: &[S],
+    value_columns: &[S],
+    notes_columns: &[S],
+) -> Option<TableColumns> {
+    let cols = split_table_row(header_row);
 
     let mut skill_idx = None;
     let mut value_idx = None;
@@ -89,33 +194,21 @@ pub fn detect_columns(header_row: &str) -> Option<TableColumns> {
         let lower = col.to_lowercase();
 
         // Check for skill column
-        if skill_idx.is_none()
-            && SKILL_COLUMNS
-                .iter()
-                .any(|&s| lower == s || lower.starts_with(s))
-        {
+        if skill_idx.is_none() && column_matches(&lower, skill_columns) {
             skill_idx = Some(i);
             skill_header = col.to_string();
             continue;
         }
 
         // Check for value column
-        if value_idx.is_none()
-            && VALUE_COLUMNS
-                .iter()
-                .any(|&v| lower == v || lower.starts_with(v))
-        {
+        if value_idx.is_none() && column_matches(&lower, value_columns) {
             value_idx = Some(i);
             value_header = col.to_string();
             continue;
         }
 
         // Check for notes column
-        if notes_idx.is_none()
-            && NOTES_COLUMNS
-                .iter()
-                .any(|&n| lower == n || lower.starts_with(n))
-        {
+        if notes_idx.is_none() && column_matches(&lower, notes_columns) {
             notes_idx = Some(i);
             notes_header = Some(col.to_string());
         }
@@ -136,45 +229,94 @@ pub fn detect_columns(header_row: &str) -> Option<TableColumns> {
 }
 
 /// Transform skill matrix content into HTML.
-pub fn transform_skill_matrices(content: &str) -> String {
+pub fn transform_skill_matrices(content: &str, options: &SkillMatrixOptions) -> String {
+    scan_skill_matrices(content, options, |table_lines| {
+        render_skill_matrix(table_lines, options)
+    })
+}
+
+/// Extract the rows of every skill matrix found in the content.
+pub fn extract_skill_matrices(content: &str, options: &SkillMatrixOptions) -> Vec<Vec<SkillRow>> {
+    let mut matrices = Vec::new();
+    scan_skill_matrices(content, options, |table_lines| {
+        let table = parse_table(table_lines, options)?;
+        matrices.push(table.rows);
+        Some(String::new())
+    });
+    matrices
+}
+
+/// Walk the content and hand every detected skill matrix table to `on_table`.
+///
+/// When `on_table` returns HTML, it replaces the table (and its marker or fence);
+/// otherwise the lines are kept unchanged.
+fn scan_skill_matrices<F>(content: &str, options: &SkillMatrixOptions, mut on_table: F) -> String
+where
+    F: FnMut(&[&str]) -> Option<String>,
+{
     let lines: Vec<&str> = content.lines().collect();
     let mut result = String::with_capacity(content.len());
     let mut i = 0;
 
     while i < lines.len() {
         let line = lines[i];
+        let trimmed = line.trim();
 
-        if is_skill_matrix_heading(line) {
-            // Output the heading first (so section navigation works)
-            result.push_str(line);
-            result.push('\n');
+        // Explicit fenced block: ```skills
+        if let Some(fence) = skill_matrix_fence(trimmed) {
+            let mut j = i + 1;
+            while j < lines.len() && !lines[j].trim().starts_with(fence) {
+                j += 1;
+            }
+            let table_lines: Vec<&str> = lines[i + 1..j.min(lines.len())]
+                .iter()
+                .map(|l| l.trim())
+                .filter(|l| l.starts_with('|'))
+                .collect();
+
+            if let Some(html) = on_table(&table_lines) {
+                result.push_str(&html);
+                result.push_str("\n\n");
+                i = j + 1;
+                continue;
+            }
+        }
 
-            // Collect any content between heading and table
+        // Explicit annotation: <!-- vellum:skill-matrix -->
+        if trimmed == SKILL_MATRIX_MARKER {
             let mut table_start = i + 1;
-            while table_start < lines.len() && !lines[table_start].trim().starts_with('|') {
-                result.push_str(lines[table_start]);
-                result.push('\n');
+            while table_start < lines.len() && lines[table_start].trim().is_empty() {
                 table_start += 1;
             }
+            let (table_lines, j) = collect_table(&lines, table_start);
 
-            let mut table_lines: Vec<&str> = Vec::new();
-            let mut j = table_start;
-            while j < lines.len() {
-                let table_line = lines[j].trim();
-                if table_line.starts_with('|') {
-                    table_lines.push(table_line);
-                    j += 1;
-                } else if table_line.is_empty() {
-                    j += 1;
-                } else {
-                    break;
-                }
+            if let Some(html) = on_table(&table_lines) {
+                result.push_str(&html);
+                result.push_str("\n\n");
+                i = j;
+                continue;
             }
+        }
 
-            if let Some(html) = render_skill_matrix_table(&table_lines) {
-                result.push_str("<div class=\"skill-matrix-container\">\n");
+        if options.is_heading(line) {
+            // Content between heading and table, up to the next heading
+            let mut table_start = i + 1;
+            while table_start < lines.len()
+                && !lines[table_start].trim().starts_with('|')
+                && !lines[table_start].trim().starts_with('#')
+            {
+                table_start += 1;
+            }
+            let (table_lines, j) = collect_table(&lines, table_start);
+
+            if let Some(html) = on_table(&table_lines) {
+                // Output the heading first (so section navigation works)
+                for kept in &lines[i..table_start] {
+                    result.push_str(kept);
+                    result.push('\n');
+                }
                 result.push_str(&html);
-                result.push_str("\n</div>\n\n");
+                result.push_str("\n\n");
                 i = j;
                 continue;
             }
@@ -188,58 +330,246 @@ pub fn transform_skill_matrices(content: &str) -> String {
     result
 }
 
-/// Render a skill matrix table as HTML.
-fn render_skill_matrix_table(table_lines: &[&str]) -> Option<String> {
+/// Return the closing fence if the line opens a skill matrix code block.
+fn skill_matrix_fence(trimmed: &str) -> Option<&'static str> {
+    for fence in ["```", "~~~"] {
+        if let Some(info) = trimmed.strip_prefix(fence) {
+            if info.trim() == SKILL_MATRIX_FENCE {
+                return Some(fence);
+            }
+        }
+    }
+    None
+}
+
+/// Collect table rows starting at `start`, skipping blank lines between rows.
+/// Returns the rows and the index of the first line after the table.
+fn collect_table<'a>(lines: &[&'a str], start: usize) -> (Vec<&'a str>, usize) {
+    let mut table_lines = Vec::new();
+    let mut j = start;
+    while j < lines.len() {
+        let table_line = lines[j].trim();
+        if table_line.starts_with('|') {
+            table_lines.push(table_line);
+            j += 1;
+        } else if table_line.is_empty() {
+            j += 1;
+        } else {
+            break;
+        }
+    }
+    (table_lines, j)
+}
+
+/// A skill matrix table split into columns, alignments and rows.
+struct ParsedTable {
+    columns: TableColumns,
+    alignments: Vec<Alignment>,
+    rows: Vec<SkillRow>,
+}
+
+/// Parse table lines (header, delimiter row, body) into a skill matrix.
+///
+/// Returns `None` if the delimiter row is missing or the columns are not recognised.
+fn parse_table(table_lines: &[&str], options: &SkillMatrixOptions) -> Option<ParsedTable> {
     if table_lines.len() < 3 {
         return None;
     }
 
-    let columns = detect_columns(table_lines[0])?;
-    let has_notes = columns.notes_idx.is_some();
-    let colspan = if has_notes { 3 } else { 2 };
+    let alignments = parse_alignments(table_lines[1])?;
+    let columns = options.detect_columns(table_lines[0])?;
+    let rows = parse_skill_rows(&table_lines[2..], &columns);
 
-    let mut html = String::from("<table class=\"skill-matrix\">\n<thead><tr>");
-    html.push_str(&format!("<th>{}</th>", columns.skill_header));
-    html.push_str(&format!("<th>{}</th>", columns.value_header));
-    if let Some(ref notes_header) = columns.notes_header {
-        html.push_str(&format!("<th>{}</th>", notes_header));
+    Some(ParsedTable {
+        columns,
+        alignments,
+        rows,
+    })
+}
+
+/// Split a table row into trimmed cells, honouring `\|` escapes.
+///
+/// Like `str::split('|')`, the text before the leading pipe is kept as the
+/// first (usually empty) cell, so column indices start at 1.
+fn split_table_row(line: &str) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
     }
-    html.push_str("</tr></thead>\n<tbody>\n");
+    cells.push(current);
+
+    cells.iter().map(|cell| cell.trim().to_string()).collect()
+}
+
+/// Parse a delimiter row like `|:---|:---:|---:|` into column alignments.
+///
+/// Returns `None` if the line is not a delimiter row.
+fn parse_alignments(line: &str) -> Option<Vec<Alignment>> {
+    let cells = split_table_row(line);
+    let mut alignments = Vec::with_capacity(cells.len());
+
+    for (i, cell) in cells.iter().enumerate() {
+        if cell.is_empty() && (i == 0 || i == cells.len() - 1) {
+            // Outside the leading/trailing pipes
+            alignments.push(Alignment::None);
+            continue;
+        }
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        if dashes.is_empty() || !dashes.chars().all(|c| c == '-') {
+            return None;
+        }
+        alignments.push(match (cell.starts_with(':'), cell.ends_with(':')) {
+            (true, true) => Alignment::Center,
+            (true, false) => Alignment::Left,
+            (false, true) => Alignment::Right,
+            (false, false) => Alignment::None,
+        });
+    }
+
+    Some(alignments)
+}
+
+/// Render a skill matrix (table and/or chart) wrapped in its container.
+fn render_skill_matrix(table_lines: &[&str], options: &SkillMatrixOptions) -> Option<String> {
+    let table = parse_table(table_lines, options)?;
+    let chart = render_skill_charts(&table.rows, options.chart);
+
+    let mut html = String::new();
+    if chart.is_some() {
+        html.push_str("<div class=\"skill-matrix-container with-chart\">\n");
+    } else {
+        html.push_str("<div class=\"skill-matrix-container\">\n");
+    }
+    // Without a chart there is nothing to replace the table with
+    if !options.chart_only || chart.is_none() {
+        html.push_str(&render_skill_matrix_table(&table));
+        html.push('\n');
+    }
+    if let Some(chart) = chart {
+        html.push_str(&chart);
+        html.push('\n');
+    }
+    html.push_str("</div>");
+
+    Some(html)
+}
 
-    for line in table_lines.iter().skip(2) {
-        let cols: Vec<&str> = line.split('|').map(|s| s.trim()).collect();
+/// Parse the body rows of a skill matrix table.
+pub fn parse_skill_rows(body_lines: &[&str], columns: &TableColumns) -> Vec<SkillRow> {
+    let mut rows = Vec::new();
 
-        let skill = cols.get(columns.skill_idx).unwrap_or(&"");
-        let value = cols.get(columns.value_idx).unwrap_or(&"");
-        let notes = columns
-            .notes_idx
-            .and_then(|idx| cols.get(idx).copied())
-            .unwrap_or("");
+    for line in body_lines {
+        let cols = split_table_row(line);
+
+        let cell = |idx: usize| cols.get(idx).map(String::as_str).unwrap_or("");
+        let skill = cell(columns.skill_idx);
+        let value = cell(columns.value_idx);
+        let notes = columns.notes_idx.map(cell).unwrap_or("");
 
         // Category row detection (bold skill name, empty value)
         if skill.starts_with("**") && skill.ends_with("**") && value.is_empty() {
             let category = skill.trim_start_matches("**").trim_end_matches("**");
-            html.push_str(&format!(
-                "<tr class=\"category-row\"><td colspan=\"{}\"><strong>{}</strong></td></tr>\n",
-                colspan, category
-            ));
+            rows.push(SkillRow::Category(category.to_string()));
         } else if !skill.is_empty() {
-            let styled_notes = style_notes(notes);
-            let level_class = format!("level-{}", value);
-
-            html.push_str(&format!(
-                "<tr><td>{}</td><td class=\"{}\">{}</td>",
-                skill, level_class, value
-            ));
-            if has_notes {
-                html.push_str(&format!("<td>{}</td>", styled_notes));
+            rows.push(SkillRow::Skill {
+                name: skill.to_string(),
+                value: value.to_string(),
+                notes: notes.to_string(),
+            });
+        }
+    }
+
+    rows
+}
+
+/// Render a skill matrix table as HTML.
+fn render_skill_matrix_table(table: &ParsedTable) -> String {
+    let columns = &table.columns;
+    let has_notes = columns.notes_idx.is_some();
+    let colspan = if has_notes { 3 } else { 2 };
+    let align = |idx: usize| alignment_attr(table.alignments.get(idx));
+
+    let mut html = String::from("<table class=\"skill-matrix\">\n<thead><tr>");
+    html.push_str(&format!(
+        "<th{}>{}</th>",
+        align(columns.skill_idx),
+        parse_inline_markdown(&columns.skill_header)
+    ));
+    html.push_str(&format!(
+        "<th{}>{}</th>",
+        align(columns.value_idx),
+        parse_inline_markdown(&columns.value_header)
+    ));
+    if let (Some(notes_idx), Some(notes_header)) = (columns.notes_idx, &columns.notes_header) {
+        html.push_str(&format!(
+            "<th{}>{}</th>",
+            align(notes_idx),
+            parse_inline_markdown(notes_header)
+        ));
+    }
+    html.push_str("</tr></thead>\n<tbody>\n");
+
+    for row in &table.rows {
+        match row {
+            SkillRow::Category(category) => {
+                html.push_str(&format!(
+                    "<tr class=\"category-row\"><td colspan=\"{}\"><strong>{}</strong></td></tr>\n",
+                    colspan,
+                    parse_inline_markdown(category)
+                ));
+            }
+            SkillRow::Skill { name, value, notes } => {
+                html.push_str(&format!(
+                    "<tr><td{}>{}</td><td class=\"{}\"{}>{}</td>",
+                    align(columns.skill_idx),
+                    parse_inline_markdown(name),
+                    level_class(value),
+                    align(columns.value_idx),
+                    parse_inline_markdown(value)
+                ));
+                if let Some(notes_idx) = columns.notes_idx {
+                    html.push_str(&format!(
+                        "<td{}>{}</td>",
+                        align(notes_idx),
+                        style_notes(notes)
+                    ));
+                }
+                html.push_str("</tr>\n");
             }
-            html.push_str("</tr>\n");
         }
     }
 
     html.push_str("</tbody>\n</table>");
-    Some(html)
+    html
+}
+
+/// CSS class for a skill level; only characters valid in a class name are kept.
+fn level_class(value: &str) -> String {
+    let level: String = value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect();
+    format!("level-{}", level)
+}
+
+/// Inline style attribute for a column alignment from the delimiter row.
+fn alignment_attr(alignment: Option<&Alignment>) -> &'static str {
+    match alignment {
+        Some(Alignment::Left) => " style=\"text-align: left\"",
+        Some(Alignment::Center) => " style=\"text-align: center\"",
+        Some(Alignment::Right) => " style=\"text-align: right\"",
+        _ => "",
+    }
 }
 
 /// Style notes content (e.g., WIP markers).
@@ -250,9 +580,12 @@ fn style_notes(notes: &str) -> String {
             .unwrap_or("")
             .trim_start_matches(" -")
             .trim_start();
-        format!("<span class=\"wip-marker\">WIP</span>{}", rest)
+        format!(
+            "<span class=\"wip-marker\">WIP</span>{}",
+            parse_inline_markdown(rest)
+        )
     } else {
-        notes.to_string()
+        parse_inline_markdown(notes)
     }
 }
 
@@ -341,7 +674,7 @@ mod tests {
 |-------|-------|-------|
 | Rust  | 8     | Good  |
 "#;
-        let output = transform_skill_matrices(input);
+        let output = transform_skill_matrices(input, &SkillMatrixOptions::default());
         assert!(output.contains("skill-matrix"));
         assert!(output.contains("level-8"));
         assert!(output.contains("Rust"));
@@ -356,7 +689,7 @@ mod tests {
 | **Languages** | | |
 | Rust  | 8     | Good  |
 "#;
-        let output = transform_skill_matrices(input);
+        let output = transform_skill_matrices(input, &SkillMatrixOptions::default());
         assert!(output.contains("category-row"));
         assert!(output.contains("Languages"));
     }
@@ -369,7 +702,7 @@ mod tests {
 |------------|--------|
 | Docker     | 7      |
 "#;
-        let output = transform_skill_matrices(input);
+        let output = transform_skill_matrices(input, &SkillMatrixOptions::default());
         assert!(output.contains("skill-matrix"));
         assert!(output.contains("Docker"));
         assert!(output.contains("level-7"));
@@ -381,4 +714,176 @@ mod tests {
         assert!(styled.contains("wip-marker"));
         assert!(styled.contains("learning"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_transform_with_chart() {
+        let input = r#"## Skills
+
+| Skill | Level |
+|-------|-------|
+| Rust  | 8     |
+| Go    | 6     |
+"#;
+        let options = SkillMatrixOptions {
+            chart: ChartKind::Bar,
+            ..Default::default()
+        };
+        let output = transform_skill_matrices(input, &options);
+        assert!(output.contains("with-chart"));
+        assert!(output.contains("<table class=\"skill-matrix\">"));
+        assert!(output.contains("<svg"));
+    }
+
+    #[test]
+    fn test_transform_chart_only() {
+        let input = r#"## Skills
+
+| Skill | Level |
+|-------|-------|
+| Rust  | 8     |
+"#;
+        let options = SkillMatrixOptions {
+            chart: ChartKind::Bar,
+            chart_only: true,
+            ..Default::default()
+        };
+        let output = transform_skill_matrices(input, &options);
+        assert!(!output.contains("<table"));
+        assert!(output.contains("<svg"));
+    }
+
+    #[test]
+    fn test_marker_annotation() {
+        let input = r#"## Soft skills reflection
+
+Nothing to see here.
+
+## Overview
+
+<!-- vellum:skill-matrix -->
+
+| Name | Score |
+|------|-------|
+| Rust | 8     |
+"#;
+        let options = SkillMatrixOptions {
+            detect_headings: false,
+            ..Default::default()
+        };
+        let output = transform_skill_matrices(input, &options);
+        assert!(output.contains("skill-matrix-container"));
+        assert!(output.contains("level-8"));
+        assert!(output.contains("## Overview"));
+    }
+
+    #[test]
+    fn test_fenced_skills_block() {
+        let input = r#"## Overview
+
+```skills
+| Skill | Level |
+|-------|-------|
+| Rust  | 8     |
+```
+
+After the table.
+"#;
+        let output = transform_skill_matrices(input, &SkillMatrixOptions::default());
+        assert!(output.contains("skill-matrix-container"));
+        assert!(!output.contains("```"));
+        assert!(output.contains("After the table."));
+    }
+
+    #[test]
+    fn test_heading_detection_disabled() {
+        let input = r#"## Soft skills reflection
+
+| Skill | Level |
+|-------|-------|
+| Listening | 6 |
+"#;
+        let options = SkillMatrixOptions {
+            detect_headings: false,
+            ..Default::default()
+        };
+        let output = transform_skill_matrices(input, &options);
+        assert!(!output.contains("skill-matrix-container"));
+        assert!(output.contains("| Listening | 6 |"));
+    }
+
+    #[test]
+    fn test_heading_without_table_is_kept_once() {
+        let input = "## Skills
+
+No table yet.
+
+## Next
+
+Text
+";
+        let output = transform_skill_matrices(input, &SkillMatrixOptions::default());
+        assert_eq!(output.matches("## Skills").count(), 1);
+        assert_eq!(output.matches("No table yet.").count(), 1);
+    }
+
+    #[test]
+    fn test_custom_keywords_and_columns() {
+        let options = SkillMatrixOptions {
+            heading_keywords: vec!["Fähigkeiten".to_string()],
+            skill_columns: vec!["Thema".to_string()],
+            value_columns: vec!["Stufe".to_string()],
+            notes_columns: vec![],
+            ..Default::default()
+        };
+        assert!(options.is_heading("## Meine Fähigkeiten"));
+        assert!(!options.is_heading("## Skills"));
+
+        let cols = options.detect_columns("| Thema | Stufe |").unwrap();
+        assert_eq!(cols.skill_header, "Thema");
+        assert_eq!(cols.value_header, "Stufe");
+    }
+
+    #[test]
+    fn test_split_table_row_escaped_pipe() {
+        let cells = split_table_row(r"| Shell | 7 | pipes like a \| b |");
+        assert_eq!(cells, vec!["", "Shell", "7", "pipes like a | b", ""]);
+    }
+
+    #[test]
+    fn test_alignment_row() {
+        let alignments = parse_alignments("|:---|:---:|---:|---|").unwrap();
+        assert_eq!(
+            alignments,
+            vec![
+                Alignment::None,
+                Alignment::Left,
+                Alignment::Center,
+                Alignment::Right,
+                Alignment::None,
+                Alignment::None
+            ]
+        );
+        assert!(parse_alignments("| Rust | 8 |").is_none());
+    }
+
+    #[test]
+    fn test_transform_renders_inline_markdown() {
+        let input = r#"## Skills
+
+| Skill | Level | Notes |
+|:------|:-----:|-------|
+| `C++` & C | 6 | See [docs](https://example.com), a \| b, x < y |
+"#;
+        let output = transform_skill_matrices(input, &SkillMatrixOptions::default());
+        assert!(output.contains("<td style=\"text-align: left\"><code>C++</code> &amp; C</td>"));
+        assert!(output.contains("<td class=\"level-6\" style=\"text-align: center\">6</td>"));
+        assert!(output.contains("<a href=\"https://example.com\">docs</a>, a | b, x &lt; y"));
+    }
+
+    #[test]
+    fn test_transform_requires_delimiter_row() {
+        let input = "## Skills\n\n| Skill | Level |\n| Rust | 8 |\n| Go | 6 |\n";
+        let output = transform_skill_matrices(input, &SkillMatrixOptions::default());
+        assert!(!output.contains("skill-matrix-container"));
+    }
+}
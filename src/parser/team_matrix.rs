@@ -0,0 +1,364 @@
+//! Team skill matrix module.
+//!
+//! Merges the skill matrices of several people into a single table with
+//! people as columns, so skills covered by only one person (or nobody)
+//! stand out as bus-factor risks.
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::markdown::parse_inline_markdown;
+use super::skill_matrix::{extract_skill_matrices, SkillMatrixOptions, SkillRow};
+
+/// Placeholder replaced by the merged team matrix.
+pub const TEAM_MATRIX_MARKER: &str = "<!-- vellum:team-matrix -->";
+
+fn default_threshold() -> f64 {
+    7.0
+}
+
+/// One person contributing a skill matrix.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TeamMember {
+    pub name: String,
+    /// Markdown file containing the person's skill matrix
+    pub skills: String,
+}
+
+/// Team mode configuration.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TeamOptions {
+    pub members: Vec<TeamMember>,
+    /// Skills where nobody reaches this level are highlighted as risks
+    #[serde(default = "default_threshold")]
+    pub threshold: f64,
+}
+
+/// A row of the merged team matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TeamRow {
+    Category(String),
+    Skill {
+        name: String,
+        /// One entry per person, `None` if missing or not numeric
+        levels: Vec<Option<f64>>,
+    },
+}
+
+/// Skill matrices of several people merged into one table.
+#[derive(Debug, Clone, Default)]
+pub struct TeamMatrix {
+    pub people: Vec<String>,
+    pub rows: Vec<TeamRow>,
+    /// Mismatched skill names and missing matrices
+    pub warnings: Vec<String>,
+}
+
+impl TeamMatrix {
+    /// Skills where nobody reaches the threshold.
+    pub fn risks(&self, threshold: f64) -> Vec<&str> {
+        self.rows
+            .iter()
+            .filter_map(|row| match row {
+                TeamRow::Skill { name, levels } if is_at_risk(levels, threshold) => {
+                    Some(name.as_str())
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// A merged skill while building the matrix.
+struct MergedSkill {
+    name: String,
+    levels: Vec<Option<f64>>,
+    present: Vec<bool>,
+}
+
+/// Merge the skill matrices of several people.
+///
+/// `sources` holds `(person, markdown)` pairs. Skills are matched by name,
+/// ignoring case and surrounding whitespace, and grouped under the category
+/// they first appeared in.
+pub fn merge_team_matrix(sources: &[(String, String)], options: &SkillMatrixOptions) -> TeamMatrix {
+    let people: Vec<String> = sources.iter().map(|(name, _)| name.clone()).collect();
+    let mut warnings = Vec::new();
+
+    // Categories in order of appearance, each with its skills
+    let mut categories: Vec<(Option<String>, Vec<usize>)> = Vec::new();
+    let mut category_index: HashMap<Option<String>, usize> = HashMap::new();
+    let mut skills: Vec<MergedSkill> = Vec::new();
+    let mut skill_index: HashMap<String, usize> = HashMap::new();
+    let mut has_matrix = vec![false; sources.len()];
+
+    for (person_idx, (person, markdown)) in sources.iter().enumerate() {
+        let rows: Vec<SkillRow> = extract_skill_matrices(markdown, options)
+            .into_iter()
+            .flatten()
+            .collect();
+
+        if rows.is_empty() {
+            warnings.push(format!("No skill matrix found for '{}'", person));
+            continue;
+        }
+        has_matrix[person_idx] = true;
+
+        let mut current_category: Option<String> = None;
+        for row in rows {
+            match row {
+                SkillRow::Category(name) => current_category = Some(name),
+                SkillRow::Skill { name, value, .. } => {
+                    let key = normalize(&name);
+                    let idx = match skill_index.get(&key) {
+                        Some(&idx) => idx,
+                        None => {
+                            let idx = skills.len();
+                            skills.push(MergedSkill {
+                                name: name.clone(),
+                                levels: vec![None; sources.len()],
+                                present: vec![false; sources.len()],
+                            });
+                            skill_index.insert(key, idx);
+
+                            let category_key = current_category.as_deref().map(normalize);
+                            let cat_idx =
+                                *category_index.entry(category_key).or_insert_with(|| {
+                                    categories.push((current_category.clone(), Vec::new()));
+                                    categories.len() - 1
+                                });
+                            categories[cat_idx].1.push(idx);
+                            idx
+                        }
+                    };
+                    skills[idx].present[person_idx] = true;
+                    skills[idx].levels[person_idx] = value.trim().parse::<f64>().ok();
+                }
+            }
+        }
+    }
+
+    for skill in &skills {
+        // People without any matrix are already reported above
+        let missing: Vec<&str> = people
+            .iter()
+            .enumerate()
+            .filter(|&(idx, _)| has_matrix[idx] && !skill.present[idx])
+            .map(|(_, person)| person.as_str())
+            .collect();
+        if !missing.is_empty() {
+            warnings.push(format!(
+                "Skill '{}' is missing for: {}",
+                skill.name,
+                missing.join(", ")
+            ));
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (category, members) in categories {
+        if let Some(name) = category {
+            rows.push(TeamRow::Category(name));
+        }
+        for idx in members {
+            rows.push(TeamRow::Skill {
+                name: skills[idx].name.clone(),
+                levels: skills[idx].levels.clone(),
+            });
+        }
+    }
+
+    TeamMatrix {
+        people,
+        rows,
+        warnings,
+    }
+}
+
+/// Render the merged team matrix as an HTML table.
+pub fn render_team_matrix(matrix: &TeamMatrix, threshold: f64) -> String {
+    let colspan = matrix.people.len() + 2;
+
+    let mut html = String::from("<div class=\"skill-matrix-container\">\n<table class=\"skill-matrix team-matrix\">\n<thead><tr><th>Skill</th>");
+    for person in &matrix.people {
+        html.push_str(&format!("<th>{}</th>", parse_inline_markdown(person)));
+    }
+    html.push_str("<th>Experts</th></tr></thead>\n<tbody>\n");
+
+    for row in &matrix.rows {
+        match row {
+            TeamRow::Category(category) => {
+                html.push_str(&format!(
+                    "<tr class=\"category-row\"><td colspan=\"{}\"><strong>{}</strong></td></tr>\n",
+                    colspan,
+                    parse_inline_markdown(category)
+                ));
+            }
+            TeamRow::Skill { name, levels } => {
+                let experts = levels.iter().flatten().filter(|&&l| l >= threshold).count();
+                if is_at_risk(levels, threshold) {
+                    html.push_str("<tr class=\"team-risk\">");
+                } else {
+                    html.push_str("<tr>");
+                }
+                html.push_str(&format!("<td>{}</td>", parse_inline_markdown(name)));
+                for level in levels {
+                    match level {
+                        Some(level) => html.push_str(&format!(
+                            "<td class=\"level-{}\">{}</td>",
+                            level.round() as u32,
+                            level
+                        )),
+                        None => html.push_str("<td class=\"team-missing\">–</td>"),
+                    }
+                }
+                html.push_str(&format!(
+                    "<td class=\"team-experts\">{}</td></tr>\n",
+                    experts
+                ));
+            }
+        }
+    }
+
+    html.push_str("</tbody>\n</table>\n</div>");
+    html
+}
+
+/// Replace the team matrix placeholder with the rendered HTML.
+///
+/// Returns `None` if the content has no placeholder.
+pub fn insert_team_matrix(content: &str, html: &str) -> Option<String> {
+    if !content.contains(TEAM_MATRIX_MARKER) {
+        return None;
+    }
+
+    let mut result = content
+        .lines()
+        .map(|line| {
+            if line.trim() == TEAM_MATRIX_MARKER {
+                format!("{}\n", html)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+
+    Some(result)
+}
+
+/// Somebody is rated for this skill, but nobody reaches the threshold.
+fn is_at_risk(levels: &[Option<f64>], threshold: f64) -> bool {
+    levels.iter().any(Option::is_some) && levels.iter().flatten().all(|&l| l < threshold)
+}
+
+/// Normalize a skill or category name for matching across files.
+fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources() -> Vec<(String, String)> {
+        vec![
+            (
+                "Alice".to_string(),
+                "## Skills\n\n| Skill | Level |\n|---|---|\n| **Languages** | |\n| Rust | 9 |\n| Go | 5 |\n".to_string(),
+            ),
+            (
+                "Bob".to_string(),
+                "## Skills\n\n| Skill | Level |\n|---|---|\n| **Languages** | |\n| rust | 4 |\n| Python | 8 |\n".to_string(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_merge_matches_names_case_insensitive() {
+        let matrix = merge_team_matrix(&sources(), &SkillMatrixOptions::default());
+
+        assert_eq!(matrix.people, vec!["Alice", "Bob"]);
+        assert_eq!(matrix.rows[0], TeamRow::Category("Languages".to_string()));
+        assert_eq!(
+            matrix.rows[1],
+            TeamRow::Skill {
+                name: "Rust".to_string(),
+                levels: vec![Some(9.0), Some(4.0)],
+            }
+        );
+        assert_eq!(matrix.rows.len(), 4);
+    }
+
+    #[test]
+    fn test_merge_reports_mismatched_skills() {
+        let matrix = merge_team_matrix(&sources(), &SkillMatrixOptions::default());
+
+        assert!(matrix
+            .warnings
+            .contains(&"Skill 'Go' is missing for: Bob".to_string()));
+        assert!(matrix
+            .warnings
+            .contains(&"Skill 'Python' is missing for: Alice".to_string()));
+    }
+
+    #[test]
+    fn test_merge_reports_missing_matrix() {
+        let mut sources = sources();
+        sources.push(("Carol".to_string(), "No table here".to_string()));
+        let matrix = merge_team_matrix(&sources, &SkillMatrixOptions::default());
+
+        assert!(matrix
+            .warnings
+            .contains(&"No skill matrix found for 'Carol'".to_string()));
+        assert!(!matrix
+            .warnings
+            .iter()
+            .any(|w| w.contains("missing for: Carol")));
+    }
+
+    #[test]
+    fn test_risks_below_threshold() {
+        let matrix = merge_team_matrix(&sources(), &SkillMatrixOptions::default());
+
+        assert_eq!(matrix.risks(7.0), vec!["Go"]);
+        let html = render_team_matrix(&matrix, 7.0);
+        assert_eq!(html.matches("team-risk").count(), 1);
+        assert!(html.contains("<th>Alice</th><th>Bob</th><th>Experts</th>"));
+        assert!(html.contains("team-missing"));
+    }
+
+    #[test]
+    fn test_unrated_skill_not_at_risk() {
+        let matrix = TeamMatrix {
+            people: vec!["Alice".to_string()],
+            rows: vec![TeamRow::Skill {
+                name: "Kotlin".to_string(),
+                levels: vec![None],
+            }],
+            warnings: Vec::new(),
+        };
+
+        assert!(matrix.risks(7.0).is_empty());
+        assert!(!render_team_matrix(&matrix, 7.0).contains("team-risk"));
+    }
+
+    #[test]
+    fn test_insert_team_matrix() {
+        let content = "## Team\n\n<!-- vellum:team-matrix -->\n\nAfter";
+        let result = insert_team_matrix(content, "<table></table>").unwrap();
+        assert!(result.contains("<table></table>"));
+        assert!(!result.contains(TEAM_MATRIX_MARKER));
+        assert!(result.ends_with("After"));
+        let result =
+            insert_team_matrix("<!-- vellum:team-matrix -->\n", "<table></table>").unwrap();
+        assert_eq!(result, "<table></table>\n\n");
+        assert!(insert_team_matrix("No marker", "<table></table>").is_none());
+    }
+}
@@ -0,0 +1,169 @@
+//! Table of contents markers.
+//!
+//! A `[[toc]]` line in a panel becomes a list of the panel's H3-H6 headings
+//! when the panel is rendered to HTML, and `[[toc:all]]` a list of every
+//! panel with its headings. Formats without heading links drop the markers.
+
+use super::document::DocumentStructure;
+use serde::Deserialize;
+
+/// Placeholder for the table of contents of the panel it is in.
+pub const TOC_MARKER: &str = "[[toc]]";
+
+/// Placeholder for the table of contents of the whole document.
+pub const DOCUMENT_TOC_MARKER: &str = "[[toc:all]]";
+
+/// Generated tables of contents, from the `[toc]` config section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TocOptions {
+    /// Insert a table of contents at the top of panels without a marker
+    pub panels: bool,
+    /// Headings a panel needs to get one inserted
+    pub min_headings: usize,
+    /// Add a panel listing all panels and their headings, first in the navigation
+    /// after the preamble's panel
+    pub global: bool,
+    /// Title of that panel
+    pub title: String,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        Self {
+            panels: false,
+            min_headings: 3,
+            global: false,
+            title: "Contents".to_string(),
+        }
+    }
+}
+
+/// Lines of `markdown`, each with whether it belongs to a fenced code block.
+pub(super) fn fenced_lines(markdown: &str) -> impl Iterator<Item = (&str, bool)> {
+    let mut fence: Option<&str> = None;
+    markdown.lines().map(move |line| {
+        let trimmed = line.trim_start();
+        match fence {
+            Some(open) => {
+                if trimmed.starts_with(open) {
+                    fence = None;
+                }
+                (line, true)
+            }
+            None => {
+                fence = ["```", "~~~"].into_iter().find(|f| trimmed.starts_with(f));
+                (line, fence.is_some())
+            }
+        }
+    })
+}
+
+/// Lines of `markdown` outside fenced code blocks.
+fn lines_outside_fences(markdown: &str) -> impl Iterator<Item = &str> {
+    fenced_lines(markdown)
+        .filter(|(_, code)| !code)
+        .map(|(line, _)| line)
+}
+
+fn is_marker(line: &str) -> bool {
+    matches!(line.trim(), TOC_MARKER | DOCUMENT_TOC_MARKER)
+}
+
+/// Whether a panel already places a table of contents itself.
+fn has_marker(markdown: &str) -> bool {
+    lines_outside_fences(markdown).any(is_marker)
+}
+
+/// Number of H3-H6 headings in panel Markdown.
+fn count_headings(markdown: &str) -> usize {
+    lines_outside_fences(markdown)
+        .filter(|line| {
+            let hashes = line.len() - line.trim_start_matches('#').len();
+            (3..=6).contains(&hashes) && line[hashes..].starts_with(' ')
+        })
+        .count()
+}
+
+/// Put a [`TOC_MARKER`] at the top of each panel with enough headings,
+/// unless it has a marker already.
+pub fn insert_toc_markers(doc: &mut DocumentStructure, min_headings: usize) {
+    for panel in &mut doc.panels {
+        let content = &panel.markdown_content;
+        if !has_marker(content) && count_headings(content) >= min_headings.max(1) {
+            panel.markdown_content =
+                format!("{}\n\n{}", TOC_MARKER, content.trim_start_matches('\n'));
+        }
+    }
+}
+
+/// Add a panel with the [`DOCUMENT_TOC_MARKER`] before all others but the
+/// preamble's panel, which stays the one shown first.
+pub fn add_contents_panel(doc: &mut DocumentStructure, title: &str) {
+    let index = usize::from(doc.preamble_panel.is_some());
+    doc.insert_panel(index, title, DOCUMENT_TOC_MARKER.to_string());
+}
+
+/// Remove the marker lines, for output without heading links.
+pub fn strip_toc_markers(markdown: &str) -> String {
+    if !has_marker(markdown) {
+        return markdown.to_string();
+    }
+    fenced_lines(markdown)
+        .filter(|(line, code)| *code || !is_marker(line))
+        .map(|(line, _)| line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_document_structure, PreamblePolicy};
+
+    #[test]
+    fn test_insert_toc_markers() {
+        let markdown = "## Goals\n\n### Q1\nA\n\n#### Detail\nB\n\n```\n### not a heading\n```\n\n### Q2\nC\n\n## About\n\n### One\n\n### Two\n";
+        let mut doc = parse_document_structure(markdown, None);
+        insert_toc_markers(&mut doc, 3);
+
+        assert!(doc.panels[0]
+            .markdown_content
+            .starts_with("[[toc]]\n\n### Q1"));
+        assert!(!doc.panels[1].markdown_content.contains(TOC_MARKER));
+
+        // Panels placing the marker themselves keep it where it is
+        let before = doc.panels[0].markdown_content.clone();
+        insert_toc_markers(&mut doc, 1);
+        assert_eq!(doc.panels[0].markdown_content, before);
+        assert!(doc.panels[1].markdown_content.starts_with("[[toc]]"));
+    }
+
+    #[test]
+    fn test_add_contents_panel_unique_id() {
+        let mut doc = parse_document_structure("## Contents\n\nMine\n", None);
+        add_contents_panel(&mut doc, "Contents");
+
+        assert_eq!(doc.navigation[0].id, "contents-1");
+        assert_eq!(doc.panels[0].markdown_content, DOCUMENT_TOC_MARKER);
+        assert_eq!(doc.panels[1].id, "contents");
+    }
+
+    #[test]
+    fn test_add_contents_panel_after_preamble() {
+        let mut doc = parse_document_structure("# Docs\n\nWelcome\n\n## About\n\nMe\n", None);
+        doc.place_preamble(PreamblePolicy::Panel, "Introduction");
+        add_contents_panel(&mut doc, "Contents");
+
+        let ids: Vec<&str> = doc.panels.iter().map(|panel| panel.id.as_str()).collect();
+        assert_eq!(ids, ["introduction", "contents", "about"]);
+        let nav: Vec<&str> = doc.navigation.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(nav, ids);
+    }
+
+    #[test]
+    fn test_strip_toc_markers_outside_code() {
+        let markdown = "[[toc]]\n\nText\n\n```\n[[toc]]\n```\n  [[toc:all]]\n";
+        assert_eq!(strip_toc_markers(markdown), "\nText\n\n```\n[[toc]]\n```");
+    }
+}
@@ -0,0 +1,62 @@
+//! Self-extracting content.
+//!
+//! The rendered panels are gzip-compressed and base64-encoded into an inline
+//! script. A small inflater expands them with `document.write` while the page
+//! is parsed, so the result is the same document the theme script expects.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use flate2::{write::GzEncoder, Compression};
+use std::io::Write;
+
+/// The inflater, a function expression taking the base64 payload.
+const INFLATE_JS: &str = include_str!("inflate.js");
+
+/// Shown by browsers with JavaScript disabled.
+const NOSCRIPT: &str =
+    "<noscript>This document is compressed and needs JavaScript to be displayed.</noscript>";
+
+/// Gzip-compress `html`.
+fn gzip(html: &str) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(html.as_bytes())?;
+    encoder.finish()
+}
+
+/// Markup that expands to `html` when the page loads.
+pub fn self_extracting(html: &str) -> std::io::Result<String> {
+    let payload = STANDARD.encode(gzip(html)?);
+    Ok(format!(
+        "<script>\n{}(\"{}\");\n</script>\n{}",
+        INFLATE_JS.trim_end(),
+        payload,
+        NOSCRIPT
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_gzip_round_trip() {
+        let html = "<div class=\"panel\">Hello, wörld</div>\n".repeat(50);
+        let compressed = gzip(&html).unwrap();
+        assert!(compressed.len() < html.len() / 4);
+
+        let mut decoded = String::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, html);
+    }
+
+    #[test]
+    fn test_self_extracting_markup() {
+        let markup = self_extracting("<p>Hi</p>").unwrap();
+        assert!(markup.starts_with("<script>\n/**"));
+        assert!(markup.contains("})(\"H4sI"));
+        assert!(markup.ends_with("</noscript>"));
+    }
+}
@@ -0,0 +1,382 @@
+//! EPUB 3 output: a title page, a navigation document in panel order and
+//! one XHTML chapter per panel.
+//!
+//! Images embedded as data URIs are stored as separate files in the book,
+//! since reading systems do not reliably show data URIs.
+
+mod xhtml;
+mod zip;
+
+use super::header::{HeaderLogo, PageHeader};
+use super::html::table_of_contents;
+use super::{escape_html, DocumentMeta, RenderError, RenderOutput, Renderer};
+use crate::parser::{
+    parse_markdown, slugify, strip_toc_markers, DocumentStructure, NavNode, SectionStyle,
+};
+use xhtml::{to_xhtml, Resources};
+use zip::ZipWriter;
+
+const STYLES: &str = include_str!("epub.css");
+
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+    </rootfiles>
+</container>
+"#;
+
+/// A content document of the book, in reading order.
+struct Chapter {
+    file: String,
+    body: String,
+    has_svg: bool,
+}
+
+/// Renders a document structure to an EPUB book.
+#[derive(Debug, Default)]
+pub struct EpubRenderer;
+
+impl EpubRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn render_epub(
+        &self,
+        doc: &DocumentStructure,
+        title: &str,
+        header: &PageHeader,
+    ) -> Result<Vec<u8>, RenderError> {
+        let mut resources = Resources::default();
+        let body = to_xhtml(&title_page(title, header), &mut resources, "");
+        let mut chapters = vec![Chapter {
+            file: "title.xhtml".to_string(),
+            has_svg: body.contains("<svg"),
+            body,
+        }];
+
+        let mut current_group = None;
+        for (i, panel) in doc.panels.iter().enumerate() {
+            let group = doc.parent_of(&panel.id);
+            if let Some(group) = group.filter(|group| current_group != Some(&group.id)) {
+                chapters.push(Chapter {
+                    file: group_file(doc, group),
+                    body: to_xhtml(
+                        &format!("<h1 class=\"group\">{}</h1>", group.title),
+                        &mut resources,
+                        "",
+                    ),
+                    has_svg: false,
+                });
+            }
+            current_group = group.map(|group| &group.id);
+
+            let html = format!(
+                "<section id=\"panel-{}\">\n<h2>{}</h2>\n{}\n</section>",
+                panel.id,
+                panel.title,
+                parse_markdown(&strip_toc_markers(&panel.markdown_content))
+            );
+            let body = to_xhtml(&html, &mut resources, "");
+            chapters.push(Chapter {
+                file: chapter_file(i),
+                has_svg: body.contains("<svg"),
+                body,
+            });
+        }
+
+        let nav = to_xhtml(
+            &format!(
+                "<nav epub:type=\"toc\" id=\"toc\">\n<h1>Contents</h1>\n{}</nav>",
+                table_of_contents(
+                    doc,
+                    |id| {
+                        let index = doc
+                            .panels
+                            .iter()
+                            .position(|p| p.id == id)
+                            .unwrap_or_default();
+                        chapter_file(index)
+                    },
+                    |group| Some(group_file(doc, group)),
+                    |_, _| String::new()
+                )
+            ),
+            &mut resources,
+            "",
+        );
+
+        let cover = match header.logo {
+            HeaderLogo::Image(ref uri) => resources.add_data_uri(uri),
+            _ => None,
+        };
+
+        let mut zip = ZipWriter::new();
+        zip.add_stored("mimetype", b"application/epub+zip");
+        zip.add_deflated("META-INF/container.xml", CONTAINER_XML.as_bytes())?;
+        zip.add_deflated(
+            "OEBPS/content.opf",
+            package_document(title, header, &chapters, &resources, cover.as_deref()).as_bytes(),
+        )?;
+        zip.add_deflated(
+            "OEBPS/nav.xhtml",
+            xhtml_document("Contents", &nav).as_bytes(),
+        )?;
+        zip.add_deflated("OEBPS/style.css", STYLES.as_bytes())?;
+        for chapter in &chapters {
+            zip.add_deflated(
+                &format!("OEBPS/{}", chapter.file),
+                xhtml_document(title, &chapter.body).as_bytes(),
+            )?;
+        }
+        for resource in &resources.files {
+            zip.add_deflated(&format!("OEBPS/{}", resource.path), &resource.data)?;
+        }
+
+        Ok(zip.finish())
+    }
+}
+
+impl Renderer for EpubRenderer {
+    fn render(
+        &self,
+        doc: &DocumentStructure,
+        meta: &DocumentMeta,
+        _warnings: &mut Vec<String>,
+    ) -> Result<RenderOutput, RenderError> {
+        let epub = self.render_epub(doc, meta.title, meta.header)?;
+        Ok(RenderOutput::File(epub))
+    }
+}
+
+fn chapter_file(index: usize) -> String {
+    format!("chapter-{}.xhtml", index + 1)
+}
+
+/// The title page of a dropdown or group, numbered in document order.
+fn group_file(doc: &DocumentStructure, group: &NavNode) -> String {
+    let index = doc
+        .navigation
+        .iter()
+        .filter(|node| node.style != SectionStyle::Button)
+        .position(|node| node.id == group.id)
+        .unwrap_or_default();
+    format!("group-{}.xhtml", index + 1)
+}
+
+/// The title with logo, subtitle, author, links and intro.
+fn title_page(title: &str, header: &PageHeader) -> String {
+    let mut html = String::from("<section class=\"title-page\">\n");
+    if header.logo != HeaderLogo::None {
+        html.push_str(&format!("<p>{}</p>\n", header.logo_html()));
+    }
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    if let Some(ref subtitle) = header.subtitle {
+        html.push_str(&format!(
+            "<p class=\"subtitle\">{}</p>\n",
+            escape_html(subtitle)
+        ));
+    }
+    if let Some(ref author) = header.author {
+        html.push_str(&format!(
+            "<p class=\"author\">{}</p>\n",
+            escape_html(author)
+        ));
+    }
+    if !header.links.is_empty() {
+        html.push_str("<ul class=\"links\">\n");
+        for link in &header.links {
+            html.push_str(&format!(
+                "<li><a href=\"{}\">{}</a></li>\n",
+                escape_html(&link.url),
+                escape_html(&link.label)
+            ));
+        }
+        html.push_str("</ul>\n");
+    }
+    if let Some(ref intro) = header.intro {
+        html.push_str(&format!(
+            "<div class=\"intro\">\n{}</div>\n",
+            parse_markdown(intro)
+        ));
+    }
+    html.push_str("</section>");
+    html
+}
+
+fn xhtml_document(title: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops" lang="en" xml:lang="en">
+<head>
+<title>{}</title>
+<link rel="stylesheet" type="text/css" href="style.css"/>
+</head>
+<body>
+{}
+</body>
+</html>
+"#,
+        escape_html(title),
+        body
+    )
+}
+
+/// The OPF package document: metadata, manifest and reading order.
+fn package_document(
+    title: &str,
+    header: &PageHeader,
+    chapters: &[Chapter],
+    resources: &Resources,
+    cover: Option<&str>,
+) -> String {
+    let mut metadata = format!(
+        "        <dc:identifier id=\"book-id\">urn:vellum:{}</dc:identifier>\n        <dc:title>{}</dc:title>\n        <dc:language>en</dc:language>\n",
+        escape_html(&slugify(title)),
+        escape_html(title)
+    );
+    if let Some(ref author) = header.author {
+        metadata.push_str(&format!(
+            "        <dc:creator>{}</dc:creator>\n",
+            escape_html(author)
+        ));
+    }
+    metadata.push_str(&format!(
+        "        <meta property=\"dcterms:modified\">{}</meta>\n",
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ")
+    ));
+
+    let mut manifest = String::from(
+        "        <item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>\n        <item id=\"style\" href=\"style.css\" media-type=\"text/css\"/>\n",
+    );
+    let mut spine = String::new();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let properties = if chapter.has_svg {
+            " properties=\"svg\""
+        } else {
+            ""
+        };
+        manifest.push_str(&format!(
+            "        <item id=\"doc-{}\" href=\"{}\" media-type=\"application/xhtml+xml\"{}/>\n",
+            i + 1,
+            chapter.file,
+            properties
+        ));
+        spine.push_str(&format!("        <itemref idref=\"doc-{}\"/>\n", i + 1));
+        if i == 0 {
+            spine.push_str("        <itemref idref=\"nav\"/>\n");
+        }
+    }
+    for (i, resource) in resources.files.iter().enumerate() {
+        let properties = if cover == Some(resource.path.as_str()) {
+            " properties=\"cover-image\""
+        } else {
+            ""
+        };
+        manifest.push_str(&format!(
+            "        <item id=\"image-{}\" href=\"{}\" media-type=\"{}\"{}/>\n",
+            i + 1,
+            resource.path,
+            resource.media_type,
+            properties
+        ));
+    }
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id" xml:lang="en">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+{}    </metadata>
+    <manifest>
+{}    </manifest>
+    <spine>
+{}    </spine>
+</package>
+"#,
+        metadata, manifest, spine
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_document_structure;
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    /// Entries of a ZIP archive as `(name, contents)`, read from the local headers.
+    fn unzip(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut entries = Vec::new();
+        let mut at = 0;
+        while bytes[at..].starts_with(&[0x50, 0x4b, 0x03, 0x04]) {
+            let u16_at = |i: usize| u16::from_le_bytes([bytes[at + i], bytes[at + i + 1]]) as usize;
+            let size = u32::from_le_bytes(bytes[at + 18..at + 22].try_into().unwrap()) as usize;
+            let name_len = u16_at(26);
+            let name = String::from_utf8(bytes[at + 30..at + 30 + name_len].to_vec()).unwrap();
+            let data = &bytes[at + 30 + name_len..at + 30 + name_len + size];
+            let contents = if u16_at(8) == 8 {
+                let mut inflated = Vec::new();
+                DeflateDecoder::new(data)
+                    .read_to_end(&mut inflated)
+                    .unwrap();
+                inflated
+            } else {
+                data.to_vec()
+            };
+            entries.push((name, contents));
+            at += 30 + name_len + size;
+        }
+        entries
+    }
+
+    #[test]
+    fn test_render_epub() {
+        let markdown = "## About\n\nQ&A <br> ![dot](data:image/png;base64,iVBORw0=)\n\n## Projects\n\n### Vellum\n\nGenerator\n";
+        let doc = parse_document_structure(markdown, Some("Projects"));
+        let mut header = PageHeader::with_logo("");
+        header.author = Some("Ada".to_string());
+
+        let epub = EpubRenderer::new()
+            .render_epub(&doc, "Portfolio", &header)
+            .unwrap();
+        let entries = unzip(&epub);
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "mimetype",
+                "META-INF/container.xml",
+                "OEBPS/content.opf",
+                "OEBPS/nav.xhtml",
+                "OEBPS/style.css",
+                "OEBPS/title.xhtml",
+                "OEBPS/chapter-1.xhtml",
+                "OEBPS/group-1.xhtml",
+                "OEBPS/chapter-2.xhtml",
+                "OEBPS/images/image-1.png",
+            ]
+        );
+        let text = |name: &str| {
+            let (_, contents) = entries.iter().find(|(n, _)| n == name).unwrap();
+            String::from_utf8(contents.clone()).unwrap()
+        };
+
+        let opf = text("OEBPS/content.opf");
+        assert!(opf.contains("<dc:creator>Ada</dc:creator>"));
+        assert!(opf.contains("<item id=\"doc-3\" href=\"group-1.xhtml\""));
+        assert!(opf.contains(
+            "<item id=\"image-1\" href=\"images/image-1.png\" media-type=\"image/png\"/>"
+        ));
+
+        let nav = text("OEBPS/nav.xhtml");
+        assert!(nav.contains("<nav epub:type=\"toc\" id=\"toc\">"));
+        assert!(nav.contains("<li><a href=\"group-1.xhtml\">Projects</a>"));
+        assert!(nav.contains("<li><a href=\"chapter-2.xhtml\">Vellum</a></li>"));
+
+        let about = text("OEBPS/chapter-1.xhtml");
+        assert!(about.contains("Q&amp;A <br/>"));
+        assert!(about.contains("<img src=\"images/image-1.png\" alt=\"dot\"/>"));
+    }
+}
@@ -0,0 +1,166 @@
+//! Conversion of rendered HTML to well-formed XHTML for EPUB content documents.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::HashMap;
+
+use crate::renderer::escape_html;
+use crate::renderer::markup::{tokenize, Token};
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// An image moved out of a data URI into its own file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resource {
+    /// Path relative to the package directory
+    pub path: String,
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+/// Images collected from all content documents, each stored once.
+#[derive(Debug, Default)]
+pub struct Resources {
+    pub files: Vec<Resource>,
+    by_uri: HashMap<String, usize>,
+}
+
+impl Resources {
+    /// Store the image of a base64 data URI; returns its path, or `None`
+    /// if the URI is not such an image.
+    pub fn add_data_uri(&mut self, uri: &str) -> Option<String> {
+        if let Some(&index) = self.by_uri.get(uri) {
+            return Some(self.files[index].path.clone());
+        }
+        let (media_type, payload) = uri.strip_prefix("data:")?.split_once(";base64,")?;
+        let extension = match media_type {
+            "image/png" => "png",
+            "image/jpeg" => "jpg",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            "image/svg+xml" => "svg",
+            _ => return None,
+        };
+        let data = STANDARD.decode(payload).ok()?;
+
+        let path = format!("images/image-{}.{}", self.files.len() + 1, extension);
+        self.by_uri.insert(uri.to_string(), self.files.len());
+        self.files.push(Resource {
+            path: path.clone(),
+            media_type: media_type.to_string(),
+            data,
+        });
+        Some(path)
+    }
+}
+
+/// Re-serialize HTML as XHTML.
+///
+/// Void elements are self-closed, elements left open are closed, stray
+/// closing tags are dropped and text is escaped for XML. Images embedded as
+/// data URIs are moved to `resources` and linked relative to `base`.
+pub fn to_xhtml(html: &str, resources: &mut Resources, base: &str) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut open: Vec<String> = Vec::new();
+
+    for token in tokenize(html) {
+        match token {
+            Token::Open {
+                ref name,
+                ref attrs,
+            } => {
+                result.push('<');
+                result.push_str(name);
+                for (key, value) in attrs {
+                    if !is_xml_name(key) {
+                        continue;
+                    }
+                    let value = if name == "img" && key.eq_ignore_ascii_case("src") {
+                        resources
+                            .add_data_uri(value)
+                            .map_or_else(|| value.clone(), |path| format!("{}{}", base, path))
+                    } else {
+                        value.clone()
+                    };
+                    result.push_str(&format!(" {}=\"{}\"", key, escape_html(&value)));
+                }
+                if VOID_ELEMENTS.contains(&name.as_str()) {
+                    result.push_str("/>");
+                } else {
+                    result.push('>');
+                    open.push(name.clone());
+                }
+            }
+            Token::Close(name) => {
+                if let Some(position) = open.iter().rposition(|n| *n == name) {
+                    for name in open.drain(position..).rev() {
+                        result.push_str(&format!("</{}>", name));
+                    }
+                }
+            }
+            Token::Text(text) => result.push_str(&escape_html(&text)),
+        }
+    }
+    for name in open.into_iter().rev() {
+        result.push_str(&format!("</{}>", name));
+    }
+
+    result
+}
+
+/// Whether an attribute name can be written as is. Malformed names and
+/// prefixes the content documents do not declare (only `epub` and `xml`
+/// are) are dropped.
+fn is_xml_name(name: &str) -> bool {
+    let local = match name.split_once(':') {
+        Some(("epub" | "xml", local)) => local,
+        Some(_) => return false,
+        None => name,
+    };
+    local.starts_with(|c: char| c.is_ascii_alphabetic())
+        && local
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_xhtml_closes_and_escapes() {
+        let mut resources = Resources::default();
+        let html =
+            "<p>Q&A &nbsp;<br>x<ul><li>one<li>two</ul></p></div><input type=checkbox disabled>";
+        assert_eq!(
+            to_xhtml(html, &mut resources, ""),
+            "<p>Q&amp;A \u{a0}<br/>x<ul><li>one<li>two</li></li></ul></p><input type=\"checkbox\" disabled=\"\"/>"
+        );
+    }
+
+    #[test]
+    fn test_to_xhtml_keeps_svg_attribute_case() {
+        let mut resources = Resources::default();
+        let html = "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><rect width=\"5\"/></svg>";
+        assert_eq!(
+            to_xhtml(html, &mut resources, ""),
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\"><rect width=\"5\"></rect></svg>"
+        );
+    }
+
+    #[test]
+    fn test_data_uri_images_become_resources() {
+        let mut resources = Resources::default();
+        let html = "<img src=\"data:image/png;base64,iVBORw0=\" alt=\"a\"><img src=\"data:image/png;base64,iVBORw0=\">";
+        let xhtml = to_xhtml(html, &mut resources, "../");
+        assert_eq!(
+            xhtml,
+            "<img src=\"../images/image-1.png\" alt=\"a\"/><img src=\"../images/image-1.png\"/>"
+        );
+        assert_eq!(resources.files.len(), 1);
+        assert_eq!(resources.files[0].media_type, "image/png");
+        assert_eq!(resources.files[0].data, vec![0x89, b'P', b'N', b'G', 0x0d]);
+    }
+}
@@ -0,0 +1,181 @@
+//! A minimal ZIP writer for the EPUB container.
+//!
+//! Writes stored or deflated entries without timestamps, extra fields or
+//! ZIP64 support, which is all an EPUB needs.
+
+use flate2::{write::DeflateEncoder, Compression, Crc};
+use std::io::Write;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+/// 1980-01-01, the earliest date ZIP can store
+const DOS_DATE: u16 = 0x0021;
+/// File names are UTF-8
+const FLAG_UTF8: u16 = 0x0800;
+
+struct Entry {
+    name: String,
+    method: u16,
+    crc: u32,
+    compressed_size: u32,
+    size: u32,
+    offset: u32,
+}
+
+/// Builds a ZIP archive in memory.
+#[derive(Default)]
+pub struct ZipWriter {
+    out: Vec<u8>,
+    entries: Vec<Entry>,
+}
+
+impl ZipWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an uncompressed entry, as the EPUB `mimetype` must be.
+    pub fn add_stored(&mut self, name: &str, data: &[u8]) {
+        self.add_entry(name, 0, data, data.to_vec());
+    }
+
+    /// Add a deflate-compressed entry.
+    pub fn add_deflated(&mut self, name: &str, data: &[u8]) -> std::io::Result<()> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data)?;
+        let compressed = encoder.finish()?;
+        self.add_entry(name, 8, data, compressed);
+        Ok(())
+    }
+
+    fn add_entry(&mut self, name: &str, method: u16, data: &[u8], stored: Vec<u8>) {
+        let mut crc = Crc::new();
+        crc.update(data);
+        let entry = Entry {
+            name: name.to_string(),
+            method,
+            crc: crc.sum(),
+            compressed_size: stored.len() as u32,
+            size: data.len() as u32,
+            offset: self.out.len() as u32,
+        };
+
+        put_u32(&mut self.out, LOCAL_HEADER);
+        put_u16(&mut self.out, 20);
+        put_u16(&mut self.out, FLAG_UTF8);
+        put_u16(&mut self.out, entry.method);
+        put_u16(&mut self.out, 0);
+        put_u16(&mut self.out, DOS_DATE);
+        put_u32(&mut self.out, entry.crc);
+        put_u32(&mut self.out, entry.compressed_size);
+        put_u32(&mut self.out, entry.size);
+        put_u16(&mut self.out, entry.name.len() as u16);
+        put_u16(&mut self.out, 0);
+        self.out.extend(entry.name.as_bytes());
+        self.out.extend(stored);
+
+        self.entries.push(entry);
+    }
+
+    /// Write the central directory and return the archive.
+    pub fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.out.len() as u32;
+        for entry in &self.entries {
+            put_u32(&mut self.out, CENTRAL_HEADER);
+            put_u16(&mut self.out, 20);
+            put_u16(&mut self.out, 20);
+            put_u16(&mut self.out, FLAG_UTF8);
+            put_u16(&mut self.out, entry.method);
+            put_u16(&mut self.out, 0);
+            put_u16(&mut self.out, DOS_DATE);
+            put_u32(&mut self.out, entry.crc);
+            put_u32(&mut self.out, entry.compressed_size);
+            put_u32(&mut self.out, entry.size);
+            put_u16(&mut self.out, entry.name.len() as u16);
+            // Extra field, comment, disk number, internal and external attributes
+            put_u16(&mut self.out, 0);
+            put_u16(&mut self.out, 0);
+            put_u16(&mut self.out, 0);
+            put_u16(&mut self.out, 0);
+            put_u32(&mut self.out, 0);
+            put_u32(&mut self.out, entry.offset);
+            self.out.extend(entry.name.as_bytes());
+        }
+        let directory_size = self.out.len() as u32 - directory_offset;
+
+        put_u32(&mut self.out, END_OF_CENTRAL_DIRECTORY);
+        put_u16(&mut self.out, 0);
+        put_u16(&mut self.out, 0);
+        put_u16(&mut self.out, self.entries.len() as u16);
+        put_u16(&mut self.out, self.entries.len() as u16);
+        put_u32(&mut self.out, directory_size);
+        put_u32(&mut self.out, directory_offset);
+        put_u16(&mut self.out, 0);
+        self.out
+    }
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend(value.to_le_bytes());
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend(value.to_le_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    fn u16_at(bytes: &[u8], at: usize) -> usize {
+        u16::from_le_bytes([bytes[at], bytes[at + 1]]) as usize
+    }
+
+    fn u32_at(bytes: &[u8], at: usize) -> usize {
+        u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+    }
+
+    #[test]
+    fn test_zip_entries_round_trip() {
+        let text = "chapter ".repeat(100);
+        let mut zip = ZipWriter::new();
+        zip.add_stored("mimetype", b"application/epub+zip");
+        zip.add_deflated("OEBPS/chapter-1.xhtml", text.as_bytes())
+            .unwrap();
+        let bytes = zip.finish();
+
+        // The mimetype comes first and uncompressed, right after its header
+        assert_eq!(&bytes[30..38], b"mimetype");
+        assert_eq!(&bytes[38..58], b"application/epub+zip");
+
+        let end = bytes.len() - 22;
+        assert_eq!(u32_at(&bytes, end), END_OF_CENTRAL_DIRECTORY as usize);
+        assert_eq!(u16_at(&bytes, end + 10), 2);
+
+        // Follow the central directory to the second entry and inflate it
+        let mut at = u32_at(&bytes, end + 16);
+        at += 46 + u16_at(&bytes, at + 28);
+        assert_eq!(u32_at(&bytes, at), CENTRAL_HEADER as usize);
+        let local = u32_at(&bytes, at + 42);
+        let name_len = u16_at(&bytes, local + 26);
+        assert_eq!(
+            &bytes[local + 30..local + 30 + name_len],
+            b"OEBPS/chapter-1.xhtml"
+        );
+
+        let data_start = local + 30 + name_len;
+        let compressed = &bytes[data_start..data_start + u32_at(&bytes, local + 18)];
+        let mut inflated = String::new();
+        DeflateDecoder::new(compressed)
+            .read_to_string(&mut inflated)
+            .unwrap();
+        assert_eq!(inflated, text);
+
+        let mut crc = Crc::new();
+        crc.update(text.as_bytes());
+        assert_eq!(u32_at(&bytes, local + 14), crc.sum() as usize);
+    }
+}
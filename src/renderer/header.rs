@@ -0,0 +1,159 @@
+//! Page header content: logo, wordmark, subtitle, author and links.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use super::template::{escape_html, Context, Value};
+use crate::parser::parse_markdown;
+
+/// What the header shows in place of the logo.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeaderLogo {
+    /// Image data URI, also used as favicon
+    Image(String),
+    /// Inline SVG markup
+    Svg(String),
+    /// Text wordmark
+    Text(String),
+    None,
+}
+
+/// A contact or profile link shown in the header.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HeaderLink {
+    pub label: String,
+    pub url: String,
+}
+
+/// Everything the template shows in the page header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageHeader {
+    pub logo: HeaderLogo,
+    pub subtitle: Option<String>,
+    pub author: Option<String>,
+    pub links: Vec<HeaderLink>,
+    /// Markdown shown below the header, from the document preamble
+    pub intro: Option<String>,
+}
+
+impl PageHeader {
+    /// A header showing only the logo image.
+    pub fn with_logo(logo_data_uri: &str) -> Self {
+        let logo = if logo_data_uri.is_empty() {
+            HeaderLogo::None
+        } else {
+            HeaderLogo::Image(logo_data_uri.to_string())
+        };
+        Self {
+            logo,
+            subtitle: None,
+            author: None,
+            links: Vec::new(),
+            intro: None,
+        }
+    }
+
+    /// Markup for the logo slot of the header.
+    pub fn logo_html(&self) -> String {
+        match self.logo {
+            HeaderLogo::Image(ref uri) => {
+                format!("<img src=\"{}\" alt=\"Logo\">", escape_html(uri))
+            }
+            HeaderLogo::Svg(ref svg) => format!("<span class=\"logo-svg\">{}</span>", svg.trim()),
+            HeaderLogo::Text(ref text) => {
+                format!("<span class=\"wordmark\">{}</span>", escape_html(text))
+            }
+            HeaderLogo::None => String::new(),
+        }
+    }
+
+    /// Add `logo`, `logo_html`, `subtitle`, `author`, `links` and `intro` to a template context.
+    ///
+    /// Missing values are empty, so templates can test them with `{% if %}`.
+    pub fn insert_into(&self, context: &mut Context) {
+        let logo_uri = match self.logo {
+            HeaderLogo::Image(ref uri) => uri.as_str(),
+            _ => "",
+        };
+        context.insert("logo".to_string(), Value::from(logo_uri));
+        context.insert("logo_html".to_string(), Value::Html(self.logo_html()));
+        context.insert(
+            "subtitle".to_string(),
+            Value::from(self.subtitle.as_deref().unwrap_or_default()),
+        );
+        context.insert(
+            "author".to_string(),
+            Value::from(self.author.as_deref().unwrap_or_default()),
+        );
+        let links = self
+            .links
+            .iter()
+            .map(|link| {
+                let mut map = BTreeMap::new();
+                map.insert("label".to_string(), Value::Str(link.label.clone()));
+                map.insert("url".to_string(), Value::Str(link.url.clone()));
+                Value::Map(map)
+            })
+            .collect();
+        context.insert("links".to_string(), Value::List(links));
+        let intro = self
+            .intro
+            .as_deref()
+            .map(parse_markdown)
+            .unwrap_or_default();
+        context.insert("intro".to_string(), Value::Html(intro));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logo_html_variants() {
+        let mut header = PageHeader::with_logo("data:image/png;base64,AAAA");
+        assert_eq!(
+            header.logo_html(),
+            "<img src=\"data:image/png;base64,AAAA\" alt=\"Logo\">"
+        );
+
+        header.logo = HeaderLogo::Text("ACME <Docs>".to_string());
+        assert_eq!(
+            header.logo_html(),
+            "<span class=\"wordmark\">ACME &lt;Docs&gt;</span>"
+        );
+
+        header.logo = HeaderLogo::Svg("<svg></svg>\n".to_string());
+        assert_eq!(
+            header.logo_html(),
+            "<span class=\"logo-svg\"><svg></svg></span>"
+        );
+
+        assert_eq!(PageHeader::with_logo("").logo, HeaderLogo::None);
+    }
+
+    #[test]
+    fn test_insert_into_context() {
+        let header = PageHeader {
+            logo: HeaderLogo::Text("ACME".to_string()),
+            subtitle: Some("Platform team".to_string()),
+            author: None,
+            links: vec![HeaderLink {
+                label: "GitHub".to_string(),
+                url: "https://github.com/acme".to_string(),
+            }],
+            intro: Some("Owns the **build**".to_string()),
+        };
+        let mut context = Context::new();
+        header.insert_into(&mut context);
+
+        assert_eq!(context["logo"], Value::from(""));
+        assert_eq!(context["subtitle"], Value::from("Platform team"));
+        assert_eq!(context["author"], Value::from(""));
+        assert!(matches!(context["links"], Value::List(ref items) if items.len() == 1));
+        assert_eq!(
+            context["intro"],
+            Value::Html("<p>Owns the <strong>build</strong></p>\n".to_string())
+        );
+    }
+}
@@ -1,65 +1,156 @@
-use super::{ParsedDocument, RenderError, Renderer};
+use super::compress::self_extracting;
+use super::header::PageHeader;
+use super::markup::{tokenize, Token};
+use super::search::{index_script, SEARCH_BOX};
+use super::template::{escape_html, Context, Template, Value};
+use super::{DocumentMeta, ParsedDocument, RenderError, RenderOutput, Renderer};
 use crate::assets::{embed_image, Assets};
-use crate::parser::{parse_markdown, DocumentStructure, Section};
+use crate::parser::{
+    generate_unique_id, parse_markdown, slugify, DocumentStructure, NavItem, NavNode, Section,
+    SectionStyle, DOCUMENT_TOC_MARKER, TOC_MARKER,
+};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Styles added for the print layout, after the theme styles.
+pub const PRINT_STYLES: &str = include_str!("print.css");
+
+/// How the panels are arranged on the page.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageLayout {
+    /// One panel at a time, selected with tab buttons and the dropdown
+    #[default]
+    Tabs,
+    /// Every panel expanded in order after a table of contents
+    Print,
+}
 
 #[derive(Debug, Default)]
-pub struct HtmlRenderer;
+pub struct HtmlRenderer {
+    compress: bool,
+    layout: PageLayout,
+    search: bool,
+}
 
 impl HtmlRenderer {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Embed the panels gzip-compressed, expanded by an inline script on load.
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
     }
 
-    /// Render all panels from DocumentStructure.
+    /// Arrange the panels as tabs or expanded for printing.
+    ///
+    /// The print layout expects [`PRINT_STYLES`] among the asset styles.
+    pub fn with_layout(mut self, layout: PageLayout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    /// Add a search box and an index of the panel text to the tabs layout.
+    ///
+    /// The search box expects [`SEARCH_SCRIPT`](super::SEARCH_SCRIPT) and
+    /// [`SEARCH_STYLES`](super::SEARCH_STYLES) among the assets.
+    pub fn with_search(mut self, search: bool) -> Self {
+        self.search = search;
+        self
+    }
+
+    /// Wrap the rendered panels, as `(id, title, html)`, in their panel divs.
     /// Panels receive normalized markdown content, so no heading adjustment needed here.
-    fn render_panels(&self, doc: &DocumentStructure) -> String {
-        let mut result = String::new();
-        let mut first = true;
+    fn render_panels(&self, contents: &[(String, String, String)]) -> String {
+        contents
+            .iter()
+            .enumerate()
+            .map(|(i, (panel_id, title, html_content))| {
+                panel_div(panel_id, title, html_content, i > 0)
+            })
+            .collect()
+    }
 
-        for panel in &doc.panels {
-            let hidden_class = if first { "" } else { " hidden" };
-            first = false;
+    /// Render every panel expanded, with dropdown and group titles heading their items.
+    fn render_print_panels(
+        &self,
+        doc: &DocumentStructure,
+        contents: &[(String, String, String)],
+    ) -> String {
+        let mut result = String::new();
+        let mut current_group = None;
 
-            let html_content = parse_markdown(&panel.markdown_content);
+        for (panel_id, title, html_content) in contents {
+            let group = doc.parent_of(panel_id);
+            if let Some(group) = group.filter(|group| current_group != Some(&group.id)) {
+                result.push_str(&format!(
+                    "<h2 class=\"panel-group\" id=\"group-{}\">{}</h2>\n",
+                    group.id, group.title
+                ));
+            }
+            current_group = group.map(|group| &group.id);
 
-            result.push_str(&format!(
-                r#"<div class="panel{}" id="panel-{}">
-    <h2>{}</h2>
-    <div class="panel-content">
-        {}
-    </div>
-</div>
-"#,
-                hidden_class, panel.id, panel.title, html_content
-            ));
+            result.push_str(&panel_div(panel_id, title, html_content, false));
         }
 
         result
     }
 
-    /// Generate navigation buttons and dropdown
+    /// Generate the table of contents for the print layout, in panel order.
+    fn generate_toc(&self, doc: &DocumentStructure) -> String {
+        format!(
+            "<nav class=\"toc\">\n    <h2>Contents</h2>\n{}</nav>\n",
+            table_of_contents(
+                doc,
+                |id| format!("#panel-{}", id),
+                |group| Some(format!("#group-{}", group.id)),
+                |_, _| String::new()
+            )
+        )
+    }
+
+    /// Generate navigation buttons and groups in document order, followed by the dropdowns
     fn generate_nav_with_dropdown(&self, doc: &DocumentStructure) -> String {
         let mut result = String::new();
 
-        // Regular nav buttons
-        for item in &doc.nav_buttons {
-            result.push_str(&format!(
-                r#"<button data-panel="{}">{}</button>
+        for node in &doc.navigation {
+            match node.style {
+                SectionStyle::Button => result.push_str(&format!(
+                    r#"<button data-panel="{}">{}</button>
         "#,
-                item.id, item.title
-            ));
+                    node.id, node.title
+                )),
+                SectionStyle::Group => {
+                    result.push_str(&format!(
+                        r#"<details class="nav-group">
+            <summary>{}</summary>
+"#,
+                        node.title
+                    ));
+                    for item in &node.items {
+                        result.push_str(&format!(
+                            r#"            <button data-panel="{}">{}</button>
+"#,
+                            item.id, item.title
+                        ));
+                    }
+                    result.push_str("        </details>\n        ");
+                }
+                SectionStyle::Dropdown => {}
+            }
         }
 
-        // Dropdown if configured
-        if let Some(ref title) = doc.dropdown_title {
+        for node in doc.dropdowns() {
             result.push_str(&format!(
-                r#"<select id="dropdown">
+                r#"<select class="nav-dropdown" id="dropdown-{}">
             <option disabled selected>{}</option>
 "#,
-                title
+                node.id, node.title
             ));
 
-            for item in &doc.dropdown_items {
+            for item in &node.items {
                 result.push_str(&format!(
                     r#"            <option data-panel="{}">{}</option>
 "#,
@@ -67,9 +158,10 @@ impl HtmlRenderer {
                 ));
             }
 
-            result.push_str("        </select>");
+            result.push_str("        </select>\n        ");
         }
 
+        result.truncate(result.trim_end().len());
         result
     }
 
@@ -81,17 +173,85 @@ impl HtmlRenderer {
         logo_data_uri: &str,
         assets: &Assets,
     ) -> Result<Vec<u8>, RenderError> {
-        let panels = self.render_panels(doc);
-        let nav = self.generate_nav_with_dropdown(doc);
-
-        let html = assets
-            .template
-            .replace("{{title}}", title)
-            .replace("{{styles}}", &assets.styles)
-            .replace("{{logo}}", logo_data_uri)
-            .replace("{{nav_buttons}}", &nav)
-            .replace("{{content}}", &panels)
-            .replace("{{script}}", &assets.script);
+        self.render_page(doc, title, &PageHeader::with_logo(logo_data_uri), assets)
+    }
+
+    /// Render the panel-based page with full header content.
+    pub fn render_page(
+        &self,
+        doc: &DocumentStructure,
+        title: &str,
+        header: &PageHeader,
+        assets: &Assets,
+    ) -> Result<Vec<u8>, RenderError> {
+        // Rendered once, for the content, the search index and the `panels` variable
+        let contents = render_panel_contents(doc, |_| String::new());
+        let (mut nav, mut panels) = match self.layout {
+            PageLayout::Tabs => (
+                self.generate_nav_with_dropdown(doc),
+                self.render_panels(&contents),
+            ),
+            PageLayout::Print => (
+                String::new(),
+                self.generate_toc(doc) + &self.render_print_panels(doc, &contents),
+            ),
+        };
+        let mut script = assets.script.clone();
+        if self.search && self.layout == PageLayout::Tabs {
+            nav.push_str("\n        ");
+            nav.push_str(SEARCH_BOX);
+            // The index repeats the panel text, so it is compressed along with the panels
+            let index = index_script(&contents)?;
+            if self.compress {
+                panels.push_str(&format!("\n<script>\n{}</script>", index));
+            } else {
+                script = index + &script;
+            }
+        }
+        if self.compress {
+            panels = self_extracting(&panels)?;
+        }
+
+        let mut context = base_context(title, header, assets);
+        context.insert("nav_buttons".to_string(), Value::Html(nav));
+        context.insert("content".to_string(), Value::Html(panels));
+        context.insert("script".to_string(), Value::Html(script));
+        context.insert(
+            "print".to_string(),
+            Value::Bool(self.layout == PageLayout::Print),
+        );
+
+        // Structured data for templates that build their own markup
+        let panel_values = contents
+            .into_iter()
+            .enumerate()
+            .map(|(i, (id, title, html))| {
+                let mut panel = BTreeMap::new();
+                panel.insert("id".to_string(), Value::Str(id));
+                panel.insert("title".to_string(), Value::Str(title));
+                panel.insert("content".to_string(), Value::Html(html));
+                panel.insert("first".to_string(), Value::Bool(i == 0));
+                Value::Map(panel)
+            })
+            .collect();
+        context.insert("panels".to_string(), Value::List(panel_values));
+        let buttons: Vec<NavItem> = doc
+            .buttons()
+            .map(|node| NavItem {
+                id: node.id.clone(),
+                title: node.title.clone(),
+            })
+            .collect();
+        context.insert("nav_items".to_string(), nav_item_list(&buttons));
+        if let Some(dropdown) = doc.dropdowns().next() {
+            context.insert("dropdown".to_string(), nav_node_value(dropdown));
+        }
+        context.insert(
+            "navigation".to_string(),
+            Value::List(doc.navigation.iter().map(nav_node_value).collect()),
+        );
+
+        let html = Template::parse(&assets.template)?.render(&context, &assets.partials)?;
 
         Ok(html.into_bytes())
     }
@@ -166,26 +326,324 @@ impl HtmlRenderer {
             format!("const DROPDOWN_SECTION = null;\n{}", &assets.script)
         };
 
-        let html = assets
-            .template
-            .replace("{{title}}", &document.title)
-            .replace("{{styles}}", &assets.styles)
-            .replace("{{logo}}", logo_data_uri)
-            .replace("{{nav_buttons}}", &nav_buttons)
-            .replace("{{content}}", &wrapped_content)
-            .replace("{{script}}", &script);
+        let mut context = base_context(
+            &document.title,
+            &PageHeader::with_logo(logo_data_uri),
+            assets,
+        );
+        context.insert("nav_buttons".to_string(), Value::Html(nav_buttons));
+        context.insert("content".to_string(), Value::Html(wrapped_content));
+        context.insert("script".to_string(), Value::Html(script));
+
+        let html = Template::parse(&assets.template)?.render(&context, &assets.partials)?;
 
         Ok(html.into_bytes())
     }
 }
 
-impl Renderer for HtmlRenderer {
-    fn render(&self, document: &ParsedDocument, logo_path: &str, assets: &Assets) -> Result<Vec<u8>, RenderError> {
+/// The markup of one panel, hidden until selected if `hidden`.
+pub(super) fn panel_div(id: &str, title: &str, html_content: &str, hidden: bool) -> String {
+    let hidden_class = if hidden { " hidden" } else { "" };
+    format!(
+        r#"<div class="panel{}" id="panel-{}">
+    <h2>{}</h2>
+    <div class="panel-content">
+        {}
+    </div>
+</div>
+"#,
+        hidden_class, id, title, html_content
+    )
+}
+
+/// A nested list of all panels in order, dropdown and group items under
+/// their section title.
+///
+/// `href` gives the link target of a panel by id; a section title links to
+/// `group_href`, or is plain text where that returns `None`. `nested` adds
+/// markup below a panel's entry, given its id and indentation.
+pub(super) fn table_of_contents(
+    doc: &DocumentStructure,
+    href: impl Fn(&str) -> String,
+    group_href: impl Fn(&NavNode) -> Option<String>,
+    nested: impl Fn(&str, usize) -> String,
+) -> String {
+    let entry = |id: &str, title: &str, indent: usize| {
+        let pad = " ".repeat(indent);
+        let children = nested(id, indent + 4);
+        let end = if children.is_empty() {
+            String::new()
+        } else {
+            format!("\n{}{}", children, pad)
+        };
+        format!(
+            "{}<li><a href=\"{}\">{}</a>{}</li>\n",
+            pad,
+            href(id),
+            title,
+            end
+        )
+    };
+    let mut result = String::from("    <ol>\n");
+
+    for node in &doc.navigation {
+        if node.style == SectionStyle::Button {
+            result.push_str(&entry(&node.id, &node.title, 8));
+            continue;
+        }
+        if node.items.is_empty() {
+            continue;
+        }
+
+        let label = match group_href(node) {
+            Some(group_href) => format!("<a href=\"{}\">{}</a>", group_href, node.title),
+            None => format!("<span>{}</span>", node.title),
+        };
+        result.push_str(&format!("        <li>{}\n            <ol>\n", label));
+        for item in &node.items {
+            result.push_str(&entry(&item.id, &item.title, 16));
+        }
+        result.push_str("            </ol>\n        </li>\n");
+    }
+
+    result.push_str("    </ol>\n");
+    result
+}
+
+/// A heading given an id by [`anchor_headings`].
+#[derive(Debug, Clone, PartialEq)]
+struct Heading {
+    level: u8,
+    id: String,
+    text: String,
+}
+
+/// Nested list of links to `headings`, deeper levels under the heading before.
+///
+/// `page` is prefixed to the links, for headings on another page.
+fn heading_list(headings: &[Heading], page: &str, indent: usize) -> String {
+    let Some(top) = headings.iter().map(|heading| heading.level).min() else {
+        return String::new();
+    };
+    let pad = " ".repeat(indent);
+    let mut result = format!("{}<ol>\n", pad);
+
+    let mut rest = headings;
+    while let Some((heading, after)) = rest.split_first() {
+        let end = after
+            .iter()
+            .position(|h| h.level <= top)
+            .unwrap_or(after.len());
+        let children = heading_list(&after[..end], page, indent + 8);
+        let link = format!(
+            "<a href=\"{}#{}\">{}</a>",
+            page,
+            heading.id,
+            escape_html(&heading.text)
+        );
+        if children.is_empty() {
+            result.push_str(&format!("{}    <li>{}</li>\n", pad, link));
+        } else {
+            result.push_str(&format!(
+                "{}    <li>{}\n{}{}    </li>\n",
+                pad, link, children, pad
+            ));
+        }
+        rest = &after[end..];
+    }
+
+    result.push_str(&format!("{}</ol>\n", pad));
+    result
+}
+
+/// Convert each panel's markdown to HTML with anchored headings, as `(id, title, html)`.
+///
+/// Table of contents markers become lists of links; `page` gives the file
+/// a panel is on, empty when all panels are on one page.
+pub(super) fn render_panel_contents(
+    doc: &DocumentStructure,
+    page: impl Fn(&str) -> String,
+) -> Vec<(String, String, String)> {
+    // Heading ids must not take the ids of panels or sections, which are link targets too
+    let mut used_ids: Vec<String> = doc
+        .navigation
+        .iter()
+        .flat_map(|node| std::iter::once(&node.id).chain(node.items.iter().map(|item| &item.id)))
+        .cloned()
+        .collect();
+
+    let rendered: Vec<(String, Vec<Heading>)> = doc
+        .panels
+        .iter()
+        .map(|panel| {
+            let mut headings = Vec::new();
+            let html = anchor_headings(
+                &parse_markdown(&panel.markdown_content),
+                &mut used_ids,
+                &mut headings,
+            );
+            headings.retain(|heading| heading.level >= 3);
+            (html, headings)
+        })
+        .collect();
+
+    let panel_marker = format!("<p>{}</p>", TOC_MARKER);
+    let document_marker = format!("<p>{}</p>", DOCUMENT_TOC_MARKER);
+    let mut document_toc = None;
+
+    doc.panels
+        .iter()
+        .zip(&rendered)
+        .map(|(panel, (html, headings))| {
+            let mut html = html.clone();
+            if html.contains(&panel_marker) {
+                let toc = match heading_list(headings, "", 4) {
+                    list if list.is_empty() => String::new(),
+                    list => format!("<nav class=\"panel-toc\">\n{}</nav>", list),
+                };
+                html = html.replace(&panel_marker, &toc);
+            }
+            if html.contains(&document_marker) {
+                let toc = document_toc.get_or_insert_with(|| {
+                    let headings_of = |id: &str| {
+                        let index = doc.panels.iter().position(|panel| panel.id == id);
+                        index.map_or(&[][..], |index| &rendered[index].1)
+                    };
+                    format!(
+                        "<nav class=\"document-toc\">\n{}</nav>",
+                        table_of_contents(
+                            doc,
+                            |id| format!("{}#panel-{}", page(id), id),
+                            |_| None,
+                            |id, indent| heading_list(headings_of(id), &page(id), indent)
+                        )
+                    )
+                });
+                html = html.replace(&document_marker, toc);
+            }
+            (panel.id.clone(), panel.title.clone(), html)
+        })
+        .collect()
+}
+
+/// Give every `<hN>` heading an id, unique among `used_ids`, and a permalink,
+/// and add it to `headings`.
+///
+/// Headings with attributes are raw HTML from the Markdown and are left alone.
+fn anchor_headings(html: &str, used_ids: &mut Vec<String>, headings: &mut Vec<Heading>) -> String {
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<h") {
+        let level = rest[start + 2..]
+            .chars()
+            .next()
+            .filter(|c| ('1'..='6').contains(c));
+        let close = level.map(|level| format!("</h{}>", level));
+        let end = close
+            .as_ref()
+            .filter(|_| rest[start + 3..].starts_with('>'))
+            .and_then(|close| rest[start..].find(close.as_str()));
+        let (Some(level), Some(close), Some(end)) = (level, close, end) else {
+            result.push_str(&rest[..start + 2]);
+            rest = &rest[start + 2..];
+            continue;
+        };
+
+        let inner = &rest[start + 4..start + end];
+        let text: String = tokenize(inner)
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Text(text) => Some(text),
+                _ => None,
+            })
+            .collect();
+        let title = if slugify(&text).is_empty() {
+            "section"
+        } else {
+            text.as_str()
+        };
+        let id = generate_unique_id(title, used_ids);
+
+        result.push_str(&rest[..start]);
+        result.push_str(&format!(
+            "<h{0} id=\"{1}\">{2} <a class=\"heading-anchor\" href=\"#{1}\" aria-label=\"Link to this section\">#</a></h{0}>",
+            level, id, inner
+        ));
+        headings.push(Heading {
+            level: level.to_digit(10).unwrap_or(0) as u8,
+            id,
+            text: text.trim().to_string(),
+        });
+        rest = &rest[start + end + close.len()..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Template variables shared by all render paths.
+pub(super) fn base_context(title: &str, header: &PageHeader, assets: &Assets) -> Context {
+    let mut context = Context::new();
+    context.insert("title".to_string(), Value::from(title));
+    header.insert_into(&mut context);
+    context.insert("styles".to_string(), Value::Html(assets.styles.clone()));
+    context
+}
+
+/// A navigation entry as a template map of `{ id, title, style, items }`.
+fn nav_node_value(node: &NavNode) -> Value {
+    let mut map = BTreeMap::new();
+    map.insert("id".to_string(), Value::Str(node.id.clone()));
+    map.insert("title".to_string(), Value::Str(node.title.clone()));
+    map.insert(
+        "style".to_string(),
+        Value::Str(node.style.as_str().to_string()),
+    );
+    map.insert("items".to_string(), nav_item_list(&node.items));
+    Value::Map(map)
+}
+
+/// Navigation items as a template list of `{ id, title }` maps.
+fn nav_item_list(items: &[NavItem]) -> Value {
+    Value::List(
+        items
+            .iter()
+            .map(|item| {
+                let mut map = BTreeMap::new();
+                map.insert("id".to_string(), Value::Str(item.id.clone()));
+                map.insert("title".to_string(), Value::Str(item.title.clone()));
+                Value::Map(map)
+            })
+            .collect(),
+    )
+}
+
+impl HtmlRenderer {
+    /// Render a legacy `ParsedDocument` with collapsible sections.
+    pub fn render_document(
+        &self,
+        document: &ParsedDocument,
+        logo_path: &str,
+        assets: &Assets,
+    ) -> Result<Vec<u8>, RenderError> {
         let logo_data_uri = embed_image(logo_path)?;
         self.render_with_options(document, &logo_data_uri, Some("Related Documents"), assets)
     }
 }
 
+impl Renderer for HtmlRenderer {
+    fn render(
+        &self,
+        doc: &DocumentStructure,
+        meta: &DocumentMeta,
+        _warnings: &mut Vec<String>,
+    ) -> Result<RenderOutput, RenderError> {
+        let html = self.render_page(doc, meta.title, meta.header, meta.assets)?;
+        Ok(RenderOutput::File(html))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -205,6 +663,7 @@ mod tests {
 </body>
 </html>"#
                 .to_string(),
+            ..Default::default()
         }
     }
 
@@ -319,7 +778,7 @@ mod tests {
             0x9C, 0x63, 0x00, 0x01, 0x00, 0x00, 0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00,
             0x00, 0x00, 0x00, 0x49, 0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
         ];
-        std::fs::write(&logo_path, &png_bytes).unwrap();
+        std::fs::write(&logo_path, png_bytes).unwrap();
 
         let document = ParsedDocument {
             html_content: "<h2>Test</h2><p>Content</p>".to_string(),
@@ -333,7 +792,7 @@ mod tests {
         };
 
         let assets = test_assets();
-        let result = renderer.render(&document, logo_path.to_str().unwrap(), &assets);
+        let result = renderer.render_document(&document, logo_path.to_str().unwrap(), &assets);
         assert!(result.is_ok());
 
         let html = String::from_utf8(result.unwrap()).unwrap();
@@ -356,7 +815,214 @@ mod tests {
         };
 
         let assets = test_assets();
-        let result = renderer.render(&document, "nonexistent_logo.png", &assets);
+        let result = renderer.render_document(&document, "nonexistent_logo.png", &assets);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_render_does_not_substitute_placeholders_in_content() {
+        let renderer = HtmlRenderer::new();
+        let doc = crate::parser::parse_document_structure(
+            "## Docs\n\nWrite `{{content}}` in templates.",
+            None,
+        );
+
+        let html = renderer
+            .render_from_structure(&doc, "Docs", "data:image/png;base64,AAAA", &test_assets())
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("<code>{{content}}</code>"));
+    }
+
+    #[test]
+    fn test_render_template_loops_over_panels() {
+        let renderer = HtmlRenderer::new();
+        let doc = crate::parser::parse_document_structure("## One\nA\n\n## Two\nB\n", None);
+        let assets = Assets {
+            template: "{% for panel in panels %}<section id=\"{{panel.id}}\">{{panel.title}}</section>{% endfor %}".to_string(),
+            ..Default::default()
+        };
+
+        let html = renderer
+            .render_from_structure(&doc, "T", "", &assets)
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert_eq!(
+            html,
+            "<section id=\"one\">One</section><section id=\"two\">Two</section>"
+        );
+    }
+
+    #[test]
+    fn test_print_layout_expands_panels_with_toc() {
+        let renderer = HtmlRenderer::new().with_layout(PageLayout::Print);
+        let markdown =
+            "## About\nA\n\n## Projects\n\n### Vellum\nV\n\n### Other\nO\n\n## Contact\nC\n";
+        let doc = crate::parser::parse_document_structure(markdown, Some("Projects"));
+
+        let html = renderer
+            .render_from_structure(&doc, "T", "", &test_assets())
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(!html.contains("hidden"));
+        assert!(!html.contains("<button"));
+        assert!(!html.contains("<select"));
+        assert!(html.contains(
+            "<li><a href=\"#group-projects\">Projects</a>\n            <ol>\n                <li><a href=\"#panel-vellum\">Vellum</a></li>"
+        ));
+        assert!(html.contains("            </ol>\n        </li>\n        <li><a href=\"#panel-contact\">Contact</a></li>"));
+
+        // Panels in document order, the dropdown title heading its items
+        let order: Vec<usize> = [
+            "id=\"panel-about\"",
+            "id=\"group-projects\"",
+            "id=\"panel-vellum\"",
+            "id=\"panel-other\"",
+            "id=\"panel-contact\"",
+        ]
+        .iter()
+        .map(|id| html.find(id).unwrap())
+        .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_toc_markers() {
+        let markdown = "## Goals\n\n[[toc]]\n\n### Q1 & Q2\n\n#### Hiring\n\n### Q3\n\n## Contents\n\n[[toc:all]]\n\n## Empty\n\n[[toc]]\n";
+        let doc = crate::parser::parse_document_structure(markdown, None);
+        let contents = render_panel_contents(&doc, |_| String::new());
+
+        assert!(contents[0].2.starts_with(
+            "<nav class=\"panel-toc\">\n    <ol>\n        <li><a href=\"#q1-q2\">Q1 &amp; Q2</a>\n            <ol>\n                <li><a href=\"#hiring\">Hiring</a></li>\n            </ol>\n        </li>\n        <li><a href=\"#q3\">Q3</a></li>\n    </ol>\n</nav>"
+        ));
+        assert!(contents[1].2.contains(
+            "<li><a href=\"#panel-goals\">Goals</a>\n            <ol>\n                <li><a href=\"#q1-q2\">"
+        ));
+        assert!(contents[1]
+            .2
+            .contains("<li><a href=\"#panel-empty\">Empty</a></li>"));
+        assert!(!contents[2].2.contains("toc"));
+
+        // On separate pages, links lead to the page of the panel
+        let contents = render_panel_contents(&doc, |id| format!("{}.html", id));
+        assert!(contents[1]
+            .2
+            .contains("<li><a href=\"goals.html#panel-goals\">Goals</a>"));
+        assert!(contents[1]
+            .2
+            .contains("<li><a href=\"goals.html#q3\">Q3</a></li>"));
+        assert!(contents[0].2.contains("<li><a href=\"#q3\">Q3</a></li>"));
+    }
+
+    #[test]
+    fn test_search_box_and_index() {
+        let markdown = "## About\nHello\n\n## Notes\n\n### Setup\nInstall it\n";
+        let doc = crate::parser::parse_document_structure(markdown, None);
+
+        let html = HtmlRenderer::new()
+            .with_search(true)
+            .render_from_structure(&doc, "T", "", &test_assets())
+            .unwrap();
+        let html = String::from_utf8(html).unwrap();
+        assert!(html.contains("<input type=\"search\" id=\"search-input\""));
+        assert!(html.contains(
+            "<script>const SEARCH_INDEX = [[\"about\",\"About\",\"\",\"Hello\"],[\"notes\",\"Notes\",\"\",\"\"],[\"setup\",\"Notes\",\"Setup\",\"Install it\"]];\nconsole.log('test');</script>"
+        ));
+
+        // Compressed pages carry the index inside the compressed panels
+        let compressed = HtmlRenderer::new()
+            .with_search(true)
+            .with_compression(true)
+            .render_from_structure(&doc, "T", "", &test_assets())
+            .unwrap();
+        let compressed = String::from_utf8(compressed).unwrap();
+        assert!(compressed.contains("search-input"));
+        assert!(!compressed.contains("SEARCH_INDEX"));
+        assert!(compressed.contains("<script>console.log('test');</script>"));
+
+        // The print layout shows every panel, so the browser can search it
+        let print = HtmlRenderer::new()
+            .with_search(true)
+            .with_layout(PageLayout::Print)
+            .render_from_structure(&doc, "T", "", &test_assets())
+            .unwrap();
+        let print = String::from_utf8(print).unwrap();
+        assert!(!print.contains("search-input"));
+        assert!(!print.contains("SEARCH_INDEX"));
+    }
+
+    #[test]
+    fn test_nav_with_groups_and_dropdowns() {
+        let renderer = HtmlRenderer::new();
+        let markdown = "## Archive\n### 2023\nA\n\n## Guides\n### Install\nI\n\n## About\nB\n";
+        let styles = std::collections::HashMap::from([
+            ("Archive".to_string(), SectionStyle::Dropdown),
+            ("Guides".to_string(), SectionStyle::Group),
+        ]);
+        let doc = crate::parser::parse_document_tree(markdown, &styles);
+
+        let nav = renderer.generate_nav_with_dropdown(&doc);
+        assert!(nav.contains("<details class=\"nav-group\">\n            <summary>Guides</summary>\n            <button data-panel=\"install\">Install</button>\n        </details>"));
+        assert!(nav.contains("<select class=\"nav-dropdown\" id=\"dropdown-archive\">"));
+        assert!(nav.contains("<option data-panel=\"2023\">2023</option>"));
+
+        // Groups and buttons in document order, dropdowns at the end
+        let order: Vec<usize> = ["<summary>Guides", "data-panel=\"about\"", "<select"]
+            .iter()
+            .map(|needle| nav.find(needle).unwrap())
+            .collect();
+        assert!(order.windows(2).all(|w| w[0] < w[1]));
+        assert!(nav.ends_with("</select>"));
+    }
+
+    #[test]
+    fn test_anchor_headings_unique_ids() {
+        let mut used_ids = vec!["skills".to_string()];
+        let mut headings = Vec::new();
+        let html = "<h3>Skills</h3>\n<h3>Rust &amp; <em>C</em></h3>\n<h4 class=\"x\">Raw</h4><hr />\n<h3>Skills</h3>\n<h3>!!</h3>";
+        assert_eq!(
+            anchor_headings(html, &mut used_ids, &mut headings),
+            "<h3 id=\"skills-1\">Skills <a class=\"heading-anchor\" href=\"#skills-1\" aria-label=\"Link to this section\">#</a></h3>\n\
+             <h3 id=\"rust-c\">Rust &amp; <em>C</em> <a class=\"heading-anchor\" href=\"#rust-c\" aria-label=\"Link to this section\">#</a></h3>\n\
+             <h4 class=\"x\">Raw</h4><hr />\n\
+             <h3 id=\"skills-2\">Skills <a class=\"heading-anchor\" href=\"#skills-2\" aria-label=\"Link to this section\">#</a></h3>\n\
+             <h3 id=\"section\">!! <a class=\"heading-anchor\" href=\"#section\" aria-label=\"Link to this section\">#</a></h3>"
+        );
+        assert_eq!(headings.len(), 4);
+        assert_eq!(
+            headings[1],
+            Heading {
+                level: 3,
+                id: "rust-c".to_string(),
+                text: "Rust & C".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_heading_ids_unique_across_panels() {
+        let markdown = "## About\n### Goals\nA\n\n## Projects\n### Goals\nB\n\n### About\nC\n";
+        let doc = crate::parser::parse_document_structure(markdown, None);
+
+        let contents = render_panel_contents(&doc, |_| String::new());
+        assert!(contents[0].2.starts_with("<h3 id=\"goals\">"));
+        assert!(contents[1].2.starts_with("<h3 id=\"goals-1\">"));
+        assert!(contents[1].2.contains("<h3 id=\"about-1\">"));
+    }
+
+    #[test]
+    fn test_render_template_error_has_line() {
+        let renderer = HtmlRenderer::new();
+        let doc = crate::parser::parse_document_structure("## One\nA\n", None);
+        let assets = Assets {
+            template: "<html>\n{% if title %}\n<body>".to_string(),
+            ..Default::default()
+        };
+
+        let result = renderer.render_from_structure(&doc, "T", "", &assets);
+        assert!(matches!(
+            result,
+            Err(RenderError::TemplateError { line: 2, .. })
+        ));
+    }
 }
@@ -0,0 +1,116 @@
+/**
+ * Vellum - Self-extracting content
+ * Inflates a base64 gzip payload and writes it into the page while it loads,
+ * so the theme script finds the panels as usual.
+ */
+(function (payload) {
+    var bin = atob(payload), src = new Uint8Array(bin.length);
+    for (var i = 0; i < bin.length; i++) src[i] = bin.charCodeAt(i);
+
+    // Skip the 10-byte gzip header; the payload never sets optional fields
+    var pos = 10, bitBuf = 0, bitCount = 0, out = [];
+
+    function bits(n) {
+        while (bitCount < n) {
+            bitBuf |= src[pos++] << bitCount;
+            bitCount += 8;
+        }
+        var value = bitBuf & ((1 << n) - 1);
+        bitBuf >>>= n;
+        bitCount -= n;
+        return value;
+    }
+
+    // Canonical Huffman code: code counts per length, symbols in code order
+    function huffman(lengths) {
+        var counts = [], offsets = [0], symbols = [];
+        for (var l = 0; l < 16; l++) counts[l] = 0;
+        lengths.forEach(function (len) { counts[len]++; });
+        counts[0] = 0;
+        for (l = 1; l < 16; l++) offsets[l] = offsets[l - 1] + counts[l - 1];
+        lengths.forEach(function (len, symbol) {
+            if (len) symbols[offsets[len]++] = symbol;
+        });
+        return { counts: counts, symbols: symbols };
+    }
+
+    function decode(code) {
+        var value = 0, first = 0, index = 0;
+        for (var len = 1; len < 16; len++) {
+            value |= bits(1);
+            var count = code.counts[len];
+            if (value - first < count) return code.symbols[index + value - first];
+            index += count;
+            first = (first + count) << 1;
+            value <<= 1;
+        }
+        throw new Error('Corrupt payload');
+    }
+
+    function repeat(value, times) {
+        var list = [];
+        for (var i = 0; i < times; i++) list.push(value);
+        return list;
+    }
+
+    var LENGTH_BASE = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59,
+        67, 83, 99, 115, 131, 163, 195, 227, 258];
+    var LENGTH_EXTRA = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4,
+        5, 5, 5, 5, 0];
+    var DIST_BASE = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513,
+        769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+    var DIST_EXTRA = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10,
+        11, 11, 12, 12, 13, 13];
+    var CODE_ORDER = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+    var last;
+    do {
+        last = bits(1);
+        var type = bits(2), lit, dist;
+
+        if (type === 0) {
+            // Stored block: byte aligned length, its complement, raw bytes
+            bitBuf = bitCount = 0;
+            var stored = src[pos] | (src[pos + 1] << 8);
+            pos += 4;
+            for (i = 0; i < stored; i++) out.push(src[pos++]);
+            continue;
+        }
+
+        if (type === 1) {
+            lit = huffman(repeat(8, 144).concat(repeat(9, 112), repeat(7, 24), repeat(8, 8)));
+            dist = huffman(repeat(5, 30));
+        } else {
+            var litCount = bits(5) + 257, distCount = bits(5) + 1, lenCount = bits(4) + 4;
+            var codeLengths = repeat(0, 19);
+            for (i = 0; i < lenCount; i++) codeLengths[CODE_ORDER[i]] = bits(3);
+            var lengthCode = huffman(codeLengths), lengths = [];
+            while (lengths.length < litCount + distCount) {
+                var symbol = decode(lengthCode);
+                if (symbol < 16) lengths.push(symbol);
+                else if (symbol === 16) lengths = lengths.concat(repeat(lengths[lengths.length - 1], 3 + bits(2)));
+                else if (symbol === 17) lengths = lengths.concat(repeat(0, 3 + bits(3)));
+                else lengths = lengths.concat(repeat(0, 11 + bits(7)));
+            }
+            lit = huffman(lengths.slice(0, litCount));
+            dist = huffman(lengths.slice(litCount));
+        }
+
+        for (;;) {
+            var sym = decode(lit);
+            if (sym < 256) {
+                out.push(sym);
+            } else if (sym === 256) {
+                break;
+            } else {
+                sym -= 257;
+                var length = LENGTH_BASE[sym] + bits(LENGTH_EXTRA[sym]);
+                var d = decode(dist);
+                var back = DIST_BASE[d] + bits(DIST_EXTRA[d]);
+                for (i = 0; i < length; i++) out.push(out[out.length - back]);
+            }
+        }
+    } while (!last);
+
+    document.write(new TextDecoder().decode(new Uint8Array(out)));
+})
\ No newline at end of file
@@ -0,0 +1,158 @@
+//! Flattened Markdown output: the whole document as a single Markdown file.
+//!
+//! Includes and variables are resolved, and Vellum's own syntax has already
+//! been turned into inline HTML (tags, markers, skill matrices), so the file
+//! renders the same in any Markdown viewer that allows HTML.
+
+use super::header::{HeaderLogo, PageHeader};
+use super::{DocumentMeta, RenderError, RenderOutput, Renderer};
+use crate::parser::{strip_toc_markers, DocumentStructure};
+
+/// Renders a document structure back to one Markdown file.
+#[derive(Debug, Default)]
+pub struct MarkdownRenderer;
+
+impl MarkdownRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// The title heading followed by all panels; dropdown and group items
+    /// become H3 sections below their section title.
+    pub fn render_markdown(
+        &self,
+        doc: &DocumentStructure,
+        title: &str,
+        header: &PageHeader,
+    ) -> String {
+        let mut result = format!("# {}\n", title);
+        result.push_str(&header_lines(header));
+
+        let mut current_group = None;
+        for panel in &doc.panels {
+            let group = doc.parent_of(&panel.id);
+            let content = strip_toc_markers(&panel.markdown_content);
+            let content = content.trim_matches(|c| c == '\n' || c == '\r');
+
+            match group {
+                Some(group) => {
+                    if current_group != Some(&group.id) {
+                        result.push_str(&format!("\n## {}\n", group.title));
+                    }
+                    result.push_str(&format!("\n### {}\n", panel.title));
+                    push_content(&mut result, &deepen_headings(content));
+                }
+                None => {
+                    result.push_str(&format!("\n## {}\n", panel.title));
+                    push_content(&mut result, content);
+                }
+            }
+            current_group = group.map(|group| &group.id);
+        }
+
+        result
+    }
+}
+
+impl Renderer for MarkdownRenderer {
+    fn render(
+        &self,
+        doc: &DocumentStructure,
+        meta: &DocumentMeta,
+        _warnings: &mut Vec<String>,
+    ) -> Result<RenderOutput, RenderError> {
+        let markdown = self.render_markdown(doc, meta.title, meta.header);
+        Ok(RenderOutput::File(markdown.into_bytes()))
+    }
+}
+
+/// Wordmark, subtitle, author, links and intro below the title.
+fn header_lines(header: &PageHeader) -> String {
+    let mut lines = Vec::new();
+    if let HeaderLogo::Text(ref wordmark) = header.logo {
+        lines.push(format!("**{}**", wordmark));
+    }
+    if let Some(ref subtitle) = header.subtitle {
+        lines.push(format!("*{}*", subtitle));
+    }
+    if let Some(ref author) = header.author {
+        lines.push(author.clone());
+    }
+
+    let mut result = String::new();
+    if !lines.is_empty() {
+        result.push_str(&format!("\n{}\n", lines.join("  \n")));
+    }
+    if !header.links.is_empty() {
+        result.push('\n');
+        for link in &header.links {
+            result.push_str(&format!("- [{}]({})\n", link.label, link.url));
+        }
+    }
+    if let Some(ref intro) = header.intro {
+        push_content(&mut result, intro);
+    }
+    result
+}
+
+fn push_content(result: &mut String, content: &str) {
+    if !content.trim().is_empty() {
+        result.push('\n');
+        result.push_str(content);
+        result.push('\n');
+    }
+}
+
+/// Move ATX headings one level down (`###` to `####`), outside code blocks.
+///
+/// Undoes the normalization of dropdown item panels, whose content was
+/// shifted up to start at H3.
+fn deepen_headings(markdown: &str) -> String {
+    let mut in_code = false;
+    markdown
+        .lines()
+        .map(|line| {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code = !in_code;
+            }
+            let level = trimmed.chars().take_while(|&c| c == '#').count();
+            let is_heading = (1..6).contains(&level) && trimmed[level..].starts_with(' ');
+            if !in_code && is_heading {
+                line.replacen('#', "##", 1)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_document_structure;
+
+    #[test]
+    fn test_render_markdown_with_dropdown() {
+        let markdown = "## About\n\nHello\n\n## Projects\n\n### Vellum\n\nGenerator\n\n#### Details\n\nMore\n\n## Contact\n\nMail\n";
+        let doc = parse_document_structure(markdown, Some("Projects"));
+        let mut header = PageHeader::with_logo("");
+        header.subtitle = Some("Engineer".to_string());
+
+        let result = MarkdownRenderer::new().render_markdown(&doc, "Portfolio", &header);
+        assert_eq!(
+            result,
+            "# Portfolio\n\n*Engineer*\n\n## About\n\nHello\n\n## Projects\n\n### Vellum\n\nGenerator\n\n#### Details\n\nMore\n\n## Contact\n\nMail\n"
+        );
+    }
+
+    #[test]
+    fn test_deepen_headings_skips_code() {
+        let markdown = "### Title\n```\n### not a heading\n```\n###### Deepest\n#hashtag";
+        assert_eq!(
+            deepen_headings(markdown),
+            "#### Title\n```\n### not a heading\n```\n###### Deepest\n#hashtag"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! A minimal HTML tokenizer for the markup Vellum renders itself.
+//!
+//! It understands tags, attributes, comments and the common character
+//! references; it does not build a tree or repair broken markup. Tag names
+//! are lowercased; attribute names keep their case, as SVG needs it.
+
+/// One piece of HTML.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Open {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    Close(String),
+    Text(String),
+}
+
+impl Token {
+    /// Value of an attribute of an opening tag, by case-insensitive name.
+    pub fn attr(&self, key: &str) -> Option<&str> {
+        match self {
+            Token::Open { attrs, .. } => attrs
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Whether an opening tag has `class` among its classes.
+    pub fn has_class(&self, class: &str) -> bool {
+        self.attr("class")
+            .is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+    }
+}
+
+/// Split HTML into tokens. Void elements produce only an opening token.
+pub fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.find("-->").map_or("", |end| &after[end + 3..]);
+            continue;
+        }
+        if rest.starts_with('<') {
+            if let Some(end) = tag_end(rest) {
+                if let Some(token) = parse_tag(&rest[1..end]) {
+                    tokens.push(token);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+        let first = rest.chars().next().map_or(1, char::len_utf8);
+        let text_end = rest[first..].find('<').map_or(rest.len(), |i| i + first);
+        tokens.push(Token::Text(decode_entities(&rest[..text_end])));
+        rest = &rest[text_end..];
+    }
+
+    tokens
+}
+
+/// Position of the `>` closing a tag, skipping quoted attribute values.
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_tag(inner: &str) -> Option<Token> {
+    if let Some(name) = inner.strip_prefix('/') {
+        return Some(Token::Close(name.trim().to_ascii_lowercase()));
+    }
+    let inner = inner.trim_end_matches('/');
+    let name_end = inner
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(inner.len());
+    let name = &inner[..name_end];
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+
+    let mut attrs = Vec::new();
+    let mut rest = inner[name_end..].trim_start();
+    while !rest.is_empty() {
+        let key_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let key = rest[..key_end].to_string();
+        rest = rest[key_end..].trim_start();
+        let mut value = String::new();
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (raw, remaining) = match after.chars().next() {
+                Some(q @ ('"' | '\'')) => {
+                    let end = after[1..].find(q).map_or(after.len(), |i| i + 1);
+                    (&after[1..end], after.get(end + 1..).unwrap_or(""))
+                }
+                _ => {
+                    let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = decode_entities(raw);
+            rest = remaining.trim_start();
+        }
+        if !key.is_empty() {
+            attrs.push((key, value));
+        }
+    }
+
+    Some(Token::Open {
+        name: name.to_ascii_lowercase(),
+        attrs,
+    })
+}
+
+/// Replace character references with the characters they stand for.
+pub fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some('\u{a0}'),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end + 1))
+        });
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize("<p class=\"a b\">x &amp; y<br /></p><!-- c -->");
+        assert_eq!(tokens.len(), 4);
+        assert!(tokens[0].has_class("b"));
+        assert_eq!(tokens[1], Token::Text("x & y".to_string()));
+        assert!(matches!(tokens[2], Token::Open { ref name, .. } if name == "br"));
+        assert_eq!(tokens[3], Token::Close("p".to_string()));
+    }
+
+    #[test]
+    fn test_attributes() {
+        let tokens = tokenize("<td COLSPAN=3 style='text-align: center' data-x=\"a>b\">");
+        assert_eq!(tokens[0].attr("colspan"), Some("3"));
+        assert_eq!(tokens[0].attr("style"), Some("text-align: center"));
+        assert_eq!(tokens[0].attr("data-x"), Some("a>b"));
+    }
+
+    #[test]
+    fn test_stray_angle_bracket_is_text() {
+        let tokens = tokenize("a < b");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Text("a ".to_string()),
+                Token::Text("< b".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_starting_with_multibyte_character() {
+        let tokens = tokenize("<p>Привет</p>");
+        assert_eq!(tokens[1], Token::Text("Привет".to_string()));
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("&lt;a&gt; &#39;x&#x27; &bogus; AT&T"),
+            "<a> 'x' &bogus; AT&T"
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Conservative minification of the generated CSS, JavaScript and HTML.
+//!
+//! Each pass only removes what is safe without a full parser: comments and
+//! redundant whitespace. Strings, regular expression literals and
+//! whitespace-sensitive elements are left untouched.
+
+/// Minify a stylesheet: drop comments and whitespace around punctuation.
+pub fn minify_css(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut chars = css.chars().peekable();
+    let mut pending_space = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '"' | '\'' => {
+                flush_space(&mut out, &mut pending_space);
+                out.push(c);
+                copy_string(&mut chars, &mut out, c);
+            }
+            c if c.is_whitespace() => pending_space = true,
+            '{' | '}' | ';' | ',' | '>' => {
+                pending_space = false;
+                if c == '}' && out.ends_with(';') {
+                    out.pop();
+                }
+                out.push(c);
+                skip_whitespace(&mut chars);
+            }
+            ':' => {
+                // Keep the space before `:` (descendant pseudo-class selectors)
+                flush_space(&mut out, &mut pending_space);
+                out.push(c);
+                if !in_selector(&out) {
+                    skip_whitespace(&mut chars);
+                }
+            }
+            _ => {
+                flush_space(&mut out, &mut pending_space);
+                out.push(c);
+            }
+        }
+    }
+
+    out.trim().to_string()
+}
+
+/// Minify a script: drop comments, indentation and blank lines.
+///
+/// Line breaks are kept so automatic semicolon insertion still works.
+pub fn minify_js(js: &str) -> String {
+    let mut stripped = String::with_capacity(js.len());
+    let mut chars = js.chars().peekable();
+    // Last significant character, to tell a regex literal from a division
+    let mut last_significant = '\0';
+
+    while let Some(c) = chars.next() {
+        match c {
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+                stripped.push(' ');
+            }
+            '/' if last_significant == '\0'
+                || "(,=:[!&|?{};+-*%<>~^".contains(last_significant) =>
+            {
+                stripped.push(c);
+                copy_regex(&mut chars, &mut stripped);
+                last_significant = '/';
+            }
+            '"' | '\'' | '`' => {
+                stripped.push(c);
+                copy_string(&mut chars, &mut stripped, c);
+                last_significant = c;
+            }
+            _ => {
+                if !c.is_whitespace() {
+                    last_significant = c;
+                }
+                stripped.push(c);
+            }
+        }
+    }
+
+    stripped
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Minify HTML: drop comments and collapse whitespace runs.
+///
+/// A run containing a line break becomes a single newline, any other run a
+/// single space, so inline text keeps its word boundaries. The content of
+/// `pre`, `textarea`, `script` and `style` elements is copied as is.
+pub fn minify_html(html: &str) -> String {
+    const RAW_ELEMENTS: [&str; 4] = ["pre", "textarea", "script", "style"];
+
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            match after.find("-->") {
+                Some(end) => rest = &after[end + 3..],
+                None => rest = "",
+            }
+            continue;
+        }
+
+        if rest.starts_with('<') {
+            if let Some(element) = RAW_ELEMENTS.iter().find(|el| starts_with_tag(rest, el)) {
+                let close = format!("</{}", element);
+                let end = find_ignore_case(rest, &close).unwrap_or(rest.len());
+                out.push_str(&rest[..end]);
+                rest = &rest[end..];
+                continue;
+            }
+        }
+
+        let c = rest.chars().next().unwrap_or_default();
+        if c.is_whitespace() {
+            let run_len = rest
+                .find(|c: char| !c.is_whitespace())
+                .unwrap_or(rest.len());
+            let mut newline = rest[..run_len].contains('\n');
+            // Merge with whitespace left before a removed comment
+            if out.ends_with([' ', '\n']) {
+                newline |= out.pop() == Some('\n');
+            }
+            out.push(if newline { '\n' } else { ' ' });
+            rest = &rest[run_len..];
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    out.trim().to_string()
+}
+
+fn flush_space(out: &mut String, pending_space: &mut bool) {
+    if *pending_space && !out.is_empty() && !out.ends_with(['{', '}', ';', ',', '>', ':']) {
+        out.push(' ');
+    }
+    *pending_space = false;
+}
+
+fn skip_whitespace(chars: &mut std::iter::Peekable<std::str::Chars>) {
+    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+/// Whether the output currently ends inside a selector rather than a declaration block.
+fn in_selector(out: &str) -> bool {
+    let last_open = out.rfind('{');
+    let last_boundary = out.rfind(['}', ';']);
+    match (last_open, last_boundary) {
+        (Some(open), Some(boundary)) => boundary > open && out[boundary..].starts_with('}'),
+        (Some(_), None) => false,
+        (None, _) => true,
+    }
+}
+
+/// Copy a quoted string up to and including its closing quote.
+fn copy_string(chars: &mut std::iter::Peekable<std::str::Chars>, out: &mut String, quote: char) {
+    let mut escaped = false;
+    for c in chars.by_ref() {
+        out.push(c);
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            break;
+        }
+    }
+}
+
+/// Copy a regex literal body up to and including its closing slash.
+fn copy_regex(chars: &mut std::iter::Peekable<std::str::Chars>, out: &mut String) {
+    let mut escaped = false;
+    let mut in_class = false;
+    for c in chars.by_ref() {
+        out.push(c);
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == '[' {
+            in_class = true;
+        } else if c == ']' {
+            in_class = false;
+        } else if (c == '/' && !in_class) || c == '\n' {
+            break;
+        }
+    }
+}
+
+fn starts_with_tag(text: &str, element: &str) -> bool {
+    let Some(rest) = text.get(1..1 + element.len()) else {
+        return false;
+    };
+    rest.eq_ignore_ascii_case(element)
+        && text[1 + element.len()..].starts_with(|c: char| c == '>' || c.is_whitespace())
+}
+
+/// Find an ASCII `needle` regardless of case, without copying the haystack.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    let needle = needle.as_bytes();
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minify_css() {
+        let css = "/* Theme */\n:root {\n    --bg: #fff;\n}\n\n.nav a:hover, .nav > li {\n    color: red;\n    content: \"a  b\";\n}\n";
+        assert_eq!(
+            minify_css(css),
+            ":root{--bg:#fff}.nav a:hover,.nav>li{color:red;content:\"a  b\"}"
+        );
+    }
+
+    #[test]
+    fn test_minify_css_keeps_descendant_pseudo_class() {
+        assert_eq!(
+            minify_css("div :hover { color: red; }"),
+            "div :hover{color:red}"
+        );
+    }
+
+    #[test]
+    fn test_minify_js() {
+        let js = "// setup\nconst url = \"http://x.org\"; /* inline */\n\n    if (a) {\n        b = /\\/\\//g;\n    }\n";
+        assert_eq!(
+            minify_js(js),
+            "const url = \"http://x.org\";\nif (a) {\nb = /\\/\\//g;\n}"
+        );
+    }
+
+    #[test]
+    fn test_minify_js_keeps_division() {
+        assert_eq!(minify_js("x = a / b / c; // half"), "x = a / b / c;");
+    }
+
+    #[test]
+    fn test_minify_html() {
+        let html = "<div>\n    <!-- note -->\n    <p>Hello   <b>big</b>\n    world</p>\n<pre>  keep\n   this</pre>\n</div>";
+        assert_eq!(
+            minify_html(html),
+            "<div>\n<p>Hello <b>big</b>\nworld</p>\n<pre>  keep\n   this</pre>\n</div>"
+        );
+    }
+
+    #[test]
+    fn test_minify_html_keeps_script() {
+        let html = "<script>\n  // comment <!-- not html\n  run();\n</script>";
+        assert_eq!(minify_html(html), html);
+    }
+
+    #[test]
+    fn test_minify_html_raw_elements_close_ignoring_case() {
+        let html = "<PRE>a   b</Pre>   <p>é   x</p>";
+        assert_eq!(minify_html(html), "<PRE>a   b</Pre> <p>é x</p>");
+        assert_eq!(find_ignore_case("xé</STYLE>", "</style"), Some(3));
+        assert_eq!(find_ignore_case("</sty", "</style"), None);
+    }
+}
@@ -1,9 +1,27 @@
+mod compress;
+mod epub;
+mod header;
 mod html;
+mod markdown;
+pub(crate) mod markup;
+mod minify;
+mod pages;
+mod pdf;
+mod search;
+mod template;
 
-pub use html::HtmlRenderer;
+pub use epub::EpubRenderer;
+pub use header::{HeaderLink, HeaderLogo, PageHeader};
+pub use html::{HtmlRenderer, PageLayout, PRINT_STYLES};
+pub use markdown::MarkdownRenderer;
+pub use minify::{minify_css, minify_html, minify_js};
+pub use pages::PagesRenderer;
+pub use pdf::PdfRenderer;
+pub use search::{SEARCH_SCRIPT, SEARCH_STYLES};
+pub use template::{escape_html, render_template, Context, Template, TemplateError, Value};
 
 use crate::assets::Assets;
-use crate::parser::Section;
+use crate::parser::{DocumentStructure, Section};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -13,6 +31,24 @@ pub enum RenderError {
 
     #[error("Invalid document structure: {0}")]
     StructureError(String),
+
+    #[error("Template error at line {line}: {message}")]
+    TemplateError { line: usize, message: String },
+
+    #[error("Compression failed: {0}")]
+    CompressionError(#[from] std::io::Error),
+
+    #[error("Search index failed: {0}")]
+    SearchIndexError(#[from] serde_json::Error),
+}
+
+impl From<TemplateError> for RenderError {
+    fn from(e: TemplateError) -> Self {
+        RenderError::TemplateError {
+            line: e.line,
+            message: e.message,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -22,6 +58,64 @@ pub struct ParsedDocument {
     pub title: String,
 }
 
+/// Everything a renderer needs besides the panels.
+#[derive(Debug, Clone, Copy)]
+pub struct DocumentMeta<'a> {
+    pub title: &'a str,
+    pub header: &'a PageHeader,
+    /// Theme template, styles and script; formats without themes ignore them
+    pub assets: &'a Assets,
+}
+
+/// One file of a multi-file output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputFile {
+    /// Path relative to the output directory, with `/` separators
+    pub path: String,
+    pub contents: Vec<u8>,
+}
+
+/// What a renderer produces.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderOutput {
+    /// A single file, written to the output path
+    File(Vec<u8>),
+    /// Several files, written to an output directory
+    Files(Vec<OutputFile>),
+}
+
+impl RenderOutput {
+    /// Total size of all files in bytes.
+    pub fn len(&self) -> usize {
+        match self {
+            RenderOutput::File(contents) => contents.len(),
+            RenderOutput::Files(files) => files.iter().map(|f| f.contents.len()).sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The contents of a single-file output.
+    pub fn into_file(self) -> Option<Vec<u8>> {
+        match self {
+            RenderOutput::File(contents) => Some(contents),
+            RenderOutput::Files(_) => None,
+        }
+    }
+}
+
+/// An output format.
+///
+/// Renderers receive the parsed document structure after all Markdown
+/// transforms, so a new format needs no changes to the pipeline.
 pub trait Renderer {
-    fn render(&self, document: &ParsedDocument, logo_path: &str, assets: &Assets) -> Result<Vec<u8>, RenderError>;
+    /// Render all panels; content the format cannot show is reported in `warnings`.
+    fn render(
+        &self,
+        doc: &DocumentStructure,
+        meta: &DocumentMeta,
+        warnings: &mut Vec<String>,
+    ) -> Result<RenderOutput, RenderError>;
 }
@@ -0,0 +1,214 @@
+//! Multi-page HTML output: an index page with the table of contents and one
+//! page per panel, linked with plain navigation instead of a script.
+
+use super::html::{base_context, panel_div, render_panel_contents, table_of_contents};
+use super::template::{Template, Value};
+use super::{DocumentMeta, OutputFile, RenderError, RenderOutput, Renderer};
+use crate::assets::Assets;
+use crate::parser::{DocumentStructure, SectionStyle};
+use std::collections::BTreeMap;
+
+/// Styles added after the theme styles on every page.
+const PAGES_STYLES: &str = include_str!("pages.css");
+
+const INDEX_FILE: &str = "index.html";
+
+/// Renders the panels as separate pages using the theme template.
+#[derive(Debug, Default)]
+pub struct PagesRenderer;
+
+impl PagesRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Navigation links: the contents, then one per section; dropdowns and
+    /// groups lead to their first item.
+    fn generate_nav_links(&self, doc: &DocumentStructure, current: Option<&str>) -> String {
+        let link = |href: String, title: &str, active: bool| {
+            let class = if active { " class=\"active\"" } else { "" };
+            format!("<a href=\"{}\"{}>{}</a>\n        ", href, class, title)
+        };
+
+        let mut result = link(INDEX_FILE.to_string(), "Contents", current.is_none());
+        for node in &doc.navigation {
+            if node.style == SectionStyle::Button {
+                result.push_str(&link(
+                    page_file(&node.id),
+                    &node.title,
+                    current == Some(node.id.as_str()),
+                ));
+            } else if let Some(first) = node.items.first() {
+                let active = node
+                    .items
+                    .iter()
+                    .any(|item| current == Some(item.id.as_str()));
+                result.push_str(&link(page_file(&first.id), &node.title, active));
+            }
+        }
+
+        result
+    }
+
+    /// Links to the previous and next panel, in document order.
+    fn generate_pager(&self, doc: &DocumentStructure, index: usize) -> String {
+        let mut result = String::from("<nav class=\"pager\">\n");
+        if let Some(prev) = index.checked_sub(1).and_then(|i| doc.panels.get(i)) {
+            result.push_str(&format!(
+                "    <a href=\"{}\" rel=\"prev\">&larr; {}</a>\n",
+                page_file(&prev.id),
+                prev.title
+            ));
+        }
+        if let Some(next) = doc.panels.get(index + 1) {
+            result.push_str(&format!(
+                "    <a href=\"{}\" rel=\"next\">{} &rarr;</a>\n",
+                page_file(&next.id),
+                next.title
+            ));
+        }
+        result.push_str("</nav>\n");
+        result
+    }
+
+    fn render_file(
+        &self,
+        template: &Template,
+        meta: &DocumentMeta,
+        assets: &Assets,
+        nav: String,
+        content: String,
+        panels: Vec<Value>,
+    ) -> Result<Vec<u8>, RenderError> {
+        let mut context = base_context(meta.title, meta.header, assets);
+        context.insert("nav_buttons".to_string(), Value::Html(nav));
+        context.insert("content".to_string(), Value::Html(content));
+        context.insert("script".to_string(), Value::Html(String::new()));
+        context.insert("print".to_string(), Value::Bool(false));
+        context.insert("panels".to_string(), Value::List(panels));
+
+        let html = template.render(&context, &assets.partials)?;
+        Ok(html.into_bytes())
+    }
+}
+
+impl Renderer for PagesRenderer {
+    fn render(
+        &self,
+        doc: &DocumentStructure,
+        meta: &DocumentMeta,
+        _warnings: &mut Vec<String>,
+    ) -> Result<RenderOutput, RenderError> {
+        // The pages work without the theme script, which expects tab buttons
+        let assets = Assets {
+            styles: format!("{}\n{}", meta.assets.styles, PAGES_STYLES),
+            script: String::new(),
+            ..meta.assets.clone()
+        };
+        let template = Template::parse(&assets.template)?;
+
+        let toc = format!(
+            "<nav class=\"toc\">\n    <h2>Contents</h2>\n{}</nav>\n",
+            table_of_contents(doc, page_file, |_| None, |_, _| String::new())
+        );
+        let index = self.render_file(
+            &template,
+            meta,
+            &assets,
+            self.generate_nav_links(doc, None),
+            toc,
+            Vec::new(),
+        )?;
+        let mut files = vec![OutputFile {
+            path: INDEX_FILE.to_string(),
+            contents: index,
+        }];
+
+        for (i, (id, title, html)) in render_panel_contents(doc, page_file)
+            .into_iter()
+            .enumerate()
+        {
+            let content = panel_div(&id, &title, &html, false) + &self.generate_pager(doc, i);
+            let nav = self.generate_nav_links(doc, Some(&id));
+            let path = page_file(&id);
+
+            let mut panel_value = BTreeMap::new();
+            panel_value.insert("id".to_string(), Value::Str(id));
+            panel_value.insert("title".to_string(), Value::Str(title));
+            panel_value.insert("content".to_string(), Value::Html(html));
+            panel_value.insert("first".to_string(), Value::Bool(true));
+
+            let contents = self.render_file(
+                &template,
+                meta,
+                &assets,
+                nav,
+                content,
+                vec![Value::Map(panel_value)],
+            )?;
+            files.push(OutputFile { path, contents });
+        }
+
+        Ok(RenderOutput::Files(files))
+    }
+}
+
+/// File name of a panel's page; a panel called "index" does not replace the index.
+fn page_file(id: &str) -> String {
+    if INDEX_FILE.strip_suffix(".html") == Some(id) {
+        format!("{}-page.html", id)
+    } else {
+        format!("{}.html", id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_document_structure;
+    use crate::renderer::PageHeader;
+
+    #[test]
+    fn test_render_pages() {
+        let markdown =
+            "## About\n\nHello\n\n## Projects\n\n### Vellum\n\nGenerator\n\n### Index\n\nList\n";
+        let doc = parse_document_structure(markdown, Some("Projects"));
+        let assets = Assets {
+            template:
+                "<nav>{{nav_buttons}}</nav><main>{{content}}</main><script>{{script}}</script>"
+                    .to_string(),
+            script: "showPanel();".to_string(),
+            ..Default::default()
+        };
+        let header = PageHeader::with_logo("");
+        let meta = DocumentMeta {
+            title: "Portfolio",
+            header: &header,
+            assets: &assets,
+        };
+
+        let output = PagesRenderer::new()
+            .render(&doc, &meta, &mut Vec::new())
+            .unwrap();
+        let RenderOutput::Files(files) = output else {
+            panic!("expected several files");
+        };
+        let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            ["index.html", "about.html", "vellum.html", "index-page.html"]
+        );
+
+        let index = String::from_utf8(files[0].contents.clone()).unwrap();
+        assert!(index.contains("<a href=\"index.html\" class=\"active\">Contents</a>"));
+        assert!(index.contains("<li><span>Projects</span>"));
+        assert!(index.contains("<li><a href=\"vellum.html\">Vellum</a></li>"));
+        assert!(index.contains("<script></script>"));
+
+        let vellum = String::from_utf8(files[2].contents.clone()).unwrap();
+        assert!(vellum.contains("<a href=\"vellum.html\" class=\"active\">Projects</a>"));
+        assert!(vellum.contains("<div class=\"panel\" id=\"panel-vellum\">"));
+        assert!(vellum.contains("<a href=\"about.html\" rel=\"prev\">&larr; About</a>"));
+        assert!(vellum.contains("<a href=\"index-page.html\" rel=\"next\">Index &rarr;</a>"));
+    }
+}
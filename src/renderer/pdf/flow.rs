@@ -0,0 +1,648 @@
+//! Lays out rendered panel HTML on PDF pages.
+//!
+//! Handles the elements Markdown and Vellum's own transforms produce:
+//! headings, paragraphs, lists, code, quotes, tables (including skill
+//! matrices with their level colours), coloured tags and images.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use super::layout::{wrap, Align, Layout, Rgb, Run, Style, ACCENT, BORDER, CONTENT_WIDTH, MUTED};
+use super::writer::PdfImage;
+use crate::renderer::markup::{tokenize, Token};
+
+const LIST_INDENT: f32 = 18.0;
+const QUOTE_INDENT: f32 = 14.0;
+const CELL_PADDING: f32 = 5.0;
+const LINK: Rgb = Rgb::hex(0x2563eb);
+const HIGHLIGHT: Rgb = Rgb::hex(0x2dd4bf);
+const CODE_BACKGROUND: Rgb = Rgb::hex(0xf0f0f0);
+
+/// Background colour of a skill level cell, as in the default theme.
+fn level_color(level: &str) -> Option<Rgb> {
+    let color = match level.parse::<u32>().ok()? {
+        0..=3 => 0xf8d7da,
+        4 | 5 => 0xfff3cd,
+        6 => 0xffeeba,
+        7 => 0xd4edda,
+        8 => 0xc3e6cb,
+        _ => 0xb1dfbb,
+    };
+    Some(Rgb::hex(color))
+}
+
+/// Background and text colour of a `color-tag-<name>` span.
+fn tag_colors(name: &str) -> Option<(Rgb, Rgb)> {
+    let white = Rgb::hex(0xffffff);
+    let colors = match name {
+        "green" => (Rgb::hex(0x22c55e), white),
+        "grey" => (Rgb::hex(0x6b7280), white),
+        "red" => (Rgb::hex(0xef4444), white),
+        "blue" => (Rgb::hex(0x3b82f6), white),
+        "yellow" => (Rgb::hex(0xeab308), Rgb::hex(0x000000)),
+        "orange" => (Rgb::hex(0xf97316), white),
+        "purple" => (Rgb::hex(0xa855f7), white),
+        _ => return None,
+    };
+    Some(colors)
+}
+
+#[derive(Debug)]
+struct Cell {
+    runs: Vec<Run>,
+    background: Option<Rgb>,
+    colspan: usize,
+    align: Align,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    rows: Vec<Vec<Cell>>,
+    cell: Option<Cell>,
+    category_row: bool,
+}
+
+#[derive(Debug)]
+struct List {
+    ordered: bool,
+    next: u32,
+}
+
+/// Walks the tokens of one panel, feeding the layout.
+pub struct Flow<'a> {
+    layout: &'a mut Layout,
+    warnings: &'a mut Vec<String>,
+    /// Open inline elements and the style inside them
+    styles: Vec<(String, Style)>,
+    runs: Vec<Run>,
+    marker: Option<Run>,
+    lists: Vec<List>,
+    indent: f32,
+    pre: Option<String>,
+    table: Option<Table>,
+    /// Nesting depth inside a skipped element such as an inline SVG chart
+    skip_depth: usize,
+    skipped_charts: usize,
+}
+
+impl<'a> Flow<'a> {
+    pub fn new(layout: &'a mut Layout, warnings: &'a mut Vec<String>) -> Self {
+        Self {
+            layout,
+            warnings,
+            styles: Vec::new(),
+            runs: Vec::new(),
+            marker: None,
+            lists: Vec::new(),
+            indent: 0.0,
+            pre: None,
+            table: None,
+            skip_depth: 0,
+            skipped_charts: 0,
+        }
+    }
+
+    /// Lay out `html` and return how many inline charts were left out.
+    pub fn render(mut self, html: &str) -> usize {
+        for token in tokenize(html) {
+            self.token(&token);
+        }
+        self.flush(0.0);
+        self.skipped_charts
+    }
+
+    fn style(&self) -> Style {
+        self.styles
+            .last()
+            .map(|(_, style)| *style)
+            .unwrap_or_default()
+    }
+
+    fn token(&mut self, token: &Token) {
+        if self.skip_depth > 0 {
+            match token {
+                Token::Open { name, .. } if name == "svg" => self.skip_depth += 1,
+                Token::Close(name) if name == "svg" => self.skip_depth -= 1,
+                _ => {}
+            }
+            return;
+        }
+
+        match token {
+            Token::Text(text) => self.text(text),
+            Token::Open { name, .. } => self.open(name, token),
+            Token::Close(name) => self.close(name),
+        }
+    }
+
+    fn text(&mut self, text: &str) {
+        if let Some(ref mut pre) = self.pre {
+            pre.push_str(text);
+            return;
+        }
+        let mut collapsed = String::with_capacity(text.len());
+        for c in text.chars() {
+            let c = if c.is_whitespace() && c != '\u{a0}' {
+                ' '
+            } else {
+                c
+            };
+            if c == ' '
+                && (collapsed.ends_with(' ') || (collapsed.is_empty() && self.ends_with_space()))
+            {
+                continue;
+            }
+            collapsed.push(c);
+        }
+        if !collapsed.is_empty() {
+            self.push_run(collapsed);
+        }
+    }
+
+    fn ends_with_space(&self) -> bool {
+        self.target_runs()
+            .last()
+            .is_none_or(|run| run.text.ends_with(' ') || run.text.ends_with('\n'))
+    }
+
+    fn target_runs(&self) -> &Vec<Run> {
+        match self.table.as_ref().and_then(|t| t.cell.as_ref()) {
+            Some(cell) => &cell.runs,
+            None => &self.runs,
+        }
+    }
+
+    fn push_run(&mut self, text: String) {
+        let style = self.style();
+        let runs = match self.table.as_mut().and_then(|t| t.cell.as_mut()) {
+            Some(cell) => &mut cell.runs,
+            None => &mut self.runs,
+        };
+        match runs.last_mut() {
+            Some(last) if last.style == style => last.text.push_str(&text),
+            _ => runs.push(Run { text, style }),
+        }
+    }
+
+    fn push_style(&mut self, name: &str, change: impl FnOnce(&mut Style)) {
+        let mut style = self.style();
+        change(&mut style);
+        self.styles.push((name.to_string(), style));
+    }
+
+    fn open(&mut self, name: &str, token: &Token) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush(0.0);
+                let level = name[1..].parse::<usize>().unwrap_or(6);
+                let size = [18.0, 15.0, 13.0, 11.5, 10.5, 10.0][level - 1];
+                self.layout.gap(size * 0.8);
+                self.layout.keep_space(size * 4.0);
+                self.push_style(name, |style| {
+                    style.bold = true;
+                    style.size = size;
+                });
+            }
+            "p" | "div" | "dl" | "dt" | "dd" => self.flush(0.0),
+            "ul" | "ol" => {
+                self.flush(0.0);
+                let start = token
+                    .attr("start")
+                    .and_then(|s| s.parse().ok())
+                    .unwrap_or(1);
+                self.lists.push(List {
+                    ordered: name == "ol",
+                    next: start,
+                });
+                self.indent += LIST_INDENT;
+            }
+            "li" => {
+                self.flush(0.0);
+                let depth = self.lists.len();
+                if let Some(list) = self.lists.last_mut() {
+                    let text = if list.ordered {
+                        format!("{}.", list.next)
+                    } else if depth > 1 {
+                        "–".to_string()
+                    } else {
+                        "•".to_string()
+                    };
+                    list.next += 1;
+                    self.marker = Some(Run {
+                        text,
+                        style: Style::default(),
+                    });
+                }
+            }
+            "input" if token.attr("type") == Some("checkbox") => {
+                let mark = if token.attr("checked").is_some() {
+                    "[x] "
+                } else {
+                    "[ ] "
+                };
+                self.push_run(mark.to_string());
+            }
+            "pre" => {
+                self.flush(0.0);
+                self.pre = Some(String::new());
+            }
+            "blockquote" => {
+                self.flush(0.0);
+                self.indent += QUOTE_INDENT;
+                self.push_style(name, |style| {
+                    style.italic = true;
+                    style.color = MUTED;
+                });
+            }
+            "br" => self.push_run("\n".to_string()),
+            "hr" => {
+                self.flush(0.0);
+                self.layout.gap(6.0);
+                self.layout.rule(self.indent, BORDER);
+                self.layout.gap(6.0);
+            }
+            "strong" | "b" => self.push_style(name, |style| style.bold = true),
+            "em" | "i" => self.push_style(name, |style| style.italic = true),
+            "del" | "s" => self.push_style(name, |style| style.color = MUTED),
+            "a" => self.push_style(name, |style| style.color = LINK),
+            "code" if self.pre.is_none() => self.push_style(name, |style| {
+                style.mono = true;
+                style.size *= 0.9;
+                style.background = Some(CODE_BACKGROUND);
+            }),
+            "span" => {
+                let tag = token
+                    .attr("class")
+                    .unwrap_or_default()
+                    .split_whitespace()
+                    .find_map(|class| class.strip_prefix("color-tag-"))
+                    .and_then(tag_colors);
+                let marker = token.has_class("achievement-marker") || token.has_class("wip-marker");
+                if marker {
+                    self.push_run(" ".to_string());
+                }
+                self.push_style(name, |style| {
+                    if let Some((background, color)) = tag {
+                        style.background = Some(background);
+                        style.color = color;
+                        style.size *= 0.85;
+                    } else if marker {
+                        style.background = Some(HIGHLIGHT);
+                        style.color = Rgb::hex(0xffffff);
+                        style.size *= 0.8;
+                    }
+                });
+            }
+            "table" => {
+                self.flush(0.0);
+                self.table = Some(Table::default());
+            }
+            "tr" => {
+                if let Some(ref mut table) = self.table {
+                    table.rows.push(Vec::new());
+                    table.category_row = token.has_class("category-row");
+                }
+            }
+            "td" | "th" => self.open_cell(name, token),
+            "img" => self.image(token),
+            "svg" => {
+                self.skip_depth = 1;
+                self.skipped_charts += 1;
+            }
+            _ => self.push_style(name, |_| {}),
+        }
+    }
+
+    fn close(&mut self, name: &str) {
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush(0.0);
+                self.pop_style(name);
+                self.layout.gap(4.0);
+            }
+            "p" => self.flush(6.0),
+            "div" | "dl" | "dt" | "dd" => self.flush(0.0),
+            "li" => self.flush(2.0),
+            "ul" | "ol" => {
+                self.flush(0.0);
+                self.lists.pop();
+                self.indent -= LIST_INDENT;
+                if self.lists.is_empty() {
+                    self.layout.gap(6.0);
+                }
+            }
+            "pre" => self.code_block(),
+            "blockquote" => {
+                self.flush(6.0);
+                self.pop_style(name);
+                self.indent -= QUOTE_INDENT;
+            }
+            "td" | "th" => self.close_cell(),
+            "table" => self.draw_table(),
+            _ => self.pop_style(name),
+        }
+    }
+
+    /// Close the innermost open element called `name`, and anything opened inside it.
+    fn pop_style(&mut self, name: &str) {
+        if let Some(pos) = self.styles.iter().rposition(|(open, _)| open == name) {
+            self.styles.truncate(pos);
+        }
+    }
+
+    /// Draw the collected paragraph text and request `gap` points after it.
+    fn flush(&mut self, gap: f32) {
+        let runs = std::mem::take(&mut self.runs);
+        let marker = self.marker.take();
+        if runs.iter().all(|run| run.text.trim().is_empty()) {
+            self.marker = marker;
+            return;
+        }
+        let width = CONTENT_WIDTH - self.indent;
+        let lines = wrap(&runs, width);
+        self.layout
+            .text(&lines, self.indent, width, Align::Left, marker.as_ref());
+        self.layout.gap(gap);
+    }
+
+    fn code_block(&mut self) {
+        let Some(code) = self.pre.take() else {
+            return;
+        };
+        let style = Style {
+            mono: true,
+            size: 8.5,
+            ..Style::default()
+        };
+        let width = CONTENT_WIDTH - self.indent;
+        let padding = 6.0;
+        let runs: Vec<Run> = code
+            .trim_end_matches('\n')
+            .split('\n')
+            .map(|line| Run {
+                // Spaces would collapse in wrapping; no-break spaces keep the indentation
+                text: format!("{}\n", line.replace(' ', "\u{a0}")),
+                style,
+            })
+            .collect();
+        let lines = wrap(&runs, width - 2.0 * padding);
+
+        self.layout.gap(4.0);
+        for line in &lines {
+            let top = self.layout.take(line.height());
+            let x = super::layout::MARGIN + self.indent;
+            self.layout
+                .fill_rect(x, top - line.height(), width, line.height(), ACCENT);
+            self.layout
+                .draw_line(line, x + padding, top - line.height() + style.size * 0.45);
+        }
+        self.layout.gap(8.0);
+    }
+
+    fn open_cell(&mut self, name: &str, token: &Token) {
+        let Some(ref mut table) = self.table else {
+            return;
+        };
+        let style_attr = token.attr("style").unwrap_or_default();
+        let align = if style_attr.contains("center") {
+            Align::Center
+        } else if style_attr.contains("right") {
+            Align::Right
+        } else {
+            Align::Left
+        };
+        let level = token
+            .attr("class")
+            .unwrap_or_default()
+            .split_whitespace()
+            .find_map(|class| class.strip_prefix("level-"))
+            .and_then(level_color);
+        let header = name == "th";
+        let category = table.category_row;
+        table.cell = Some(Cell {
+            runs: Vec::new(),
+            background: level.or(if header || category {
+                Some(ACCENT)
+            } else {
+                None
+            }),
+            colspan: token
+                .attr("colspan")
+                .and_then(|c| c.parse().ok())
+                .unwrap_or(1)
+                .max(1),
+            align: if category { Align::Center } else { align },
+        });
+        if header || category {
+            self.push_style(name, |style| style.bold = true);
+        } else {
+            self.push_style(name, |_| {});
+        }
+    }
+
+    fn close_cell(&mut self) {
+        self.pop_style("td");
+        self.pop_style("th");
+        if let Some(ref mut table) = self.table {
+            if let Some(cell) = table.cell.take() {
+                match table.rows.last_mut() {
+                    Some(row) => row.push(cell),
+                    None => table.rows.push(vec![cell]),
+                }
+            }
+        }
+    }
+
+    fn draw_table(&mut self) {
+        let Some(table) = self.table.take() else {
+            return;
+        };
+        let columns = table
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.colspan).sum::<usize>())
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return;
+        }
+
+        // Share the width in proportion to each column's widest single-cell content
+        let mut natural = vec![30.0_f32; columns];
+        for row in &table.rows {
+            let mut col = 0;
+            for cell in row {
+                if cell.colspan == 1 && col < columns {
+                    let width: f32 = wrap(&cell.runs, f32::INFINITY)
+                        .iter()
+                        .map(|l| l.width)
+                        .fold(0.0, f32::max);
+                    natural[col] = natural[col].max(width + 2.0 * CELL_PADDING);
+                }
+                col += cell.colspan;
+            }
+        }
+        let available = CONTENT_WIDTH - self.indent;
+        let total: f32 = natural.iter().sum();
+        let widths: Vec<f32> = natural.iter().map(|w| w * available / total).collect();
+
+        self.layout.gap(6.0);
+        for row in &table.rows {
+            let mut cells = Vec::with_capacity(row.len());
+            let mut col = 0;
+            for cell in row {
+                let end = (col + cell.colspan).min(columns);
+                let width: f32 = widths[col.min(columns)..end].iter().sum();
+                let lines = wrap(&cell.runs, width - 2.0 * CELL_PADDING);
+                cells.push((cell, col, width, lines));
+                col = end;
+            }
+            let height = cells
+                .iter()
+                .map(|(_, _, _, lines)| lines.iter().map(|l| l.height()).sum::<f32>())
+                .fold(0.0, f32::max)
+                + 2.0 * CELL_PADDING;
+
+            let top = self.layout.take(height);
+            for (cell, col, width, lines) in cells {
+                let x = super::layout::MARGIN + self.indent + widths[..col].iter().sum::<f32>();
+                if let Some(background) = cell.background {
+                    self.layout
+                        .fill_rect(x, top - height, width, height, background);
+                }
+                self.layout
+                    .stroke_rect(x, top - height, width, height, BORDER);
+                let mut y = top - CELL_PADDING;
+                for line in &lines {
+                    let inner = width - 2.0 * CELL_PADDING;
+                    let offset = match cell.align {
+                        Align::Left => 0.0,
+                        Align::Center => ((inner - line.width) / 2.0).max(0.0),
+                        Align::Right => (inner - line.width).max(0.0),
+                    };
+                    let size = line.height() / 1.4;
+                    self.layout
+                        .draw_line(line, x + CELL_PADDING + offset, y - size * 1.1);
+                    y -= line.height();
+                }
+            }
+        }
+        self.layout.gap(8.0);
+    }
+
+    fn image(&mut self, token: &Token) {
+        let alt = token.attr("alt").unwrap_or("image").to_string();
+        let decoded = token.attr("src").and_then(decode_raster_image);
+        let Some(image) = decoded else {
+            self.warnings
+                .push(format!("Image '{}' is not included in the PDF", alt));
+            self.push_style("img", |style| {
+                style.italic = true;
+                style.color = MUTED;
+            });
+            self.push_run(format!("[{}]", alt));
+            self.pop_style("img");
+            return;
+        };
+
+        self.flush(0.0);
+        let available = CONTENT_WIDTH - self.indent;
+        // CSS pixels are 0.75 points; large images shrink to fit
+        let mut width = image.width as f32 * 0.75;
+        let mut height = image.height as f32 * 0.75;
+        let scale = (available / width).min(400.0 / height).min(1.0);
+        width *= scale;
+        height *= scale;
+        self.layout.gap(4.0);
+        self.layout.image(image, self.indent, width, height);
+        self.layout.gap(6.0);
+    }
+}
+
+/// Decode a PNG, JPEG, GIF or WebP data URI to RGB samples on white.
+pub fn decode_raster_image(src: &str) -> Option<PdfImage> {
+    let (header, data) = src.strip_prefix("data:")?.split_once(',')?;
+    if !header.ends_with(";base64") || header.starts_with("image/svg") {
+        return None;
+    }
+    let bytes = STANDARD.decode(data).ok()?;
+    let image = image::load_from_memory(&bytes).ok()?.to_rgba8();
+
+    let mut rgb = Vec::with_capacity(image.width() as usize * image.height() as usize * 3);
+    for pixel in image.pixels() {
+        let [r, g, b, a] = pixel.0;
+        let alpha = a as u32;
+        for channel in [r, g, b] {
+            rgb.push(((channel as u32 * alpha + 255 * (255 - alpha)) / 255) as u8);
+        }
+    }
+    Some(PdfImage {
+        width: image.width(),
+        height: image.height(),
+        rgb,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(html: &str) -> (String, Vec<String>) {
+        let mut layout = Layout::default();
+        let mut warnings = Vec::new();
+        Flow::new(&mut layout, &mut warnings).render(html);
+        (layout.pages.join("\n"), warnings)
+    }
+
+    #[test]
+    fn test_paragraph_and_inline_styles() {
+        let (ops, _) = render("<p>Plain <strong>bold</strong> <em>it</em> <code>x</code></p>");
+        assert!(ops.contains("/F1 10 Tf"));
+        assert!(ops.contains("/F2 10 Tf") && ops.contains("(bold) Tj"));
+        assert!(ops.contains("/F3 10 Tf") && ops.contains("(it) Tj"));
+        assert!(ops.contains("/F5 9 Tf"));
+    }
+
+    #[test]
+    fn test_skill_matrix_colours() {
+        let html = "<table class=\"skill-matrix\">\n<thead><tr><th>Skill</th><th>Level</th></tr></thead>\n<tbody>\n\
+                    <tr class=\"category-row\"><td colspan=\"2\"><strong>Languages</strong></td></tr>\n\
+                    <tr><td>Rust</td><td class=\"level-9\" style=\"text-align: center\">9</td></tr>\n</tbody>\n</table>";
+        let (ops, _) = render(html);
+        // Pastel green of .level-9 (#b1dfbb)
+        assert!(ops.contains("0.69 0.87 0.73 rg"));
+        assert!(ops.contains("(Languages) Tj"));
+        assert!(ops.contains("(Rust) Tj"));
+    }
+
+    #[test]
+    fn test_colored_tag_background() {
+        let (ops, _) = render("<p><span class=\"color-tag color-tag-red\">KW03-FAIL</span></p>");
+        assert!(ops.contains("0.94 0.27 0.27 rg"));
+        assert!(ops.contains("(KW03-FAIL) Tj"));
+    }
+
+    #[test]
+    fn test_lists_and_code() {
+        let (ops, _) = render("<ul>\n<li>One</li>\n<li>Two</li>\n</ul>\n<pre><code>fn main() {\n    x\n}</code></pre>");
+        assert_eq!(ops.matches("(\\225) Tj").count(), 2);
+        assert!(ops.contains("/F5 8.5 Tf"));
+        assert!(ops.contains("(\\240\\240\\240\\240x) Tj"));
+    }
+
+    #[test]
+    fn test_svg_skipped_and_image_placeholder() {
+        let mut layout = Layout::default();
+        let mut warnings = Vec::new();
+        let skipped = Flow::new(&mut layout, &mut warnings).render(
+            "<svg><text>Rust</text><svg></svg></svg><p><img src=\"photo.png\" alt=\"Team\"></p>",
+        );
+        let ops = layout.pages.join("\n");
+        assert_eq!(skipped, 1);
+        assert!(!ops.contains("(Rust)"));
+        assert!(ops.contains("([Team]) Tj"));
+        assert_eq!(
+            warnings,
+            vec!["Image 'Team' is not included in the PDF".to_string()]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+//! The standard PDF fonts used for text, with their metrics.
+//!
+//! The base-14 fonts need no embedding, so every PDF viewer can show them.
+//! Text is encoded as WinAnsi; other characters are replaced.
+
+/// A base-14 font face.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Font {
+    Regular,
+    Bold,
+    Italic,
+    BoldItalic,
+    Mono,
+}
+
+impl Font {
+    pub const ALL: [Font; 5] = [
+        Font::Regular,
+        Font::Bold,
+        Font::Italic,
+        Font::BoldItalic,
+        Font::Mono,
+    ];
+
+    pub fn select(bold: bool, italic: bool, mono: bool) -> Self {
+        match (mono, bold, italic) {
+            (true, _, _) => Font::Mono,
+            (false, true, true) => Font::BoldItalic,
+            (false, true, false) => Font::Bold,
+            (false, false, true) => Font::Italic,
+            (false, false, false) => Font::Regular,
+        }
+    }
+
+    /// Resource name in the page resources.
+    pub fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Italic => "F3",
+            Font::BoldItalic => "F4",
+            Font::Mono => "F5",
+        }
+    }
+
+    pub fn base_font(self) -> &'static str {
+        match self {
+            Font::Regular => "Helvetica",
+            Font::Bold => "Helvetica-Bold",
+            Font::Italic => "Helvetica-Oblique",
+            Font::BoldItalic => "Helvetica-BoldOblique",
+            Font::Mono => "Courier",
+        }
+    }
+
+    /// Width of `text` at `size` points.
+    pub fn text_width(self, text: &str, size: f32) -> f32 {
+        let units: u32 = text.chars().map(|c| self.char_width(c)).sum();
+        units as f32 * size / 1000.0
+    }
+
+    /// Advance width of a character in 1/1000 em.
+    fn char_width(self, c: char) -> u32 {
+        let table = match self {
+            Font::Mono => return 600,
+            Font::Regular | Font::Italic => &HELVETICA_WIDTHS,
+            Font::Bold | Font::BoldItalic => &HELVETICA_BOLD_WIDTHS,
+        };
+        match encode_char(c) {
+            code @ 32..=126 => table[(code - 32) as usize] as u32,
+            0x95 => 350,
+            0x96 => 556,
+            0x97 => 1000,
+            0x85 => 1000,
+            _ => 556,
+        }
+    }
+}
+
+/// Helvetica advance widths for ASCII 32..=126.
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278,
+    278, // space../
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // 0..?
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // @..O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // P.._
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // `..o
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // p..~
+];
+
+/// Helvetica-Bold advance widths for ASCII 32..=126.
+const HELVETICA_BOLD_WIDTHS: [u16; 95] = [
+    278, 333, 474, 556, 556, 889, 722, 238, 333, 333, 389, 584, 278, 333, 278,
+    278, // space../
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 333, 333, 584, 584, 584, 611, // 0..?
+    975, 722, 722, 722, 722, 667, 611, 778, 722, 278, 556, 722, 611, 833, 722, 778, // @..O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 333, 278, 333, 584, 556, // P.._
+    333, 556, 611, 556, 611, 556, 333, 611, 611, 278, 278, 556, 278, 889, 611, 611, // `..o
+    611, 611, 389, 556, 333, 611, 556, 778, 556, 556, 500, 389, 280, 389, 584, // p..~
+];
+
+/// Whether the standard fonts can show `c`; others are written as `?`.
+pub fn is_encodable(c: char) -> bool {
+    c == '?' || encode_char(c) != b'?'
+}
+
+/// WinAnsi code of a character, `?` if it has none.
+pub fn encode_char(c: char) -> u8 {
+    match c {
+        ' '..='~' => c as u8,
+        '\u{a0}'..='\u{ff}' => c as u32 as u8,
+        '€' => 0x80,
+        '‚' => 0x82,
+        '„' => 0x84,
+        '…' => 0x85,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '™' => 0x99,
+        _ => b'?',
+    }
+}
+
+/// A PDF literal string holding `text` in WinAnsi.
+pub fn pdf_string(text: &str) -> Vec<u8> {
+    let mut out = vec![b'('];
+    for c in text.chars() {
+        match encode_char(c) {
+            code @ (b'(' | b')' | b'\\') => out.extend([b'\\', code]),
+            code => out.push(code),
+        }
+    }
+    out.push(b')');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(
+            Font::Regular.text_width("Hi", 10.0),
+            (722.0 + 222.0) / 100.0
+        );
+        assert_eq!(Font::Bold.text_width("i", 10.0), 2.78);
+        assert_eq!(Font::Mono.text_width("abc", 10.0), 18.0);
+    }
+
+    #[test]
+    fn test_pdf_string_escapes_and_encodes() {
+        assert_eq!(pdf_string("a(b)\\"), b"(a\\(b\\)\\\\)".to_vec());
+        assert_eq!(pdf_string("Grüße – 🎸"), b"(Gr\xfc\xdfe \x96 ?)".to_vec());
+        assert!(is_encodable('?') && is_encodable('€'));
+        assert!(!is_encodable('ł') && !is_encodable('→'));
+    }
+}
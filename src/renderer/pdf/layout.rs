@@ -0,0 +1,555 @@
+//! Page layout: line wrapping, page breaks and drawing operators.
+
+use super::fonts::{is_encodable, pdf_string, Font};
+use super::writer::{number, PdfImage};
+use std::collections::BTreeSet;
+
+/// A4 portrait, in points.
+pub const PAGE_WIDTH: f32 = 595.28;
+pub const PAGE_HEIGHT: f32 = 841.89;
+pub const MARGIN: f32 = 56.0;
+pub const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+/// Space below the content area holding the page footer.
+const FOOTER_HEIGHT: f32 = 20.0;
+const LINE_SPACING: f32 = 1.4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rgb(pub f32, pub f32, pub f32);
+
+impl Rgb {
+    pub const fn hex(value: u32) -> Self {
+        Rgb(
+            ((value >> 16) & 0xff) as f32 / 255.0,
+            ((value >> 8) & 0xff) as f32 / 255.0,
+            (value & 0xff) as f32 / 255.0,
+        )
+    }
+
+    fn fill(self) -> String {
+        format!(
+            "{} {} {} rg",
+            number(self.0),
+            number(self.1),
+            number(self.2)
+        )
+    }
+
+    fn stroke(self) -> String {
+        format!(
+            "{} {} {} RG",
+            number(self.0),
+            number(self.1),
+            number(self.2)
+        )
+    }
+}
+
+pub const TEXT: Rgb = Rgb::hex(0x1a1a1a);
+pub const MUTED: Rgb = Rgb::hex(0x666666);
+pub const BORDER: Rgb = Rgb::hex(0xe0e0e0);
+pub const ACCENT: Rgb = Rgb::hex(0xf5f5f5);
+
+/// How a run of text looks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub mono: bool,
+    pub size: f32,
+    pub color: Rgb,
+    pub background: Option<Rgb>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            bold: false,
+            italic: false,
+            mono: false,
+            size: 10.0,
+            color: TEXT,
+            background: None,
+        }
+    }
+}
+
+impl Style {
+    pub fn font(&self) -> Font {
+        Font::select(self.bold, self.italic, self.mono)
+    }
+}
+
+/// Text in one style. A `\n` forces a line break.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub text: String,
+    pub style: Style,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Align {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// A word or space of a wrapped line.
+#[derive(Debug, Clone)]
+struct Piece {
+    text: String,
+    style: Style,
+    width: f32,
+}
+
+/// One wrapped line of text.
+#[derive(Debug, Clone, Default)]
+pub struct Line {
+    pieces: Vec<Piece>,
+    pub width: f32,
+    size: f32,
+}
+
+impl Line {
+    pub fn height(&self) -> f32 {
+        self.size * LINE_SPACING
+    }
+
+    fn push(&mut self, piece: Piece) {
+        self.width += piece.width;
+        self.size = self.size.max(piece.style.size);
+        self.pieces.push(piece);
+    }
+
+    fn trim_end(&mut self) {
+        while self.pieces.last().is_some_and(|p| p.text == " ") {
+            if let Some(piece) = self.pieces.pop() {
+                self.width -= piece.width;
+            }
+        }
+    }
+
+    fn has_words(&self) -> bool {
+        self.pieces.iter().any(|p| p.text != " ")
+    }
+}
+
+/// Break runs into lines no wider than `width`.
+pub fn wrap(runs: &[Run], width: f32) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut line = Line::default();
+
+    for run in runs {
+        let font = run.style.font();
+        for piece in split_pieces(&run.text) {
+            if piece == "\n" {
+                line.trim_end();
+                if line.size == 0.0 {
+                    line.size = run.style.size;
+                }
+                lines.push(std::mem::take(&mut line));
+                continue;
+            }
+            if piece == " " {
+                if line.has_words() {
+                    line.push(Piece {
+                        text: piece.to_string(),
+                        style: run.style,
+                        width: font.text_width(" ", run.style.size),
+                    });
+                }
+                continue;
+            }
+
+            let piece_width = font.text_width(piece, run.style.size);
+            if line.has_words() && line.width + piece_width > width {
+                line.trim_end();
+                lines.push(std::mem::take(&mut line));
+            }
+            if piece_width <= width {
+                line.push(Piece {
+                    text: piece.to_string(),
+                    style: run.style,
+                    width: piece_width,
+                });
+                continue;
+            }
+
+            // A word wider than the line, e.g. a long URL, is split anywhere
+            let mut chunk = String::new();
+            for c in piece.chars() {
+                let mut candidate = chunk.clone();
+                candidate.push(c);
+                if !chunk.is_empty()
+                    && line.width + font.text_width(&candidate, run.style.size) > width
+                {
+                    let chunk_width = font.text_width(&chunk, run.style.size);
+                    line.push(Piece {
+                        text: std::mem::take(&mut chunk),
+                        style: run.style,
+                        width: chunk_width,
+                    });
+                    lines.push(std::mem::take(&mut line));
+                    candidate = c.to_string();
+                }
+                chunk = candidate;
+            }
+            let chunk_width = font.text_width(&chunk, run.style.size);
+            line.push(Piece {
+                text: chunk,
+                style: run.style,
+                width: chunk_width,
+            });
+        }
+    }
+
+    line.trim_end();
+    if line.has_words() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Split text into words, single spaces and line breaks.
+fn split_pieces(text: &str) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if c == ' ' || c == '\n' {
+            if start < i {
+                pieces.push(&text[start..i]);
+            }
+            pieces.push(if c == ' ' { " " } else { "\n" });
+            start = i + 1;
+        }
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+    pieces
+}
+
+/// Pages being filled from top to bottom.
+#[derive(Debug)]
+pub struct Layout {
+    pub pages: Vec<String>,
+    pub images: Vec<PdfImage>,
+    /// Top of the free space on the current page
+    y: f32,
+    /// Vertical space requested before the next element
+    gap: f32,
+    /// Number of characters drawn as `?`, and which ones
+    pub replaced: usize,
+    pub replaced_chars: BTreeSet<char>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            pages: vec![String::new()],
+            images: Vec::new(),
+            y: PAGE_HEIGHT - MARGIN,
+            gap: 0.0,
+            replaced: 0,
+            replaced_chars: BTreeSet::new(),
+        }
+    }
+}
+
+impl Layout {
+    /// Current page index and vertical position, for bookmarks.
+    pub fn position(&self) -> (usize, f32) {
+        (self.pages.len() - 1, self.y)
+    }
+
+    /// Request space before the next element; consecutive gaps collapse.
+    pub fn gap(&mut self, height: f32) {
+        self.gap = self.gap.max(height);
+    }
+
+    pub fn new_page(&mut self) {
+        self.pages.push(String::new());
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.gap = 0.0;
+    }
+
+    fn at_page_top(&self) -> bool {
+        self.y >= PAGE_HEIGHT - MARGIN
+    }
+
+    /// Make room for `height` points (plus the pending gap), breaking the page if needed.
+    pub fn reserve(&mut self, height: f32) {
+        if !self.at_page_top() && self.y - self.gap - height < MARGIN + FOOTER_HEIGHT {
+            self.new_page();
+        }
+        if !self.at_page_top() {
+            self.y -= self.gap;
+        }
+        self.gap = 0.0;
+    }
+
+    /// Ensure `height` points fit on the current page without taking them.
+    pub fn keep_space(&mut self, height: f32) {
+        if !self.at_page_top() && self.y - self.gap - height < MARGIN + FOOTER_HEIGHT {
+            self.new_page();
+        }
+    }
+
+    fn op(&mut self, op: &str) {
+        let page = self.pages.last_mut().expect("layout always has a page");
+        page.push_str(op);
+        page.push('\n');
+    }
+
+    /// Draw wrapped text lines at `indent`, with an optional marker left of the first line.
+    pub fn text(
+        &mut self,
+        lines: &[Line],
+        indent: f32,
+        width: f32,
+        align: Align,
+        marker: Option<&Run>,
+    ) {
+        for (i, line) in lines.iter().enumerate() {
+            self.reserve(line.height());
+            let baseline = self.y - line.size * 1.1;
+            let x = MARGIN + indent + align_offset(align, width, line.width);
+            if i == 0 {
+                if let Some(marker) = marker {
+                    let marker_width = marker
+                        .style
+                        .font()
+                        .text_width(&marker.text, marker.style.size);
+                    self.draw_text(
+                        &marker.text,
+                        &marker.style,
+                        MARGIN + indent - marker_width - 5.0,
+                        baseline,
+                    );
+                }
+            }
+            self.draw_line(line, x, baseline);
+            self.y -= line.height();
+        }
+    }
+
+    /// Draw a line with its baseline at `baseline`.
+    pub fn draw_line(&mut self, line: &Line, x: f32, baseline: f32) {
+        let mut piece_x = x;
+        for piece in &line.pieces {
+            if let Some(background) = piece.style.background {
+                let pad = if piece.text == " " {
+                    0.0
+                } else {
+                    piece.style.size * 0.25
+                };
+                self.fill_rect(
+                    piece_x - pad,
+                    baseline - piece.style.size * 0.28,
+                    piece.width + 2.0 * pad,
+                    piece.style.size * 1.2,
+                    background,
+                );
+            }
+            piece_x += piece.width;
+        }
+
+        // Consecutive pieces in the same style share one text operator
+        let mut group_x = x;
+        let mut group = String::new();
+        let mut group_style: Option<Style> = None;
+        let mut group_width = 0.0;
+        for piece in &line.pieces {
+            if group_style.is_some_and(|style| style != piece.style) {
+                if let Some(ref style) = group_style {
+                    self.draw_text(&group, style, group_x, baseline);
+                }
+                group_x += group_width;
+                group.clear();
+                group_width = 0.0;
+            }
+            group_style = Some(piece.style);
+            group.push_str(&piece.text);
+            group_width += piece.width;
+        }
+        if let Some(ref style) = group_style {
+            if !group.trim().is_empty() {
+                self.draw_text(&group, style, group_x, baseline);
+            }
+        }
+    }
+
+    fn draw_text(&mut self, text: &str, style: &Style, x: f32, baseline: f32) {
+        for c in text.chars().filter(|&c| !is_encodable(c)) {
+            self.replaced += 1;
+            self.replaced_chars.insert(c);
+        }
+        let mut op = format!(
+            "BT /{} {} Tf {} 1 0 0 1 {} {} Tm ",
+            style.font().resource(),
+            number(style.size),
+            style.color.fill(),
+            number(x),
+            number(baseline)
+        );
+        op.push_str(&String::from_utf8_lossy(&latin_escape(text)));
+        op.push_str(" Tj ET");
+        self.op(&op);
+    }
+
+    pub fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb) {
+        let op = format!(
+            "{} {} {} {} {} re f",
+            color.fill(),
+            number(x),
+            number(y),
+            number(width),
+            number(height)
+        );
+        self.op(&op);
+    }
+
+    pub fn stroke_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb) {
+        let op = format!(
+            "0.75 w {} {} {} {} {} re S",
+            color.stroke(),
+            number(x),
+            number(y),
+            number(width),
+            number(height)
+        );
+        self.op(&op);
+    }
+
+    /// A horizontal rule across the content width.
+    pub fn rule(&mut self, indent: f32, color: Rgb) {
+        self.reserve(6.0);
+        let y = self.y - 3.0;
+        let op = format!(
+            "0.75 w {} {} {} m {} {} l S",
+            color.stroke(),
+            number(MARGIN + indent),
+            number(y),
+            number(PAGE_WIDTH - MARGIN),
+            number(y)
+        );
+        self.op(&op);
+        self.y -= 6.0;
+    }
+
+    /// Take `height` points of space and return the top of it.
+    pub fn take(&mut self, height: f32) -> f32 {
+        self.reserve(height);
+        let top = self.y;
+        self.y -= height;
+        top
+    }
+
+    /// Place an image scaled to `width` x `height` points at `indent`.
+    pub fn image(&mut self, image: PdfImage, indent: f32, width: f32, height: f32) {
+        let top = self.take(height);
+        self.images.push(image);
+        let op = format!(
+            "q {} 0 0 {} {} {} cm /Im{} Do Q",
+            number(width),
+            number(height),
+            number(MARGIN + indent),
+            number(top - height),
+            self.images.len()
+        );
+        self.op(&op);
+    }
+
+    /// Add a footer with the page number to every page and return the content streams.
+    pub fn finish(mut self, title: &str) -> (Vec<Vec<u8>>, Vec<PdfImage>) {
+        let total = self.pages.len();
+        let style = Style {
+            size: 8.0,
+            color: MUTED,
+            ..Style::default()
+        };
+        let pages = std::mem::take(&mut self.pages);
+        let mut finished = Vec::with_capacity(total);
+        for (i, page) in pages.into_iter().enumerate() {
+            self.pages = vec![page];
+            let label = format!("{} / {}", i + 1, total);
+            let label_width = style.font().text_width(&label, style.size);
+            let baseline = MARGIN - 10.0;
+            self.draw_text(title, &style, MARGIN, baseline);
+            self.draw_text(&label, &style, PAGE_WIDTH - MARGIN - label_width, baseline);
+            finished.extend(self.pages.drain(..).map(String::into_bytes));
+        }
+        (finished, self.images)
+    }
+}
+
+fn align_offset(align: Align, width: f32, line_width: f32) -> f32 {
+    match align {
+        Align::Left => 0.0,
+        Align::Center => ((width - line_width) / 2.0).max(0.0),
+        Align::Right => (width - line_width).max(0.0),
+    }
+}
+
+/// A PDF string of `text`, made ASCII-safe for embedding in a text operator.
+///
+/// Bytes above 127 are written as octal escapes so the content stream stays valid UTF-8.
+fn latin_escape(text: &str) -> Vec<u8> {
+    let mut out = Vec::new();
+    for byte in pdf_string(text) {
+        if byte >= 0x80 {
+            out.extend(format!("\\{:03o}", byte).bytes());
+        } else {
+            out.push(byte);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str) -> Run {
+        Run {
+            text: text.to_string(),
+            style: Style::default(),
+        }
+    }
+
+    #[test]
+    fn test_wrap_breaks_at_width() {
+        let lines = wrap(
+            &[run("aaa bbb ccc")],
+            Font::Regular.text_width("aaa bbb", 10.0) + 1.0,
+        );
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].pieces.len(), 3);
+        assert_eq!(lines[1].pieces[0].text, "ccc");
+    }
+
+    #[test]
+    fn test_wrap_forced_break_and_long_word() {
+        let lines = wrap(&[run("a\nb")], 100.0);
+        assert_eq!(lines.len(), 2);
+
+        let lines = wrap(&[run(&"x".repeat(40))], 50.0);
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| line.width <= 50.0));
+    }
+
+    #[test]
+    fn test_page_break() {
+        let mut layout = Layout::default();
+        let lines = wrap(&[run(&"word ".repeat(3000))], CONTENT_WIDTH);
+        layout.text(&lines, 0.0, CONTENT_WIDTH, Align::Left, None);
+        assert!(layout.pages.len() > 1);
+        let (pages, _) = layout.finish("Doc");
+        assert!(String::from_utf8_lossy(&pages[0]).contains("(1 / "));
+    }
+
+    #[test]
+    fn test_non_ascii_text_is_octal_escaped() {
+        assert_eq!(latin_escape("é"), b"(\\351)".to_vec());
+    }
+}
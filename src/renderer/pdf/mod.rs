@@ -0,0 +1,239 @@
+//! PDF output: every panel in sequence, with bookmarks for navigation.
+//!
+//! The PDF is written directly, using the standard PDF fonts, so no
+//! browser or network access is needed.
+
+mod flow;
+mod fonts;
+mod layout;
+mod writer;
+
+use super::header::{HeaderLogo, PageHeader};
+use super::{DocumentMeta, RenderError, RenderOutput, Renderer};
+use crate::parser::{parse_markdown, strip_toc_markers, DocumentStructure};
+use flow::{decode_raster_image, Flow};
+use layout::{wrap, Align, Layout, Run, Style, BORDER, CONTENT_WIDTH, MUTED};
+use writer::{Bookmark, PdfDocument};
+
+/// Renders a document structure to PDF.
+#[derive(Debug, Default)]
+pub struct PdfRenderer;
+
+impl PdfRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Render the title block and all panels; nav titles become bookmarks.
+    ///
+    /// Content that cannot be shown in the PDF is reported in `warnings`.
+    pub fn render_page(
+        &self,
+        doc: &DocumentStructure,
+        title: &str,
+        header: &PageHeader,
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<u8>, RenderError> {
+        let mut layout = Layout::default();
+        title_block(&mut layout, title, header);
+
+        let mut bookmarks: Vec<Bookmark> = Vec::new();
+        let mut skipped_charts = 0;
+        if let Some(ref intro) = header.intro {
+            skipped_charts += Flow::new(&mut layout, warnings).render(&parse_markdown(intro));
+        }
+        let mut current_group = None;
+        for (i, panel) in doc.panels.iter().enumerate() {
+            let group = doc.parent_of(&panel.id);
+            let first_in_group = group.is_some_and(|group| current_group != Some(&group.id));
+            current_group = group.map(|group| &group.id);
+
+            if let Some(group) = group.filter(|_| first_in_group) {
+                let (page, y) = section_heading(&mut layout, &group.title, 17.0, i > 0);
+                bookmarks.push(Bookmark {
+                    title: group.title.clone(),
+                    page,
+                    y,
+                    children: Vec::new(),
+                });
+            }
+
+            let size = if group.is_some() { 14.0 } else { 17.0 };
+            let (page, y) =
+                section_heading(&mut layout, &panel.title, size, i > 0 && !first_in_group);
+            let bookmark = Bookmark {
+                title: panel.title.clone(),
+                page,
+                y,
+                children: Vec::new(),
+            };
+            match bookmarks.last_mut() {
+                Some(parent) if group.is_some() => parent.children.push(bookmark),
+                _ => bookmarks.push(bookmark),
+            }
+
+            let html = parse_markdown(&strip_toc_markers(&panel.markdown_content));
+            skipped_charts += Flow::new(&mut layout, warnings).render(&html);
+        }
+        if skipped_charts > 0 {
+            warnings.push(format!(
+                "{} skill chart(s) are not included in the PDF; the tables are",
+                skipped_charts
+            ));
+        }
+
+        // Counted before the page footers repeat the title
+        if layout.replaced > 0 {
+            let chars: Vec<String> = layout
+                .replaced_chars
+                .iter()
+                .take(10)
+                .map(char::to_string)
+                .collect();
+            warnings.push(format!(
+                "{} character(s) are not in the PDF fonts and are shown as '?': {}",
+                layout.replaced,
+                chars.join(" ")
+            ));
+        }
+
+        let (pages, images) = layout.finish(title);
+        let document = PdfDocument {
+            title: title.to_string(),
+            width: layout::PAGE_WIDTH,
+            height: layout::PAGE_HEIGHT,
+            pages,
+            images,
+            bookmarks,
+        };
+        Ok(document.to_bytes()?)
+    }
+}
+
+impl Renderer for PdfRenderer {
+    fn render(
+        &self,
+        doc: &DocumentStructure,
+        meta: &DocumentMeta,
+        warnings: &mut Vec<String>,
+    ) -> Result<RenderOutput, RenderError> {
+        let pdf = self.render_page(doc, meta.title, meta.header, warnings)?;
+        Ok(RenderOutput::File(pdf))
+    }
+}
+
+/// The document title with logo, subtitle, author and links.
+fn title_block(layout: &mut Layout, title: &str, header: &PageHeader) {
+    let text = |text: &str, size: f32, bold: bool, color| Run {
+        text: text.to_string(),
+        style: Style {
+            bold,
+            size,
+            color,
+            ..Style::default()
+        },
+    };
+
+    match header.logo {
+        HeaderLogo::Image(ref uri) => {
+            if let Some(image) = decode_raster_image(uri) {
+                let height = 48.0_f32.min(image.height as f32 * 0.75);
+                let width = image.width as f32 * height / image.height as f32;
+                layout.image(image, 0.0, width, height);
+                layout.gap(8.0);
+            }
+        }
+        HeaderLogo::Text(ref wordmark) => {
+            let lines = wrap(&[text(wordmark, 14.0, true, MUTED)], CONTENT_WIDTH);
+            layout.text(&lines, 0.0, CONTENT_WIDTH, Align::Left, None);
+        }
+        HeaderLogo::Svg(_) | HeaderLogo::None => {}
+    }
+
+    let mut runs = vec![text(title, 22.0, true, layout::TEXT)];
+    if let Some(ref subtitle) = header.subtitle {
+        runs.push(text(&format!("\n{}", subtitle), 12.0, false, MUTED));
+    }
+    if let Some(ref author) = header.author {
+        runs.push(text(&format!("\n{}", author), 10.0, false, MUTED));
+    }
+    for link in &header.links {
+        runs.push(text(
+            &format!("\n{}: {}", link.label, link.url),
+            9.0,
+            false,
+            MUTED,
+        ));
+    }
+    let lines = wrap(&runs, CONTENT_WIDTH);
+    layout.text(&lines, 0.0, CONTENT_WIDTH, Align::Left, None);
+    layout.gap(6.0);
+    layout.rule(0.0, BORDER);
+    layout.gap(12.0);
+}
+
+/// A section title; returns where it was placed for the bookmark.
+fn section_heading(layout: &mut Layout, title: &str, size: f32, spaced: bool) -> (usize, f32) {
+    if spaced {
+        layout.gap(size);
+    }
+    // Keep the heading together with the start of its content
+    layout.keep_space(size * 5.0);
+    let lines = wrap(
+        &[Run {
+            text: title.to_string(),
+            style: Style {
+                bold: true,
+                size,
+                ..Style::default()
+            },
+        }],
+        CONTENT_WIDTH,
+    );
+    layout.reserve(0.0);
+    let position = layout.position();
+    layout.text(&lines, 0.0, CONTENT_WIDTH, Align::Left, None);
+    layout.gap(6.0);
+    position
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_document_structure;
+
+    #[test]
+    fn test_render_pdf_with_bookmarks() {
+        let markdown = "## About\n\nHello\n\n## Projects\n\nIntro\n\n### Vellum\n\nGenerator\n\n### Other\n\nMore\n";
+        let doc = parse_document_structure(markdown, Some("Projects"));
+        let mut warnings = Vec::new();
+        let pdf = PdfRenderer::new()
+            .render_page(&doc, "Portfolio", &PageHeader::with_logo(""), &mut warnings)
+            .unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.contains("/PageMode /UseOutlines"));
+        // About, Projects (with Vellum and Other)
+        assert!(text.contains("/Type /Outlines /First"));
+        assert!(text.contains("/Count 4 >>"));
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_unencodable_characters_reported() {
+        let markdown = "## Łódź → Kraków\n\nПривет, 世界 → ok\n";
+        let doc = parse_document_structure(markdown, None);
+        let mut warnings = Vec::new();
+        let pdf = PdfRenderer::new()
+            .render_page(&doc, "Trip", &PageHeader::with_logo(""), &mut warnings)
+            .unwrap();
+        let text = String::from_utf8_lossy(&pdf);
+
+        // The bookmark keeps the title as UTF-16
+        assert!(text.contains("/Title <FEFF0141"));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("12 character(s) are not in the PDF fonts"));
+        assert!(warnings[0].contains('→') && warnings[0].contains('Ł'));
+    }
+}
@@ -0,0 +1,338 @@
+//! Serialisation of laid-out pages into a PDF file.
+
+use flate2::{write::ZlibEncoder, Compression};
+use std::io::Write;
+
+use super::fonts::Font;
+
+/// A raster image as 8-bit RGB samples.
+#[derive(Debug, Clone)]
+pub struct PdfImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+}
+
+/// An outline entry pointing at a position on a page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub title: String,
+    pub page: usize,
+    /// Distance from the bottom of the page in points
+    pub y: f32,
+    pub children: Vec<Bookmark>,
+}
+
+/// A complete document: page content streams, images and bookmarks.
+#[derive(Debug, Default)]
+pub struct PdfDocument {
+    pub title: String,
+    pub width: f32,
+    pub height: f32,
+    pub pages: Vec<Vec<u8>>,
+    /// Images referenced from the pages as `/Im<index + 1>`
+    pub images: Vec<PdfImage>,
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl PdfDocument {
+    /// Write the document as PDF 1.4.
+    pub fn to_bytes(&self) -> std::io::Result<Vec<u8>> {
+        let mut objects = Objects::default();
+        let catalog = objects.reserve();
+        let pages_root = objects.reserve();
+        let resources = objects.reserve();
+        let outlines = objects.reserve();
+
+        let mut font_refs = String::new();
+        for font in Font::ALL {
+            let num = objects.add(
+                format!(
+                    "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+                    font.base_font()
+                )
+                .into_bytes(),
+            );
+            font_refs.push_str(&format!("/{} {} 0 R ", font.resource(), num));
+        }
+
+        let mut image_refs = String::new();
+        for (i, image) in self.images.iter().enumerate() {
+            let header = format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} /ColorSpace /DeviceRGB /BitsPerComponent 8",
+                image.width, image.height
+            );
+            let num = objects.add(stream(&header, &image.rgb)?);
+            image_refs.push_str(&format!("/Im{} {} 0 R ", i + 1, num));
+        }
+        objects.set(
+            resources,
+            format!(
+                "<< /Font << {}>> /XObject << {}>> >>",
+                font_refs, image_refs
+            )
+            .into_bytes(),
+        );
+
+        let mut page_nums = Vec::with_capacity(self.pages.len());
+        for content in &self.pages {
+            let content_num = objects.add(stream("<<", content)?);
+            let page_num = objects.add(
+                format!(
+                    "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources {} 0 R /Contents {} 0 R >>",
+                    pages_root,
+                    number(self.width),
+                    number(self.height),
+                    resources,
+                    content_num
+                )
+                .into_bytes(),
+            );
+            page_nums.push(page_num);
+        }
+        let kids: Vec<String> = page_nums.iter().map(|n| format!("{} 0 R", n)).collect();
+        objects.set(
+            pages_root,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                page_nums.len()
+            )
+            .into_bytes(),
+        );
+
+        let (first, last, count) =
+            write_outline_items(&mut objects, &self.bookmarks, outlines, &page_nums);
+        let outline_root = match (first, last) {
+            (Some(first), Some(last)) => format!(
+                "<< /Type /Outlines /First {} 0 R /Last {} 0 R /Count {} >>",
+                first, last, count
+            ),
+            _ => "<< /Type /Outlines /Count 0 >>".to_string(),
+        };
+        objects.set(outlines, outline_root.into_bytes());
+
+        let page_mode = if self.bookmarks.is_empty() {
+            "UseNone"
+        } else {
+            "UseOutlines"
+        };
+        objects.set(
+            catalog,
+            format!(
+                "<< /Type /Catalog /Pages {} 0 R /Outlines {} 0 R /PageMode /{} >>",
+                pages_root, outlines, page_mode
+            )
+            .into_bytes(),
+        );
+        let info = objects.add(
+            format!(
+                "<< /Title {} /Producer (Vellum) >>",
+                text_string(&self.title)
+            )
+            .into_bytes(),
+        );
+
+        Ok(objects.finish(catalog, info))
+    }
+}
+
+/// Write outline items for `bookmarks`, returning first, last and visible count.
+fn write_outline_items(
+    objects: &mut Objects,
+    bookmarks: &[Bookmark],
+    parent: usize,
+    page_nums: &[usize],
+) -> (Option<usize>, Option<usize>, usize) {
+    let nums: Vec<usize> = bookmarks.iter().map(|_| objects.reserve()).collect();
+    let mut count = bookmarks.len();
+
+    for (i, bookmark) in bookmarks.iter().enumerate() {
+        let mut dict = format!(
+            "<< /Title {} /Parent {} 0 R",
+            text_string(&bookmark.title),
+            parent
+        );
+        if i > 0 {
+            dict.push_str(&format!(" /Prev {} 0 R", nums[i - 1]));
+        }
+        if let Some(next) = nums.get(i + 1) {
+            dict.push_str(&format!(" /Next {} 0 R", next));
+        }
+        let (first, last, children) =
+            write_outline_items(objects, &bookmark.children, nums[i], page_nums);
+        if let (Some(first), Some(last)) = (first, last) {
+            dict.push_str(&format!(
+                " /First {} 0 R /Last {} 0 R /Count {}",
+                first, last, children
+            ));
+            count += children;
+        }
+        if let Some(page) = page_nums.get(bookmark.page) {
+            dict.push_str(&format!(
+                " /Dest [{} 0 R /XYZ 0 {} null]",
+                page,
+                number(bookmark.y)
+            ));
+        }
+        dict.push_str(" >>");
+        objects.set(nums[i], dict.into_bytes());
+    }
+
+    (nums.first().copied(), nums.last().copied(), count)
+}
+
+/// Numbered objects of the file, in order.
+#[derive(Default)]
+struct Objects {
+    bodies: Vec<Vec<u8>>,
+}
+
+impl Objects {
+    fn reserve(&mut self) -> usize {
+        self.bodies.push(Vec::new());
+        self.bodies.len()
+    }
+
+    fn add(&mut self, body: Vec<u8>) -> usize {
+        self.bodies.push(body);
+        self.bodies.len()
+    }
+
+    fn set(&mut self, num: usize, body: Vec<u8>) {
+        self.bodies[num - 1] = body;
+    }
+
+    fn finish(self, root: usize, info: usize) -> Vec<u8> {
+        let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.bodies.len());
+        for (i, body) in self.bodies.iter().enumerate() {
+            offsets.push(out.len());
+            out.extend(format!("{} 0 obj\n", i + 1).as_bytes());
+            out.extend(body);
+            out.extend(b"\nendobj\n");
+        }
+
+        let xref = out.len();
+        out.extend(format!("xref\n0 {}\n0000000000 65535 f \n", self.bodies.len() + 1).as_bytes());
+        for offset in offsets {
+            out.extend(format!("{:010} 00000 n \n", offset).as_bytes());
+        }
+        out.extend(
+            format!(
+                "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+                self.bodies.len() + 1,
+                root,
+                info,
+                xref
+            )
+            .as_bytes(),
+        );
+        out
+    }
+}
+
+/// A Flate-compressed stream object; `dict_start` is its dictionary without `>>`.
+fn stream(dict_start: &str, data: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    let compressed = encoder.finish()?;
+
+    let mut body = format!(
+        "{} /Filter /FlateDecode /Length {} >>\nstream\n",
+        dict_start,
+        compressed.len()
+    )
+    .into_bytes();
+    body.extend(compressed);
+    body.extend(b"\nendstream");
+    Ok(body)
+}
+
+/// A text string as UTF-16BE hex, which viewers show in any script.
+fn text_string(text: &str) -> String {
+    let mut hex = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        hex.push_str(&format!("{:04X}", unit));
+    }
+    hex.push('>');
+    hex
+}
+
+/// A number with at most two decimals.
+pub fn number(value: f32) -> String {
+    let rounded = format!("{:.2}", value);
+    rounded
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_formatting() {
+        assert_eq!(number(12.0), "12");
+        assert_eq!(number(0.5), "0.5");
+        assert_eq!(number(12.3456), "12.35");
+    }
+
+    #[test]
+    fn test_text_string() {
+        assert_eq!(text_string("Hé"), "<FEFF004800E9>");
+    }
+
+    #[test]
+    fn test_document_structure() {
+        let document = PdfDocument {
+            title: "Test".to_string(),
+            width: 595.0,
+            height: 842.0,
+            pages: vec![b"BT ET".to_vec(), b"BT ET".to_vec()],
+            images: Vec::new(),
+            bookmarks: vec![Bookmark {
+                title: "Projects".to_string(),
+                page: 1,
+                y: 800.0,
+                children: vec![Bookmark {
+                    title: "Vellum".to_string(),
+                    page: 1,
+                    y: 700.0,
+                    children: Vec::new(),
+                }],
+            }],
+        };
+        let bytes = document.to_bytes().unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.ends_with("%%EOF\n"));
+        assert!(text.contains("/Type /Pages /Kids ["));
+        assert!(text.contains("/Count 2 >>"));
+        assert!(text.contains("/Type /Outlines /First"));
+        assert!(text.contains("/Dest ["));
+
+        // Every xref offset points at its object
+        let xref_start: usize = text
+            .rsplit("startxref\n")
+            .next()
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let xref = String::from_utf8_lossy(&bytes[xref_start..]);
+        for (i, line) in xref
+            .lines()
+            .skip(3)
+            .take_while(|l| l.ends_with(" n "))
+            .enumerate()
+        {
+            let offset: usize = line[..10].parse().unwrap();
+            assert!(bytes[offset..].starts_with(format!("{} 0 obj", i + 1).as_bytes()));
+        }
+    }
+}
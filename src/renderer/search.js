@@ -0,0 +1,97 @@
+/**
+ * Vellum - Search
+ * Finds text in every panel, hidden ones included, using SEARCH_INDEX.
+ * Results link to the panel or heading, which the navigation script shows.
+ */
+
+document.addEventListener('DOMContentLoaded', function() {
+    const input = document.getElementById('search-input');
+    const results = document.getElementById('search-results');
+    if (!input || !results || typeof SEARCH_INDEX === 'undefined') {
+        return;
+    }
+
+    const MAX_RESULTS = 20;
+
+    // Text around the first match, cut at word boundaries where possible
+    function excerpt(text, term) {
+        const at = Math.max(text.toLowerCase().indexOf(term), 0);
+        let start = Math.max(at - 40, 0);
+        let end = Math.min(at + term.length + 80, text.length);
+        if (start > 0) {
+            const space = text.indexOf(' ', start);
+            if (space !== -1 && space < at) start = space + 1;
+        }
+        if (end < text.length) {
+            const space = text.lastIndexOf(' ', end);
+            if (space > at) end = space;
+        }
+        return (start > 0 ? '…' : '') + text.slice(start, end) + (end < text.length ? '…' : '');
+    }
+
+    function addResult(id, title, detail) {
+        const item = document.createElement('li');
+        const link = document.createElement('a');
+        link.href = '#' + encodeURIComponent(id);
+        const strong = document.createElement('strong');
+        strong.textContent = title;
+        link.appendChild(strong);
+        if (detail) {
+            const span = document.createElement('span');
+            span.textContent = detail;
+            link.appendChild(span);
+        }
+        item.appendChild(link);
+        results.appendChild(item);
+    }
+
+    function search() {
+        const terms = input.value.toLowerCase().split(/\s+/).filter(t => t);
+        results.textContent = '';
+        results.hidden = terms.length === 0;
+        if (terms.length === 0) return;
+
+        let count = 0;
+        for (const [id, panel, heading, text] of SEARCH_INDEX) {
+            const haystack = (panel + ' ' + heading + ' ' + text).toLowerCase();
+            if (!terms.every(t => haystack.includes(t))) continue;
+
+            const title = heading ? panel + ' › ' + heading : panel;
+            addResult(id, title, text ? excerpt(text, terms[0]) : '');
+            if (++count === MAX_RESULTS) break;
+        }
+
+        if (count === 0) {
+            const item = document.createElement('li');
+            item.className = 'search-empty';
+            item.textContent = 'No results';
+            results.appendChild(item);
+        }
+    }
+
+    function close() {
+        results.hidden = true;
+    }
+
+    input.addEventListener('input', search);
+    input.addEventListener('focus', () => {
+        if (input.value.trim()) search();
+    });
+    input.addEventListener('keydown', event => {
+        if (event.key === 'Escape') {
+            input.value = '';
+            close();
+        } else if (event.key === 'Enter') {
+            const first = results.querySelector('a');
+            if (first) first.click();
+        }
+    });
+
+    // Following a result changes the hash; the navigation script shows the target
+    results.addEventListener('click', event => {
+        if (event.target.closest('a')) close();
+    });
+    document.addEventListener('click', event => {
+        if (!event.target.closest('.search')) close();
+    });
+});
@@ -0,0 +1,160 @@
+//! Full-text search of the tabbed page.
+//!
+//! Only one panel is shown at a time, so the browser's own search misses the
+//! rest. The text of every panel is embedded as a compact index, split at the
+//! headings, and searched by a small script that links to the matches.
+
+use super::markup::{tokenize, Token};
+use serde::Serialize;
+
+/// Searches the index and lists the matches under the search box.
+pub const SEARCH_SCRIPT: &str = include_str!("search.js");
+
+/// Styles of the search box and results, added after the theme styles.
+pub const SEARCH_STYLES: &str = include_str!("search.css");
+
+/// The search box, placed after the navigation.
+pub(super) const SEARCH_BOX: &str = r#"<div class="search" role="search">
+            <input type="search" id="search-input" placeholder="Search" aria-label="Search the document" autocomplete="off">
+            <ol id="search-results" class="search-results" hidden></ol>
+        </div>"#;
+
+/// Elements that do not separate words.
+const INLINE_ELEMENTS: [&str; 16] = [
+    "a", "abbr", "b", "cite", "code", "del", "em", "i", "ins", "kbd", "mark", "s", "small", "span",
+    "strong", "u",
+];
+
+/// Elements whose text is not searched; `nav` holds tables of contents.
+const SKIPPED_ELEMENTS: [&str; 4] = ["nav", "script", "style", "svg"];
+
+/// One searchable part of a panel: `[target id, panel title, heading, text]`.
+///
+/// The target is the panel id for the text before the first heading and the
+/// heading id after it. Serialized as an array to keep the index small.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(super) struct SearchEntry(pub String, pub String, pub String, pub String);
+
+/// Split rendered panels, as `(id, title, html)`, into search entries.
+pub(super) fn search_entries(panels: &[(String, String, String)]) -> Vec<SearchEntry> {
+    let mut entries = Vec::new();
+
+    for (id, title, html) in panels {
+        let mut entry = SearchEntry(id.clone(), title.clone(), String::new(), String::new());
+        // Heading whose text is being read, and depth inside skipped elements
+        let mut in_heading = None;
+        let mut in_anchor = false;
+        let mut skipped = 0usize;
+
+        for token in tokenize(html) {
+            match token {
+                Token::Open { ref name, .. } if SKIPPED_ELEMENTS.contains(&name.as_str()) => {
+                    skipped += 1
+                }
+                Token::Close(ref name) if SKIPPED_ELEMENTS.contains(&name.as_str()) => {
+                    skipped = skipped.saturating_sub(1)
+                }
+                _ if skipped > 0 => {}
+                Token::Open { ref name, .. } if is_heading(name) => {
+                    if let Some(heading_id) = token.attr("id") {
+                        entries.push(finish(entry));
+                        entry = SearchEntry(
+                            heading_id.to_string(),
+                            title.clone(),
+                            String::new(),
+                            String::new(),
+                        );
+                        in_heading = Some(name.clone());
+                    } else {
+                        entry.3.push(' ');
+                    }
+                }
+                Token::Close(ref name) if in_heading.as_ref() == Some(name) => in_heading = None,
+                Token::Open { ref name, .. }
+                    if name == "a" && token.has_class("heading-anchor") =>
+                {
+                    in_anchor = true
+                }
+                Token::Close(ref name) if name == "a" && in_anchor => in_anchor = false,
+                Token::Open { ref name, .. } | Token::Close(ref name) => {
+                    if !INLINE_ELEMENTS.contains(&name.as_str()) {
+                        entry.3.push(' ');
+                    }
+                }
+                Token::Text(_) if in_anchor => {}
+                Token::Text(ref text) if in_heading.is_some() => entry.2.push_str(text),
+                Token::Text(ref text) => entry.3.push_str(text),
+            }
+        }
+        entries.push(finish(entry));
+    }
+
+    entries
+}
+
+/// Script defining `SEARCH_INDEX`, safe to place inside a `<script>` element.
+pub(super) fn index_script(
+    panels: &[(String, String, String)],
+) -> Result<String, serde_json::Error> {
+    let json = serde_json::to_string(&search_entries(panels))?;
+    Ok(format!(
+        "const SEARCH_INDEX = {};\n",
+        json.replace("</", "<\\/")
+    ))
+}
+
+fn is_heading(name: &str) -> bool {
+    matches!(name, "h1" | "h2" | "h3" | "h4" | "h5" | "h6")
+}
+
+/// Collapse the whitespace of the heading and text.
+fn finish(mut entry: SearchEntry) -> SearchEntry {
+    entry.2 = entry.2.split_whitespace().collect::<Vec<_>>().join(" ");
+    entry.3 = entry.3.split_whitespace().collect::<Vec<_>>().join(" ");
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panel(id: &str, title: &str, html: &str) -> (String, String, String) {
+        (id.to_string(), title.to_string(), html.to_string())
+    }
+
+    #[test]
+    fn test_search_entries_split_at_headings() {
+        let html = "<p>Intro <strong>text</strong></p><ul><li>one</li><li>two</li></ul>\
+            <h3 id=\"setup\">Set&amp;up <a class=\"heading-anchor\" href=\"#setup\">#</a></h3><p>Install it</p>\
+            <svg><text>chart</text></svg><p>Done</p>";
+        let entries = search_entries(&[panel("notes", "Notes", html)]);
+
+        assert_eq!(
+            entries,
+            [
+                SearchEntry(
+                    "notes".into(),
+                    "Notes".into(),
+                    String::new(),
+                    "Intro text one two".into()
+                ),
+                SearchEntry(
+                    "setup".into(),
+                    "Notes".into(),
+                    "Set&up".into(),
+                    "Install it Done".into()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_script_escapes_closing_tags() {
+        let script =
+            index_script(&[panel("about", "About", "<pre>&lt;/script&gt;</pre>")]).unwrap();
+        assert_eq!(
+            script,
+            "const SEARCH_INDEX = [[\"about\",\"About\",\"\",\"<\\/script>\"]];\n"
+        );
+    }
+}
@@ -0,0 +1,692 @@
+//! Template engine for `template.html`.
+//!
+//! Supports escaped variables (`{{ title }}`), raw output (`{{ title | raw }}`),
+//! conditionals (`{% if %}`/`{% elif %}`/`{% else %}`/`{% endif %}`), loops
+//! (`{% for panel in panels %}`), partials (`{% include "header" %}`) and
+//! comments (`{# ... #}`). Values marked as HTML (styles, script, rendered
+//! panels) are inserted verbatim, which keeps the classic `{{content}}`
+//! style placeholders working. Templates are rendered in a single pass, so
+//! placeholders inside inserted content are never substituted again.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+/// Maximum nesting depth of `{% include %}`, guarding against recursive partials.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// A value available to templates.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// Plain text, escaped on output
+    Str(String),
+    /// Trusted markup, inserted verbatim
+    Html(String),
+    Bool(bool),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl Value {
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Str(s) | Value::Html(s) => !s.is_empty(),
+            Value::Bool(b) => *b,
+            Value::List(items) => !items.is_empty(),
+            Value::Map(map) => !map.is_empty(),
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Str(_) => "string",
+            Value::Html(_) => "html",
+            Value::Bool(_) => "bool",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+/// Variables passed to a template.
+pub type Context = BTreeMap<String, Value>;
+
+/// A template syntax or evaluation error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Var {
+        path: Vec<String>,
+        raw: bool,
+        line: usize,
+    },
+    If {
+        branches: Vec<(Condition, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+    For {
+        var: String,
+        path: Vec<String>,
+        body: Vec<Node>,
+        line: usize,
+    },
+    Include {
+        name: String,
+        line: usize,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Condition {
+    negate: bool,
+    path: Vec<String>,
+}
+
+/// A lexed piece of the template source.
+#[derive(Debug)]
+enum Token {
+    Text(String),
+    Var(String, usize),
+    Tag(String, usize),
+}
+
+/// Parsed template, ready to be rendered with different contexts.
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let tokens = tokenize(source)?;
+        let mut pos = 0;
+        let (nodes, end) = parse_nodes(&tokens, &mut pos, &[])?;
+        if let Some((tag, line)) = end {
+            return Err(TemplateError {
+                line,
+                message: format!("Unexpected '{{% {} %}}'", tag),
+            });
+        }
+        Ok(Self { nodes })
+    }
+
+    /// Render the template. `partials` resolves `{% include "name" %}`.
+    pub fn render(
+        &self,
+        context: &Context,
+        partials: &HashMap<String, String>,
+    ) -> Result<String, TemplateError> {
+        let mut out = String::new();
+        let mut scopes = vec![context.clone()];
+        render_nodes(&self.nodes, &mut scopes, partials, 0, &mut out)?;
+        Ok(out)
+    }
+
+    /// Whether the template itself refers to the top-level variable `name`.
+    /// Partials are not followed.
+    pub fn uses(&self, name: &str) -> bool {
+        nodes_use(&self.nodes, name)
+    }
+}
+
+fn nodes_use(nodes: &[Node], name: &str) -> bool {
+    let is_name = |path: &[String]| path.first().is_some_and(|first| first == name);
+    nodes.iter().any(|node| match node {
+        Node::Text(_) | Node::Include { .. } => false,
+        Node::Var { path, .. } => is_name(path),
+        Node::If {
+            branches,
+            otherwise,
+        } => {
+            branches
+                .iter()
+                .any(|(condition, body)| is_name(&condition.path) || nodes_use(body, name))
+                || nodes_use(otherwise, name)
+        }
+        Node::For {
+            var, path, body, ..
+        } => is_name(path) || (var != name && nodes_use(body, name)),
+    })
+}
+
+/// Parse and render a template in one go.
+pub fn render_template(
+    source: &str,
+    context: &Context,
+    partials: &HashMap<String, String>,
+) -> Result<String, TemplateError> {
+    Template::parse(source)?.render(context, partials)
+}
+
+/// Escape text for HTML output.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    let mut line = 1;
+
+    while !rest.is_empty() {
+        let next = ["{{", "{%", "{#"]
+            .iter()
+            .filter_map(|open| rest.find(open).map(|pos| (pos, *open)))
+            .min_by_key(|(pos, _)| *pos);
+
+        let Some((pos, open)) = next else {
+            tokens.push(Token::Text(rest.to_string()));
+            break;
+        };
+
+        if pos > 0 {
+            tokens.push(Token::Text(rest[..pos].to_string()));
+            line += rest[..pos].matches('\n').count();
+        }
+
+        let close = match open {
+            "{{" => "}}",
+            "{%" => "%}",
+            _ => "#}",
+        };
+        let after_open = &rest[pos + 2..];
+        let Some(end) = after_open.find(close) else {
+            return Err(TemplateError {
+                line,
+                message: format!("Unclosed '{}'", open),
+            });
+        };
+        let inner = after_open[..end].trim().to_string();
+        match open {
+            "{{" => tokens.push(Token::Var(inner, line)),
+            "{%" => tokens.push(Token::Tag(inner, line)),
+            _ => {}
+        }
+
+        line += after_open[..end].matches('\n').count();
+        rest = &after_open[end + 2..];
+    }
+
+    Ok(tokens)
+}
+
+/// A closing tag with its line number.
+type EndTag = Option<(String, usize)>;
+
+/// Parse nodes until one of `terminators` (e.g. `endif`) is reached.
+/// Returns the nodes and the terminating tag, if any.
+fn parse_nodes(
+    tokens: &[Token],
+    pos: &mut usize,
+    terminators: &[&str],
+) -> Result<(Vec<Node>, EndTag), TemplateError> {
+    let mut nodes = Vec::new();
+
+    while *pos < tokens.len() {
+        let token = &tokens[*pos];
+        *pos += 1;
+
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.clone())),
+            Token::Var(expr, line) => nodes.push(parse_var(expr, *line)?),
+            Token::Tag(tag, line) => {
+                let keyword = tag.split_whitespace().next().unwrap_or("");
+                if terminators.contains(&keyword) {
+                    return Ok((nodes, Some((tag.clone(), *line))));
+                }
+                match keyword {
+                    "if" => nodes.push(parse_if(tag, *line, tokens, pos)?),
+                    "for" => nodes.push(parse_for(tag, *line, tokens, pos)?),
+                    "include" => nodes.push(parse_include(tag, *line)?),
+                    _ => {
+                        return Err(TemplateError {
+                            line: *line,
+                            message: format!("Unknown tag '{{% {} %}}'", tag),
+                        })
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((nodes, None))
+}
+
+fn parse_var(expr: &str, line: usize) -> Result<Node, TemplateError> {
+    let mut parts = expr.split('|').map(str::trim);
+    let path = parse_path(parts.next().unwrap_or(""), line)?;
+    let mut raw = false;
+    for filter in parts {
+        match filter {
+            "raw" => raw = true,
+            "escape" => raw = false,
+            _ => {
+                return Err(TemplateError {
+                    line,
+                    message: format!("Unknown filter '{}'", filter),
+                })
+            }
+        }
+    }
+    Ok(Node::Var { path, raw, line })
+}
+
+fn parse_path(expr: &str, line: usize) -> Result<Vec<String>, TemplateError> {
+    let valid = !expr.is_empty()
+        && expr
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_'));
+    if !valid {
+        return Err(TemplateError {
+            line,
+            message: format!("Invalid variable name '{}'", expr),
+        });
+    }
+    Ok(expr.split('.').map(str::to_string).collect())
+}
+
+fn parse_condition(expr: &str, line: usize) -> Result<Condition, TemplateError> {
+    let expr = expr.trim();
+    match expr.strip_prefix("not ") {
+        Some(rest) => Ok(Condition {
+            negate: true,
+            path: parse_path(rest.trim(), line)?,
+        }),
+        None => Ok(Condition {
+            negate: false,
+            path: parse_path(expr, line)?,
+        }),
+    }
+}
+
+fn parse_if(
+    tag: &str,
+    line: usize,
+    tokens: &[Token],
+    pos: &mut usize,
+) -> Result<Node, TemplateError> {
+    let mut branches = Vec::new();
+    let mut otherwise = Vec::new();
+    let mut condition = parse_condition(&tag[2..], line)?;
+
+    loop {
+        let (body, end) = parse_nodes(tokens, pos, &["elif", "else", "endif"])?;
+        let Some((end_tag, end_line)) = end else {
+            return Err(TemplateError {
+                line,
+                message: "Missing '{% endif %}'".to_string(),
+            });
+        };
+        branches.push((condition, body));
+
+        match end_tag.split_whitespace().next() {
+            Some("elif") => condition = parse_condition(&end_tag[4..], end_line)?,
+            Some("else") => {
+                let (body, end) = parse_nodes(tokens, pos, &["endif"])?;
+                if end.is_none() {
+                    return Err(TemplateError {
+                        line,
+                        message: "Missing '{% endif %}'".to_string(),
+                    });
+                }
+                otherwise = body;
+                break;
+            }
+            _ => break,
+        }
+    }
+
+    Ok(Node::If {
+        branches,
+        otherwise,
+    })
+}
+
+fn parse_for(
+    tag: &str,
+    line: usize,
+    tokens: &[Token],
+    pos: &mut usize,
+) -> Result<Node, TemplateError> {
+    let parts: Vec<&str> = tag.split_whitespace().collect();
+    if parts.len() != 4 || parts[2] != "in" {
+        return Err(TemplateError {
+            line,
+            message: format!(
+                "Expected '{{% for item in list %}}', found '{{% {} %}}'",
+                tag
+            ),
+        });
+    }
+    let var = parse_path(parts[1], line)?.join(".");
+    let path = parse_path(parts[3], line)?;
+
+    let (body, end) = parse_nodes(tokens, pos, &["endfor"])?;
+    if end.is_none() {
+        return Err(TemplateError {
+            line,
+            message: "Missing '{% endfor %}'".to_string(),
+        });
+    }
+
+    Ok(Node::For {
+        var,
+        path,
+        body,
+        line,
+    })
+}
+
+fn parse_include(tag: &str, line: usize) -> Result<Node, TemplateError> {
+    let name = tag["include".len()..].trim();
+    let unquoted = name
+        .strip_prefix('"')
+        .and_then(|n| n.strip_suffix('"'))
+        .filter(|n| !n.is_empty());
+    match unquoted {
+        Some(name) => Ok(Node::Include {
+            name: name.to_string(),
+            line,
+        }),
+        None => Err(TemplateError {
+            line,
+            message: format!(
+                "Expected '{{% include \"name\" %}}', found '{{% {} %}}'",
+                tag
+            ),
+        }),
+    }
+}
+
+fn lookup<'a>(scopes: &'a [Context], path: &[String]) -> Option<&'a Value> {
+    let (first, rest) = path.split_first()?;
+    let mut value = scopes.iter().rev().find_map(|scope| scope.get(first))?;
+    for key in rest {
+        match value {
+            Value::Map(map) => value = map.get(key)?,
+            _ => return None,
+        }
+    }
+    Some(value)
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    scopes: &mut Vec<Context>,
+    partials: &HashMap<String, String>,
+    depth: usize,
+    out: &mut String,
+) -> Result<(), TemplateError> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { path, raw, line } => match lookup(scopes, path) {
+                Some(Value::Str(s)) if *raw => out.push_str(s),
+                Some(Value::Str(s)) => out.push_str(&escape_html(s)),
+                Some(Value::Html(s)) => out.push_str(s),
+                Some(Value::Bool(b)) => out.push_str(if *b { "true" } else { "false" }),
+                Some(other) => {
+                    return Err(TemplateError {
+                        line: *line,
+                        message: format!("Cannot print {} '{}'", other.type_name(), path.join(".")),
+                    })
+                }
+                None => {
+                    return Err(TemplateError {
+                        line: *line,
+                        message: format!("Unknown variable '{}'", path.join(".")),
+                    })
+                }
+            },
+            Node::If {
+                branches,
+                otherwise,
+            } => {
+                // Missing variables are simply false in conditions
+                let chosen = branches
+                    .iter()
+                    .find(|(cond, _)| {
+                        let truthy = lookup(scopes, &cond.path).is_some_and(Value::is_truthy);
+                        truthy != cond.negate
+                    })
+                    .map(|(_, body)| body)
+                    .unwrap_or(otherwise);
+                render_nodes(chosen, scopes, partials, depth, out)?;
+            }
+            Node::For {
+                var,
+                path,
+                body,
+                line,
+            } => {
+                let items = match lookup(scopes, path) {
+                    Some(Value::List(items)) => items.clone(),
+                    None => Vec::new(),
+                    Some(other) => {
+                        return Err(TemplateError {
+                            line: *line,
+                            message: format!(
+                                "Cannot loop over {} '{}'",
+                                other.type_name(),
+                                path.join(".")
+                            ),
+                        })
+                    }
+                };
+                let count = items.len();
+                for (index, item) in items.into_iter().enumerate() {
+                    let mut loop_info = BTreeMap::new();
+                    loop_info.insert("index".to_string(), Value::Str((index + 1).to_string()));
+                    loop_info.insert("first".to_string(), Value::Bool(index == 0));
+                    loop_info.insert("last".to_string(), Value::Bool(index + 1 == count));
+
+                    let mut scope = Context::new();
+                    scope.insert(var.clone(), item);
+                    scope.insert("loop".to_string(), Value::Map(loop_info));
+
+                    scopes.push(scope);
+                    let result = render_nodes(body, scopes, partials, depth, out);
+                    scopes.pop();
+                    result?;
+                }
+            }
+            Node::Include { name, line } => {
+                if depth >= MAX_INCLUDE_DEPTH {
+                    return Err(TemplateError {
+                        line: *line,
+                        message: format!("Partial '{}' is nested too deeply", name),
+                    });
+                }
+                let Some(source) = partials.get(name) else {
+                    return Err(TemplateError {
+                        line: *line,
+                        message: format!("Unknown partial '{}'", name),
+                    });
+                };
+                let partial = Template::parse(source).map_err(|e| TemplateError {
+                    line: *line,
+                    message: format!("In partial '{}' at line {}: {}", name, e.line, e.message),
+                })?;
+                render_nodes(&partial.nodes, scopes, partials, depth + 1, out).map_err(|e| {
+                    if e.message.starts_with("In partial") {
+                        e
+                    } else {
+                        TemplateError {
+                            line: *line,
+                            message: format!(
+                                "In partial '{}' at line {}: {}",
+                                name, e.line, e.message
+                            ),
+                        }
+                    }
+                })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> Context {
+        let mut ctx = Context::new();
+        ctx.insert("title".to_string(), Value::from("Tom & Jerry"));
+        ctx.insert(
+            "content".to_string(),
+            Value::Html("<p>{{title}}</p>".to_string()),
+        );
+        ctx.insert(
+            "items".to_string(),
+            Value::List(vec![Value::from("a"), Value::from("b")]),
+        );
+        ctx
+    }
+
+    fn render(source: &str) -> Result<String, TemplateError> {
+        render_template(source, &context(), &HashMap::new())
+    }
+
+    #[test]
+    fn test_variables_are_escaped() {
+        assert_eq!(
+            render("<h1>{{title}}</h1>").unwrap(),
+            "<h1>Tom &amp; Jerry</h1>"
+        );
+        assert_eq!(render("{{ title | raw }}").unwrap(), "Tom & Jerry");
+    }
+
+    #[test]
+    fn test_html_values_are_not_substituted_again() {
+        assert_eq!(render("{{content}}").unwrap(), "<p>{{title}}</p>");
+    }
+
+    #[test]
+    fn test_conditionals() {
+        assert_eq!(
+            render("{% if title %}yes{% else %}no{% endif %}").unwrap(),
+            "yes"
+        );
+        assert_eq!(
+            render("{% if missing %}yes{% elif title %}elif{% endif %}").unwrap(),
+            "elif"
+        );
+        assert_eq!(
+            render("{% if not missing %}none{% endif %}").unwrap(),
+            "none"
+        );
+    }
+
+    #[test]
+    fn test_loops() {
+        let output = render("{% for item in items %}{{loop.index}}={{item}}{% if not loop.last %},{% endif %}{% endfor %}").unwrap();
+        assert_eq!(output, "1=a,2=b");
+    }
+
+    #[test]
+    fn test_nested_map_access() {
+        let mut ctx = Context::new();
+        let mut panel = BTreeMap::new();
+        panel.insert("id".to_string(), Value::from("intro"));
+        ctx.insert("panel".to_string(), Value::Map(panel));
+
+        let output = render_template("{{ panel.id }}", &ctx, &HashMap::new()).unwrap();
+        assert_eq!(output, "intro");
+    }
+
+    #[test]
+    fn test_partials() {
+        let mut partials = HashMap::new();
+        partials.insert("header".to_string(), "<h1>{{title}}</h1>".to_string());
+
+        let output = render_template("{% include \"header\" %}", &context(), &partials).unwrap();
+        assert_eq!(output, "<h1>Tom &amp; Jerry</h1>");
+    }
+
+    #[test]
+    fn test_recursive_partial_is_an_error() {
+        let mut partials = HashMap::new();
+        partials.insert("loop".to_string(), "{% include \"loop\" %}".to_string());
+
+        let err = render_template("{% include \"loop\" %}", &context(), &partials).unwrap_err();
+        assert!(err.message.contains("nested too deeply"));
+    }
+
+    #[test]
+    fn test_comments_are_dropped() {
+        assert_eq!(render("a{# note #}b").unwrap(), "ab");
+    }
+
+    #[test]
+    fn test_unknown_variable_reports_line() {
+        let err = render("line one\nline two {{ nope }}").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("nope"));
+    }
+
+    #[test]
+    fn test_unclosed_block_reports_line() {
+        let err = render("\n\n{% if title %}open").unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(err.message.contains("endif"));
+    }
+
+    #[test]
+    fn test_uses_variable() {
+        let template = Template::parse(
+            "{% if print %}{% for p in panels %}{{ p.title }}{% endfor %}{% endif %}",
+        )
+        .unwrap();
+        assert!(template.uses("panels"));
+        assert!(template.uses("print"));
+        assert!(!template.uses("p"));
+        assert!(!Template::parse("{{ content }}").unwrap().uses("panels"));
+    }
+
+    #[test]
+    fn test_unknown_tag() {
+        let err = render("{% while x %}").unwrap_err();
+        assert!(err.message.contains("Unknown tag"));
+    }
+}
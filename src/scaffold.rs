@@ -0,0 +1,278 @@
+//! Project scaffolding for `vellum init`.
+//!
+//! Creates a ready-to-build project folder from one of the demo trees, with
+//! the stock assets written to `config/assets/` and `[paths]` pointing into
+//! the new project.
+
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+use crate::assets::defaults;
+
+#[derive(Debug, Error)]
+pub enum ScaffoldError {
+    #[error("Unknown preset '{0}' (expected portfolio, documentation or band)")]
+    UnknownPreset(String),
+
+    #[error("'{0}' already exists (use --force to overwrite)")]
+    AlreadyExists(String),
+
+    #[error("Failed to write '{path}': {source}")]
+    WriteError {
+        path: String,
+        source: std::io::Error,
+    },
+}
+
+/// Project template mirroring one of the demos.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Preset {
+    #[default]
+    Portfolio,
+    Documentation,
+    Band,
+}
+
+impl FromStr for Preset {
+    type Err = ScaffoldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "portfolio" => Ok(Self::Portfolio),
+            "documentation" | "docs" => Ok(Self::Documentation),
+            "band" => Ok(Self::Band),
+            _ => Err(ScaffoldError::UnknownPreset(s.to_string())),
+        }
+    }
+}
+
+impl Preset {
+    fn title(self) -> &'static str {
+        match self {
+            Self::Portfolio => "My Portfolio",
+            Self::Documentation => "Project Documentation",
+            Self::Band => "Rust & Roll - Official Band Page",
+        }
+    }
+
+    fn dropdown(self) -> &'static str {
+        match self {
+            Self::Portfolio => "Other Documents",
+            Self::Documentation => "Technical",
+            Self::Band => "Archive",
+        }
+    }
+
+    fn colored_tags(self) -> &'static str {
+        match self {
+            Self::Portfolio => "\"\\\\[DONE\\\\]\" = \"green\"\n\"\\\\[WIP\\\\]\" = \"yellow\"\n\"\\\\[BLOCKED\\\\]\" = \"red\"\n",
+            Self::Documentation => "\"v\\\\d+\\\\.\\\\d+\\\\.\\\\d+\" = \"blue\"\n",
+            Self::Band => "",
+        }
+    }
+
+    /// Markdown files below `data/`.
+    fn data_files(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::Portfolio => &[
+                ("index.md", include_str!("../demo/portfolio/data/index.md")),
+                (
+                    "projects.md",
+                    include_str!("../demo/portfolio/data/projects.md"),
+                ),
+                (
+                    "skills.md",
+                    include_str!("../demo/portfolio/data/skills.md"),
+                ),
+                (
+                    "quarterly-goals.md",
+                    include_str!("../demo/portfolio/data/quarterly-goals.md"),
+                ),
+                (
+                    "self-assessment.md",
+                    include_str!("../demo/portfolio/data/self-assessment.md"),
+                ),
+                (
+                    "worklog.md",
+                    include_str!("../demo/portfolio/data/worklog.md"),
+                ),
+                (
+                    "feedback-log.md",
+                    include_str!("../demo/portfolio/data/feedback-log.md"),
+                ),
+                (
+                    "learning-plan.md",
+                    include_str!("../demo/portfolio/data/learning-plan.md"),
+                ),
+                (
+                    "career-roadmap.md",
+                    include_str!("../demo/portfolio/data/career-roadmap.md"),
+                ),
+            ],
+            Self::Documentation => &[
+                ("index.md", include_str!("../demo/vellum/data/index.md")),
+                ("why.md", include_str!("../demo/vellum/data/why.md")),
+                ("howto.md", include_str!("../demo/vellum/data/howto.md")),
+                (
+                    "features.md",
+                    include_str!("../demo/vellum/data/features.md"),
+                ),
+                (
+                    "getting-started.md",
+                    include_str!("../demo/vellum/data/getting-started.md"),
+                ),
+                (
+                    "technical.md",
+                    include_str!("../demo/vellum/data/technical.md"),
+                ),
+            ],
+            Self::Band => &[("index.md", include_str!("../demo/rockband/data/index.md"))],
+        }
+    }
+
+    /// The `config.toml` for a project built from its own directory.
+    fn config(self) -> String {
+        let mut config = format!(
+            r#"# Vellum - Configuration
+# Paths are relative to the project directory; run `vellum` from there.
+
+[document]
+# Document title (shown in browser tab and header)
+title = "{}"
+
+# Section title(s) that become a dropdown menu (empty string to disable);
+# matched ignoring case, by slug, or as a /regex/
+dropdown = "{}"
+
+[paths]
+markdown = "data/index.md"
+logo = "config/assets/logo.png"
+output = "index.html"
+"#,
+            self.title(),
+            self.dropdown()
+        );
+        let tags = self.colored_tags();
+        if !tags.is_empty() {
+            config.push_str("\n# Regex patterns rendered as colored tags\n[colored_tags]\n");
+            config.push_str(tags);
+        }
+        config
+    }
+}
+
+/// Create a new project in `dir` and return the written files.
+///
+/// Fails without writing anything if `config/config.toml` already exists,
+/// unless `force` is set.
+pub fn init_project(
+    dir: &Path,
+    preset: Preset,
+    force: bool,
+) -> Result<Vec<PathBuf>, ScaffoldError> {
+    let config_path = dir.join("config").join("config.toml");
+    if config_path.exists() && !force {
+        return Err(ScaffoldError::AlreadyExists(
+            config_path.display().to_string(),
+        ));
+    }
+
+    let mut files: Vec<(PathBuf, Vec<u8>)> = vec![(config_path, preset.config().into_bytes())];
+    for (name, content) in defaults::FILES {
+        files.push((
+            dir.join("config").join("assets").join(name),
+            content.to_vec(),
+        ));
+    }
+    for (name, content) in preset.data_files() {
+        files.push((
+            dir.join("data").join(name),
+            strip_external_includes(content).into_bytes(),
+        ));
+    }
+
+    let mut written = Vec::with_capacity(files.len());
+    for (path, content) in files {
+        write_file(&path, &content)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Drop includes pointing outside the project, which would not resolve in a fresh folder.
+fn strip_external_includes(content: &str) -> String {
+    content
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            !(trimmed.starts_with("Include:") && trimmed.contains("(../"))
+        })
+        .map(|line| format!("{}\n", line))
+        .collect()
+}
+
+fn write_file(path: &Path, content: &[u8]) -> Result<(), ScaffoldError> {
+    let to_error = |source| ScaffoldError::WriteError {
+        path: path.display().to_string(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(to_error)?;
+    }
+    std::fs::write(path, content).map_err(to_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_preset() {
+        assert_eq!("band".parse::<Preset>().unwrap(), Preset::Band);
+        assert_eq!("docs".parse::<Preset>().unwrap(), Preset::Documentation);
+        assert!(matches!(
+            "blog".parse::<Preset>(),
+            Err(ScaffoldError::UnknownPreset(_))
+        ));
+    }
+
+    #[test]
+    fn test_config_is_valid_toml() {
+        for preset in [Preset::Portfolio, Preset::Documentation, Preset::Band] {
+            let config: toml::Value = toml::from_str(&preset.config()).unwrap();
+            assert_eq!(config["paths"]["markdown"].as_str(), Some("data/index.md"));
+        }
+        let config: toml::Value = toml::from_str(&Preset::Portfolio.config()).unwrap();
+        assert_eq!(config["colored_tags"]["\\[DONE\\]"].as_str(), Some("green"));
+    }
+
+    #[test]
+    fn test_init_project_writes_files() {
+        let dir = TempDir::new().unwrap();
+        let written = init_project(dir.path(), Preset::Portfolio, false).unwrap();
+
+        assert!(written.contains(
+            &dir.path()
+                .join("config")
+                .join("assets")
+                .join("template.html")
+        ));
+        assert!(dir.path().join("data").join("worklog.md").exists());
+        assert!(matches!(
+            init_project(dir.path(), Preset::Portfolio, false),
+            Err(ScaffoldError::AlreadyExists(_))
+        ));
+        assert!(init_project(dir.path(), Preset::Band, true).is_ok());
+    }
+
+    #[test]
+    fn test_strip_external_includes() {
+        let content = "Intro\nInclude: [a](../../a.md)\nInclude: [b](b.md)\n";
+        assert_eq!(
+            strip_external_includes(content),
+            "Intro\nInclude: [b](b.md)\n"
+        );
+    }
+}
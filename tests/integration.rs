@@ -2,10 +2,14 @@
 //!
 //! These tests use real temporary files to test the full pipeline.
 
-use vellum::{generate_html, generate_html_from_content, validate_inputs, GeneratorConfig, assets::Assets};
+use base64::Engine;
 use std::collections::HashMap;
 use std::fs;
 use tempfile::TempDir;
+use vellum::{
+    assets::Assets, check_output_paths, generate_html, generate_html_from_content,
+    generate_html_with_config, parser::ChartKind, validate_inputs, GeneratorConfig, GeneratorError,
+};
 
 /// Creates a minimal valid PNG file (1x1 transparent pixel).
 fn create_test_png() -> Vec<u8> {
@@ -34,6 +38,7 @@ fn test_assets() -> Assets {
 </body>
 </html>"#
             .to_string(),
+        ..Default::default()
     }
 }
 
@@ -68,6 +73,7 @@ This is a test document.
         output_path: dir.path().join("output.html").to_str().unwrap().to_string(),
         dropdown_section: None,
         colored_tags: HashMap::new(),
+        ..Default::default()
     };
 
     // Validate inputs
@@ -107,6 +113,7 @@ fn test_validate_inputs_missing_markdown() {
         output_path: "output.html".to_string(),
         dropdown_section: None,
         colored_tags: HashMap::new(),
+        ..Default::default()
     };
 
     let result = validate_inputs(&config);
@@ -128,10 +135,18 @@ fn test_validate_inputs_missing_logo() {
         output_path: "output.html".to_string(),
         dropdown_section: None,
         colored_tags: HashMap::new(),
+        ..Default::default()
     };
 
-    let result = validate_inputs(&config);
-    assert!(result.is_err());
+    // A missing logo falls back to the built-in one with a warning
+    assert!(validate_inputs(&config).is_ok());
+    let (html, stats) = generate_html(&config, &test_assets()).unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+    assert!(html_str.contains("data:image/png;base64,iVBORw0KGgo"));
+    assert!(stats
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("Logo not found: ") && w.ends_with("using the default logo")));
 }
 
 #[test]
@@ -151,6 +166,7 @@ fn test_validate_inputs_both_exist() {
         output_path: "output.html".to_string(),
         dropdown_section: None,
         colored_tags: HashMap::new(),
+        ..Default::default()
     };
 
     assert!(validate_inputs(&config).is_ok());
@@ -171,7 +187,15 @@ Some details here.
     let logo_uri = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg==";
     let assets = test_assets();
 
-    let result = generate_html_from_content(markdown, ".", "Test Title", logo_uri, None, &HashMap::new(), &assets);
+    let result = generate_html_from_content(
+        markdown,
+        ".",
+        "Test Title",
+        logo_uri,
+        None,
+        &HashMap::new(),
+        &assets,
+    );
     assert!(result.is_ok());
 
     let (html, stats) = result.unwrap();
@@ -209,7 +233,15 @@ Include: [include]({})
     let logo_uri = "data:image/png;base64,AAAA";
     let assets = test_assets();
 
-    let result = generate_html_from_content(&markdown, ".", "Test", logo_uri, None, &HashMap::new(), &assets);
+    let result = generate_html_from_content(
+        &markdown,
+        ".",
+        "Test",
+        logo_uri,
+        None,
+        &HashMap::new(),
+        &assets,
+    );
     assert!(result.is_ok());
 
     let (html, stats) = result.unwrap();
@@ -226,13 +258,66 @@ fn test_generate_html_from_content_empty_document() {
     let logo_uri = "data:image/png;base64,AAAA";
     let assets = test_assets();
 
-    let result = generate_html_from_content(markdown, ".", "Empty", logo_uri, None, &HashMap::new(), &assets);
+    let result = generate_html_from_content(
+        markdown,
+        ".",
+        "Empty",
+        logo_uri,
+        None,
+        &HashMap::new(),
+        &assets,
+    );
     assert!(result.is_ok());
 
     let (html, stats) = result.unwrap();
     assert!(!html.is_empty()); // Should still produce valid HTML
     assert_eq!(stats.source_lines, 0);
     assert_eq!(stats.section_count, 0);
+    assert_eq!(stats.warnings, ["The document has no content"]);
+}
+
+#[test]
+fn test_generate_html_from_content_without_sections() {
+    let markdown = "# Notes\n\nA short note without sections.\n";
+    let (html, stats) = generate_html_from_content(
+        markdown,
+        ".",
+        "Notes",
+        "",
+        None,
+        &HashMap::new(),
+        &test_assets(),
+    )
+    .unwrap();
+    let html = String::from_utf8(html).unwrap();
+
+    assert_eq!(stats.section_count, 1);
+    assert!(html.contains("<button data-panel=\"introduction\">Introduction</button>"));
+    assert!(html.contains("<p>A short note without sections.</p>"));
+    assert!(stats.warnings[0].contains("no '## ' sections"));
+}
+
+#[test]
+fn test_output_must_not_overwrite_sources() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(&md_path, "## About\n\nInclude: [Notes](notes.md)\n").unwrap();
+    fs::write(dir.path().join("notes.md"), "# Notes\n").unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+
+    let config = GeneratorConfig {
+        markdown_path: md_path.to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    let other = dir.path().join("sub").join("..").join("notes.md");
+    let result = check_output_paths(&config, &[dir.path().join("index.html"), other]);
+    assert!(matches!(
+        result,
+        Err(GeneratorError::OutputOverwritesSource(_, ref source)) if source.ends_with("notes.md")
+    ));
+
+    assert!(check_output_paths(&config, &[md_path]).is_err());
+    assert!(check_output_paths(&config, &[dir.path().join("index.flat.md")]).is_ok());
 }
 
 #[test]
@@ -244,6 +329,7 @@ fn test_generate_html_missing_file() {
         output_path: "output.html".to_string(),
         dropdown_section: None,
         colored_tags: HashMap::new(),
+        ..Default::default()
     };
     let assets = test_assets();
 
@@ -276,3 +362,1014 @@ output = "out/index.html"
     assert_eq!(config.output_path, "out/index.html");
     assert_eq!(config.dropdown_section, Some("Custom Dropdown".to_string()));
 }
+
+#[test]
+fn test_config_skill_matrix_chart() {
+    let dir = TempDir::new().unwrap();
+
+    let config_path = dir.path().join("config.toml");
+    let config_content = r#"
+[document]
+title = "Charts"
+
+[paths]
+markdown = "test.md"
+logo = "logo.png"
+output = "out/index.html"
+
+[skill_matrix]
+chart = "radar"
+chart_only = true
+"#;
+    fs::write(&config_path, config_content).unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.skill_matrix.chart, ChartKind::Radar);
+    assert!(config.skill_matrix.chart_only);
+}
+
+#[test]
+fn test_config_skill_matrix_detection() {
+    let dir = TempDir::new().unwrap();
+
+    let config_path = dir.path().join("config.toml");
+    let config_content = r#"
+[document]
+title = "Detection"
+
+[paths]
+markdown = "test.md"
+logo = "logo.png"
+output = "out/index.html"
+
+[skill_matrix]
+detect_headings = false
+value_columns = ["stufe"]
+"#;
+    fs::write(&config_path, config_content).unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert!(!config.skill_matrix.detect_headings);
+    assert_eq!(config.skill_matrix.value_columns, vec!["stufe".to_string()]);
+    // Lists that are not configured keep their defaults
+    assert!(config
+        .skill_matrix
+        .skill_columns
+        .contains(&"skill".to_string()));
+
+    let markdown = "## Soft skills reflection\n\n| Skill | Stufe |\n|---|---|\n| Listening | 6 |\n\n## Tech\n\n<!-- vellum:skill-matrix -->\n| Skill | Stufe |\n|---|---|\n| Rust | 8 |\n";
+    let (html, _) = generate_html_with_config(
+        markdown,
+        ".",
+        "data:image/png;base64,AAAA",
+        &config,
+        &test_assets(),
+    )
+    .unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+    assert_eq!(html_str.matches("skill-matrix-container").count(), 1);
+    assert!(html_str.contains("level-8"));
+    assert!(!html_str.contains("achievement-marker"));
+}
+
+#[test]
+fn test_team_matrix_from_config() {
+    let dir = TempDir::new().unwrap();
+
+    let alice_path = dir.path().join("alice.md");
+    fs::write(
+        &alice_path,
+        "## Skills\n\n| Skill | Level |\n|---|---|\n| Rust | 9 |\n| Go | 4 |\n",
+    )
+    .unwrap();
+    let bob_path = dir.path().join("bob.md");
+    fs::write(
+        &bob_path,
+        "## Skills\n\n| Skill | Level |\n|---|---|\n| Rust | 6 |\n",
+    )
+    .unwrap();
+
+    let config_path = dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[document]
+title = "Team"
+
+[paths]
+markdown = "test.md"
+logo = "logo.png"
+output = "out/index.html"
+
+[skill_matrix.team]
+threshold = 7
+members = [
+    {{ name = "Alice", skills = "{}" }},
+    {{ name = "Bob", skills = "{}" }},
+]
+"#,
+        alice_path.to_str().unwrap(),
+        bob_path.to_str().unwrap()
+    );
+    fs::write(&config_path, config_content).unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    let markdown = "## Team\n\n<!-- vellum:team-matrix -->\n";
+    let (html, stats) = generate_html_with_config(
+        markdown,
+        ".",
+        "data:image/png;base64,AAAA",
+        &config,
+        &test_assets(),
+    )
+    .unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+
+    assert!(html_str.contains("team-matrix"));
+    assert!(html_str.contains("<th>Alice</th><th>Bob</th>"));
+    assert!(stats
+        .warnings
+        .iter()
+        .any(|w| w == "Skill 'Go' is missing for: Bob"));
+    assert!(stats
+        .warnings
+        .iter()
+        .any(|w| w.contains("Nobody reaches level 7 in: Go")));
+}
+
+#[test]
+fn test_theme_from_config_with_project_override() {
+    let dir = TempDir::new().unwrap();
+
+    let config_path = dir.path().join("config.toml");
+    let config_content = r#"
+[document]
+title = "Themed"
+theme = "sepia"
+
+[paths]
+markdown = "test.md"
+logo = "logo.png"
+output = "out/index.html"
+"#;
+    fs::write(&config_path, config_content).unwrap();
+    fs::create_dir_all(dir.path().join("assets")).unwrap();
+    fs::write(
+        dir.path().join("assets").join("script.js"),
+        "console.log('custom');",
+    )
+    .unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.theme, Some("sepia".to_string()));
+
+    let assets = Assets::load_with_theme(dir.path(), config.theme.as_deref()).unwrap();
+    assert!(assets.styles.contains("Sepia Theme"));
+    assert_eq!(assets.script, "console.log('custom');");
+
+    let (html, _) = generate_html_with_config(
+        "## About\n\nHello",
+        ".",
+        "data:image/png;base64,AAAA",
+        &config,
+        &assets,
+    )
+    .unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+    assert!(html_str.contains("Sepia Theme"));
+    assert!(html_str.contains("console.log('custom');"));
+}
+
+#[test]
+fn test_assets_fall_back_to_builtin_defaults() {
+    let dir = TempDir::new().unwrap();
+    fs::create_dir_all(dir.path().join("assets")).unwrap();
+    fs::write(
+        dir.path().join("assets").join("style.css"),
+        "body { color: red; }",
+    )
+    .unwrap();
+
+    let assets = Assets::load(dir.path()).unwrap();
+    assert_eq!(assets.styles, "body { color: red; }");
+    assert!(assets.template.contains("{{content}}"));
+    assert_eq!(assets.defaults_used, vec!["script.js", "template.html"]);
+
+    assert!(Assets::load_with_theme(dir.path(), Some("nonexistent")).is_err());
+}
+
+#[test]
+fn test_builtin_logo_when_not_configured() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("test.md");
+    fs::write(&md_path, "## About\n\nHello").unwrap();
+
+    let config_path = dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[document]
+title = "No Logo"
+
+[paths]
+markdown = "{}"
+output = "out/index.html"
+"#,
+        md_path.to_str().unwrap()
+    );
+    fs::write(&config_path, config_content).unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert!(config.logo_path.is_empty());
+    assert!(validate_inputs(&config).is_ok());
+
+    let (html, _) = generate_html(&config, &test_assets()).unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+    assert!(html_str.contains("data:image/png;base64,iVBORw0KGgo"));
+
+    // A theme's logo comes before the built-in one
+    let theme_dir = dir.path().join("themes").join("brand");
+    fs::create_dir_all(&theme_dir).unwrap();
+    fs::write(theme_dir.join("logo.png"), create_test_png()).unwrap();
+    let assets = Assets::load_with_theme(dir.path(), Some("brand")).unwrap();
+    let (html, _) = generate_html(&config, &assets).unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+    assert!(html_str.contains(&format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(create_test_png())
+    )));
+}
+
+#[test]
+fn test_local_images_embedded_from_included_files() {
+    let dir = TempDir::new().unwrap();
+    let chapter_dir = dir.path().join("chapters");
+    fs::create_dir_all(chapter_dir.join("img")).unwrap();
+    fs::write(
+        chapter_dir.join("img").join("arch.png"),
+        [0x89, b'P', b'N', b'G'],
+    )
+    .unwrap();
+    fs::write(dir.path().join("cover image.gif"), b"GIF89a").unwrap();
+    fs::write(
+        chapter_dir.join("design.md"),
+        "# Design\n\n![diagram](img/arch.png)\n\n![remote](https://example.com/a.png)\n",
+    )
+    .unwrap();
+
+    let markdown = "## Overview\n\n![cover](cover%20image.gif)\n![gone](missing.png)\n\n## Design\n\nInclude: [design](chapters/design.md)\n";
+    let config = GeneratorConfig::default();
+    let (html, stats) = generate_html_with_config(
+        markdown,
+        dir.path().to_str().unwrap(),
+        "data:image/png;base64,AAAA",
+        &config,
+        &test_assets(),
+    )
+    .unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+
+    assert_eq!(stats.images.len(), 2);
+    assert!(html_str.contains("src=\"data:image/png;base64,iVBORw==\""));
+    assert!(html_str.contains("src=\"data:image/gif;base64,"));
+    assert!(html_str.contains("src=\"https://example.com/a.png\""));
+    assert!(stats
+        .warnings
+        .iter()
+        .any(|w| w == "Remote image not embedded: https://example.com/a.png"));
+    assert!(stats
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("Image not found:") && w.ends_with("missing.png")));
+}
+
+fn write_test_png(path: &std::path::Path, width: u32, height: u32) {
+    let img = image::RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([(x % 256) as u8, (y % 256) as u8, 64])
+    });
+    img.save(path).unwrap();
+}
+
+#[test]
+fn test_images_downscaled_per_config() {
+    let dir = TempDir::new().unwrap();
+    write_test_png(&dir.path().join("shot.png"), 800, 400);
+
+    let config_path = dir.path().join("config.toml");
+    let config_content = r#"
+[document]
+title = "Images"
+
+[paths]
+markdown = "test.md"
+output = "out/index.html"
+
+[assets]
+max_image_width = 200
+"#;
+    fs::write(&config_path, config_content).unwrap();
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.images.max_image_width, Some(200));
+
+    let markdown = "## Screens\n\n![shot](shot.png)\n";
+    let (_, stats) = generate_html_with_config(
+        markdown,
+        dir.path().to_str().unwrap(),
+        "data:image/png;base64,AAAA",
+        &config,
+        &test_assets(),
+    )
+    .unwrap();
+
+    let report = &stats.images[0];
+    assert_eq!(report.path, "shot.png");
+    assert!(report.optimized_size < report.original_size);
+}
+
+#[test]
+fn test_image_budget_exceeded() {
+    let dir = TempDir::new().unwrap();
+    write_test_png(&dir.path().join("shot.png"), 64, 64);
+
+    let config = GeneratorConfig {
+        images: vellum::assets::ImageOptions {
+            max_embed_bytes: Some(100),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let markdown = "## Screens\n\n![shot](shot.png)\n";
+    let result = generate_html_with_config(
+        markdown,
+        dir.path().to_str().unwrap(),
+        "data:image/png;base64,AAAA",
+        &config,
+        &test_assets(),
+    );
+
+    assert!(matches!(
+        result,
+        Err(vellum::GeneratorError::ImageBudgetExceeded { limit: 100, .. })
+    ));
+}
+
+#[test]
+fn test_logo_sniffed_and_used_as_favicon() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("test.md");
+    let logo_path = dir.path().join("logo.jpg");
+    fs::write(&md_path, "## About\n\nHello").unwrap();
+    fs::write(&logo_path, b"GIF89a").unwrap();
+
+    let config = GeneratorConfig {
+        markdown_path: md_path.to_str().unwrap().to_string(),
+        logo_path: logo_path.to_str().unwrap().to_string(),
+        ..Default::default()
+    };
+    let assets = Assets::load(dir.path()).unwrap();
+    let (html, stats) = generate_html(&config, &assets).unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+
+    assert!(html_str.contains("<link rel=\"icon\" href=\"data:image/gif;base64,"));
+    assert!(stats
+        .warnings
+        .iter()
+        .any(|w| w.contains(".jpg extension but contains image/gif")));
+}
+
+#[test]
+fn test_fonts_and_attachments_from_config() {
+    let dir = TempDir::new().unwrap();
+    let font_path = dir.path().join("corp.woff");
+    let cv_path = dir.path().join("cv.pdf");
+    fs::write(&font_path, b"wOFF\x00\x01\x00\x00").unwrap();
+    fs::write(&cv_path, b"%PDF-1.4 cv").unwrap();
+
+    let config_path = dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[document]
+title = "Files"
+
+[paths]
+markdown = "test.md"
+output = "out/index.html"
+
+[assets]
+max_image_width = 1200
+fonts = [{{ family = "Corp", path = "{}", selector = "body" }}]
+attachments = [{{ name = "cv", path = "{}" }}]
+"#,
+        font_path.to_str().unwrap(),
+        cv_path.to_str().unwrap()
+    );
+    fs::write(&config_path, config_content).unwrap();
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.images.max_image_width, Some(1200));
+
+    let markdown = "## About\n\n[Download my *CV*](attachment:cv) and [old](attachment:old)\n";
+    let (html, stats) = generate_html_with_config(
+        markdown,
+        ".",
+        "data:image/png;base64,AAAA",
+        &config,
+        &test_assets(),
+    )
+    .unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+
+    assert!(html_str.contains("@font-face"));
+    assert!(html_str.contains("url(\"data:font/woff;base64,"));
+    assert!(html_str.contains("<a class=\"attachment\" href=\"data:application/pdf;base64,"));
+    assert!(html_str.contains("download=\"cv.pdf\">Download my <em>CV</em></a>"));
+    assert_eq!(stats.fonts_embedded, 1);
+    assert_eq!(stats.attachments_embedded, 1);
+    assert!(stats
+        .warnings
+        .contains(&"Unknown attachment 'old'".to_string()));
+}
+
+#[test]
+fn test_header_without_logo() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("test.md");
+    fs::write(&md_path, "## About\n\nHello").unwrap();
+
+    let config_path = dir.path().join("config.toml");
+    let config_content = format!(
+        r#"
+[document]
+title = "Internal Docs"
+
+[paths]
+markdown = "{}"
+logo = "does-not-exist.png"
+output = "out/index.html"
+
+[header]
+logo = false
+subtitle = "Platform & Tools"
+author = "Platform Team"
+links = [{{ label = "Wiki", url = "https://wiki.example.com" }}]
+"#,
+        md_path.to_str().unwrap()
+    );
+    fs::write(&config_path, config_content).unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert!(!config.header.show_logo);
+    assert!(validate_inputs(&config).is_ok());
+
+    let assets = Assets::load(dir.path()).unwrap();
+    let (html, _) = generate_html(&config, &assets).unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+
+    assert!(!html_str.contains("<img"));
+    assert!(!html_str.contains("rel=\"icon\""));
+    assert!(html_str.contains("<p class=\"header-subtitle\">Platform &amp; Tools</p>"));
+    assert!(html_str.contains("<p class=\"header-author\">Platform Team</p>"));
+    assert!(html_str.contains("<a href=\"https://wiki.example.com\">Wiki</a>"));
+}
+
+#[test]
+fn test_header_wordmark_and_svg_logo() {
+    let dir = TempDir::new().unwrap();
+    let svg_path = dir.path().join("logo.svg");
+    fs::write(&svg_path, "<?xml version=\"1.0\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\"><circle r=\"4\"/></svg>\n").unwrap();
+
+    let mut config = GeneratorConfig::default();
+    config.header.logo_svg = Some(svg_path.to_str().unwrap().to_string());
+    let assets = Assets::load(dir.path()).unwrap();
+    let (html, _) = generate_html_with_config(
+        "## A\n\nB",
+        ".",
+        "data:image/png;base64,AAAA",
+        &config,
+        &assets,
+    )
+    .unwrap();
+    let html_str = String::from_utf8(html).unwrap();
+    assert!(html_str.contains("<span class=\"logo-svg\"><svg xmlns=\"http://www.w3.org/2000/svg\"><circle r=\"4\"/></svg></span>"));
+    assert!(!html_str.contains("<?xml"));
+
+    config.header.wordmark = Some("ACME".to_string());
+    let (html, _) = generate_html_with_config(
+        "## A\n\nB",
+        ".",
+        "data:image/png;base64,AAAA",
+        &config,
+        &assets,
+    )
+    .unwrap();
+    assert!(String::from_utf8(html)
+        .unwrap()
+        .contains("<span class=\"wordmark\">ACME</span>"));
+}
+
+#[test]
+fn test_compressed_output_expands_to_uncompressed_render() {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use std::io::Read;
+
+    let markdown = "## Worklog\n\n".to_string()
+        + &"- 2024-01-01: Reviewed the release checklist\n".repeat(1000)
+        + "\n## Notes\n\nShort notes with `code` and **bold** text.\n";
+    let assets = Assets::load(std::path::Path::new("config")).unwrap();
+    let mut config = GeneratorConfig {
+        title: "Worklog".to_string(),
+        ..Default::default()
+    };
+
+    let (plain, _) = generate_html_with_config(&markdown, ".", "", &config, &assets).unwrap();
+    config.output.compress = true;
+    let (compressed, _) = generate_html_with_config(&markdown, ".", "", &config, &assets).unwrap();
+    let plain = String::from_utf8(plain).unwrap();
+    let compressed = String::from_utf8(compressed).unwrap();
+    assert!(compressed.len() < plain.len() / 2);
+    assert!(!compressed.contains("Reviewed the release checklist"));
+
+    // Cut out the self-extracting script and decompress its payload
+    let start = compressed
+        .find("<script>\n/**\n * Vellum - Self-extracting")
+        .unwrap();
+    let noscript_end = "</noscript>";
+    let end = compressed[start..].find(noscript_end).unwrap() + start + noscript_end.len();
+    let script = &compressed[start..end];
+    let payload_start = script.find("})(\"").unwrap() + 4;
+    let payload_end = script[payload_start..].find('"').unwrap() + payload_start;
+    let gzipped = STANDARD
+        .decode(&script[payload_start..payload_end])
+        .unwrap();
+    let mut panels = String::new();
+    flate2::read::GzDecoder::new(gzipped.as_slice())
+        .read_to_string(&mut panels)
+        .unwrap();
+
+    let expanded = format!("{}{}{}", &compressed[..start], panels, &compressed[end..]);
+    assert_eq!(expanded, plain);
+}
+
+#[test]
+fn test_pdf_output_with_bookmarks_and_skill_colours() {
+    use std::io::Read;
+
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(
+        &md_path,
+        "## About\n\nHello <! milestone\n\n## Skills\n\n| Skill | Level |\n|-------|-------|\n| Rust | 9 |\n\n\
+         ## Projects\n\n### Vellum\n\nA generator.\n",
+    )
+    .unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[document]
+title = "Portfolio"
+dropdown = "Projects"
+
+[paths]
+markdown = "{}"
+output = "out/index.pdf"
+
+[header]
+logo = false
+
+[output]
+format = "pdf"
+"#,
+            md_path.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.output.format, vellum::OutputFormat::Pdf);
+    let (output, stats) = vellum::generate(&config, &Assets::default()).unwrap();
+    let pdf = output.into_file().unwrap();
+    assert!(pdf.starts_with(b"%PDF-1.4"));
+    assert_eq!(stats.section_count, 3);
+
+    // Nav titles become bookmarks, the dropdown items nested under their section
+    let text = String::from_utf8_lossy(&pdf);
+    let title = |t: &str| {
+        let hex: String = t.encode_utf16().map(|u| format!("{:04X}", u)).collect();
+        format!("/Title <FEFF{}>", hex)
+    };
+    for bookmark in ["About", "Skills", "Projects", "Vellum"] {
+        assert!(
+            text.contains(&title(bookmark)),
+            "missing bookmark {}",
+            bookmark
+        );
+    }
+
+    // Page content: all panels in sequence, skill level colour carried over
+    let mut content = String::new();
+    let mut rest = &pdf[..];
+    while let Some(start) = rest.windows(7).position(|w| w == b"stream\n") {
+        let body = &rest[start + 7..];
+        let mut decoded = String::new();
+        if flate2::read::ZlibDecoder::new(body)
+            .read_to_string(&mut decoded)
+            .is_ok()
+        {
+            content.push_str(&decoded);
+        }
+        rest = &body[1..];
+    }
+    for expected in [
+        "(About) Tj",
+        "(Skills) Tj",
+        "(Vellum) Tj",
+        "(A generator.) Tj",
+        "(milestone) Tj",
+    ] {
+        assert!(content.contains(expected), "missing {}", expected);
+    }
+    assert!(content.contains("0.69 0.87 0.73 rg"));
+}
+
+#[test]
+fn test_print_layout_from_config() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(&md_path, "## About\n\nHello\n\n## Contact\n\nMail\n").unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[document]
+title = "Portfolio"
+
+[paths]
+markdown = "{}"
+output = "out/index.html"
+
+[header]
+logo = false
+
+[output]
+layout = "print"
+"#,
+            md_path.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.output.layout, vellum::renderer::PageLayout::Print);
+    let (output, _) = vellum::generate(&config, &test_assets()).unwrap();
+    let html = String::from_utf8(output.into_file().unwrap()).unwrap();
+
+    assert!(html.contains("<nav class=\"toc\">"));
+    assert!(html.contains("<li><a href=\"#panel-contact\">Contact</a></li>"));
+    assert!(html.contains("break-before: page"));
+    assert!(!html.contains("class=\"panel hidden\""));
+}
+
+#[test]
+fn test_navigation_styles_from_config() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(
+        &md_path,
+        "## Guides\n\n### Install\n\nSteps\n\n#### Linux\n\napt\n\n## About\n\nMe\n\n## Projects\n\n### Vellum\n\nV\n\n## Archive\n\n### 2023\n\nOld\n",
+    )
+    .unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[document]
+title = "Docs"
+dropdown = "Projects"
+
+[document.navigation]
+"Guides" = "group"
+"Archive" = "dropdown"
+
+[paths]
+markdown = "{}"
+output = "out/index.html"
+
+[header]
+logo = false
+"#,
+            md_path.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    let (html, stats) = generate_html(&config, &test_assets()).unwrap();
+    let html = String::from_utf8(html).unwrap();
+
+    assert_eq!(stats.section_count, 4);
+    assert!(html.contains("<summary>Guides</summary>"));
+    assert!(html.contains("<button data-panel=\"install\">Install</button>"));
+    assert!(html.contains("<select class=\"nav-dropdown\" id=\"dropdown-projects\">"));
+    assert!(html.contains("<select class=\"nav-dropdown\" id=\"dropdown-archive\">"));
+    assert!(html.contains("id=\"panel-2023\""));
+    assert!(html.contains("<h3 id=\"linux\">Linux <a class=\"heading-anchor\" href=\"#linux\""));
+}
+
+#[test]
+fn test_dropdown_sections_matched_loosely() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(
+        &md_path,
+        "## My projects \n\n### Vellum\n\nV\n\n## Archive 2023\n\n### Q1\n\nOld\n\n## About\n\nMe\n",
+    )
+    .unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[document]
+title = "Docs"
+dropdown = ["my-projects", "/^Archive \\d+$/", "Talks"]
+
+[paths]
+markdown = "{}"
+output = "out/index.html"
+
+[header]
+logo = false
+"#,
+            md_path.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert_eq!(config.dropdown_section, Some("my-projects".to_string()));
+
+    let (html, stats) = generate_html(&config, &test_assets()).unwrap();
+    let html = String::from_utf8(html).unwrap();
+
+    assert!(html.contains("<select class=\"nav-dropdown\" id=\"dropdown-my-projects\">"));
+    assert!(html.contains("<select class=\"nav-dropdown\" id=\"dropdown-archive-2023\">"));
+    assert_eq!(
+        stats.warnings,
+        ["No '## ' heading matches the dropdown section 'Talks'; it is shown as usual"]
+    );
+}
+
+#[test]
+fn test_alternative_formats_from_the_same_document() {
+    use vellum::renderer::RenderOutput;
+    use vellum::OutputFormat;
+
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(
+        &md_path,
+        "## About\n\nHello <! milestone\n\n## Projects\n\n### Vellum\n\nA generator.\n",
+    )
+    .unwrap();
+    let mut config = GeneratorConfig {
+        markdown_path: md_path.to_str().unwrap().to_string(),
+        dropdown_section: Some("Projects".to_string()),
+        ..Default::default()
+    };
+    config.header.show_logo = false;
+
+    config.output.format = OutputFormat::Pages;
+    let (output, stats) = vellum::generate(&config, &test_assets()).unwrap();
+    let RenderOutput::Files(ref files) = output else {
+        panic!("pages should be several files");
+    };
+    let paths: Vec<&str> = files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, ["index.html", "about.html", "vellum.html"]);
+    assert_eq!(stats.html_content_size, output.len());
+
+    config.output.format = OutputFormat::Markdown;
+    let (output, _) = vellum::generate(&config, &test_assets()).unwrap();
+    let markdown = String::from_utf8(output.into_file().unwrap()).unwrap();
+    assert!(markdown.starts_with("# My Portfolio\n\n## About\n"));
+    assert!(markdown.contains("## Projects\n\n### Vellum\n\nA generator.\n"));
+    assert!(markdown.contains("achievement-marker"));
+
+    config.output.format = OutputFormat::Epub;
+    let (output, _) = vellum::generate(&config, &test_assets()).unwrap();
+    let epub = output.into_file().unwrap();
+    assert!(epub.starts_with(b"PK\x03\x04"));
+    assert_eq!(&epub[30..58], b"mimetypeapplication/epub+zip");
+}
+
+#[test]
+fn test_json_export_from_config() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(
+        dir.path().join("worklog.md"),
+        "Shipped it <! first release\n",
+    )
+    .unwrap();
+    fs::write(
+        &md_path,
+        "## Worklog\n\nInclude: (worklog.md)\n\n## Projects\n\n### Vellum\n\nA generator.\n",
+    )
+    .unwrap();
+    let mut config = GeneratorConfig {
+        markdown_path: md_path.to_str().unwrap().to_string(),
+        dropdown_section: Some("Projects".to_string()),
+        ..Default::default()
+    };
+    config.header.show_logo = false;
+
+    let (json, stats) = vellum::export::export_json(&config).unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(value["schema_version"], 2);
+    assert_eq!(value["navigation"][0]["id"], "worklog");
+    assert_eq!(value["navigation"][0]["style"], "button");
+    assert_eq!(value["navigation"][1]["title"], "Projects");
+    assert_eq!(value["navigation"][1]["style"], "dropdown");
+    assert_eq!(value["navigation"][1]["items"][0]["title"], "Vellum");
+    assert_eq!(value["panels"][0]["achievements"][0], "first release");
+    assert!(value["panels"][1]["html"]
+        .as_str()
+        .unwrap()
+        .contains("<p>A generator.</p>"));
+    assert_eq!(value["stats"]["expanded_lines"], stats.expanded_lines);
+}
+
+#[test]
+fn test_search_index_from_config() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(
+        &md_path,
+        "## About\n\nMe\n\n## Worklog\n\n### March\n\nMigrated the `</script>` parser\n",
+    )
+    .unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[document]
+title = "Docs"
+
+[paths]
+markdown = "{}"
+output = "out/index.html"
+
+[header]
+logo = false
+
+[output]
+search = true
+minify = true
+"#,
+            md_path.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    assert!(config.output.search);
+    let (html, _) = generate_html(&config, &test_assets()).unwrap();
+    let html = String::from_utf8(html).unwrap();
+
+    assert!(html.contains("id=\"search-input\""));
+    assert!(html.contains("[\"march\",\"Worklog\",\"March\",\"Migrated the <\\/script> parser\"]"));
+    assert!(html.contains("SEARCH_INDEX"));
+    assert!(html.contains(".search-results"));
+}
+
+#[test]
+fn test_tables_of_contents_from_config() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(
+        &md_path,
+        "## Goals\n\n### Q1\n\nA\n\n### Q2\n\nB\n\n## About\n\n### Me\n\nC\n",
+    )
+    .unwrap();
+    let config_path = dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            r#"
+[document]
+title = "Docs"
+
+[paths]
+markdown = "{}"
+output = "out/index.html"
+
+[header]
+logo = false
+
+[toc]
+panels = true
+min_headings = 2
+global = true
+title = "Overview"
+"#,
+            md_path.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let config = GeneratorConfig::from_file(&config_path).unwrap();
+    let (html, stats) = generate_html(&config, &test_assets()).unwrap();
+    let html = String::from_utf8(html).unwrap();
+
+    // The contents panel comes first and is not counted as a section
+    assert_eq!(stats.section_count, 2);
+    assert!(
+        html.find("<button data-panel=\"overview\">Overview</button>")
+            < html.find("data-panel=\"goals\"")
+    );
+    assert!(html.contains("<nav class=\"document-toc\">"));
+    assert!(html.contains("<li><a href=\"#panel-about\">About</a>"));
+    assert_eq!(html.matches("<nav class=\"panel-toc\">").count(), 1);
+    assert!(html.contains("                <li><a href=\"#me\">Me</a></li>"));
+
+    // The preamble's panel stays first, so it is still the one shown on load
+    fs::write(&md_path, "# Docs\n\nWelcome\n\n## About\n\nMe\n").unwrap();
+    let (html, _) = generate_html(&config, &test_assets()).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    let order: Vec<usize> = ["introduction", "overview", "about"]
+        .iter()
+        .map(|id| {
+            html.find(&format!("<button data-panel=\"{}\"", id))
+                .unwrap()
+        })
+        .collect();
+    assert!(order.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // Other formats drop the markers
+    let mut config = config;
+    config.output.format = vellum::OutputFormat::Markdown;
+    let (output, stats) = vellum::generate(&config, &test_assets()).unwrap();
+    let markdown = String::from_utf8(output.into_file().unwrap()).unwrap();
+    assert!(!markdown.contains("[[toc"));
+    assert!(!markdown.contains("Overview"));
+    assert!(stats
+        .warnings
+        .iter()
+        .any(|w| w.contains("only HTML output gets a Contents panel")));
+}
+
+#[test]
+fn test_preamble_policy_from_config() {
+    let dir = TempDir::new().unwrap();
+    let md_path = dir.path().join("index.md");
+    fs::write(&md_path, "# Docs\n\nRead me *first*.\n\n## About\n\nMe\n").unwrap();
+    let config_path = dir.path().join("config.toml");
+    let write_config = |preamble: &str| {
+        fs::write(
+            &config_path,
+            format!(
+                r#"
+[document]
+title = "Docs"
+preamble = "{}"
+preamble_title = "Welcome"
+
+[paths]
+markdown = "{}"
+output = "out/index.html"
+
+[header]
+logo = false
+"#,
+                preamble,
+                md_path.to_str().unwrap()
+            ),
+        )
+        .unwrap();
+        GeneratorConfig::from_file(&config_path).unwrap()
+    };
+    let template_assets = Assets {
+        template: "<header>{% if intro %}<div class=\"intro\">{{intro}}</div>{% endif %}</header><nav>{{nav_buttons}}</nav><main>{{content}}</main>".to_string(),
+        ..Default::default()
+    };
+
+    let (html, stats) = generate_html(&write_config("panel"), &template_assets).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.contains("<button data-panel=\"welcome\">Welcome</button>"));
+    assert!(html.contains("<header></header>"));
+    assert_eq!(stats.section_count, 2);
+    assert!(stats.warnings.is_empty());
+
+    let (html, stats) = generate_html(&write_config("header"), &template_assets).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(html.contains("<div class=\"intro\"><p>Read me <em>first</em>.</p>\n</div>"));
+    assert!(!html.contains("data-panel=\"welcome\""));
+    assert_eq!(stats.section_count, 1);
+
+    let (html, stats) = generate_html(&write_config("drop"), &template_assets).unwrap();
+    let html = String::from_utf8(html).unwrap();
+    assert!(!html.contains("Read me"));
+    assert_eq!(stats.warnings.len(), 1);
+    assert!(stats.warnings[0].contains("dropped"));
+}